@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{AnaLog, AnaTracker};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = AnaLog::parse(data) {
+        for group in log.groups() {
+            group.desc.state();
+            group.nsids().count();
+        }
+        let mut tracker = AnaTracker::new();
+        tracker.update(&log, false);
+        tracker.update(&log, true);
+        let _ = serde_json::to_string(&log);
+    }
+});
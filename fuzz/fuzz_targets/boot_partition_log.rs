@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{BootPartitionLog, FromBytes};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = BootPartitionLog::from_bytes(data) {
+        log.full_len();
+        log.bpinfo.read_status();
+        let _ = serde_json::to_string(log);
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{MediaUnitStatusLog, SuppCapConfigList};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = MediaUnitStatusLog::parse(data) {
+        for unit in log.units() {
+            unit.chanids().count();
+        }
+        let _ = serde_json::to_string(&log);
+    }
+    if let Ok(list) = SuppCapConfigList::parse(data) {
+        for config in list.configs() {
+            config.total_cap();
+            for eg in config.endur_grps() {
+                eg.nvmsetids().count();
+                eg.muids().count();
+            }
+        }
+        let _ = serde_json::to_string(&list);
+    }
+});
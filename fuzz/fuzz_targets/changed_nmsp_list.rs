@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{ChangedNmspList, FromBytes, NmspInventory};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(list) = ChangedNmspList::from_bytes(data) {
+        list.overflowed();
+        list.is_full();
+        let inventory: NmspInventory = list.nsids().step_by(2).collect();
+        inventory.diff(list, list.nsids().skip(1).step_by(3));
+        let _ = serde_json::to_string(list);
+    }
+});
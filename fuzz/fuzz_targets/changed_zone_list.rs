@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{ChangedZoneList, FromBytes};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(list) = ChangedZoneList::from_bytes(data) {
+        let _ = list.zids().count();
+        let _ = serde_json::to_string(list);
+    }
+});
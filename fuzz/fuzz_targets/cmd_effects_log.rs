@@ -0,0 +1,20 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{CmdEffectsLog, FromBytes, MiCmdEffectsLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = CmdEffectsLog::from_bytes(data) {
+        for (_, effects) in log.supported_admin().chain(log.supported_io()) {
+            effects.cse();
+            effects.nmsp_rescan_required();
+        }
+        let _ = serde_json::to_string(log);
+    }
+    if let Ok(log) = MiCmdEffectsLog::from_bytes(data) {
+        for (_, effects) in log.supported() {
+            effects.has_effects();
+            effects.nmsp_rescan_required();
+        }
+        let _ = serde_json::to_string(log);
+    }
+});
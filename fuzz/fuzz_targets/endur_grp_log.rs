@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{EndurGrpLog, FromBytes};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = EndurGrpLog::from_bytes(data) {
+        log.has_crit_warning();
+        log.crit_warning();
+        let _ = serde_json::to_string(log);
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{ErrLogEntry, FromBytes};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(errs) = <[ErrLogEntry]>::from_bytes(data) {
+        for entry in errs {
+            let status_field = entry.status_field;
+            status_field.status_code();
+        }
+        let _ = serde_json::to_string(errs);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::EventAggregate;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = EventAggregate::parse(data) {
+        log.ids().count();
+        log.full_len();
+        log.is_complete();
+        let _ = serde_json::to_string(&log);
+    }
+});
@@ -0,0 +1,30 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FdpConfigLog, FdpEventsLog, FromBytes, RuhStatus, RuhUsageLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = FdpConfigLog::parse(data) {
+        for config in log.configs() {
+            config.ruhs.iter().for_each(|ruh| {
+                ruh.ruht();
+            });
+            let (rgid, phndl) = config.desc.split_pid(0xffff);
+            config.desc.pid(rgid, phndl);
+        }
+        let _ = serde_json::to_string(&log);
+    }
+    if let Ok(log) = RuhUsageLog::parse(data) {
+        let _ = serde_json::to_string(&log);
+    }
+    if let Ok(status) = RuhStatus::parse(data) {
+        status.is_complete();
+        let _ = serde_json::to_string(&status);
+    }
+    if let Ok(log) = FdpEventsLog::from_bytes(data) {
+        for event in log.events() {
+            event.etype();
+            event.media_realloc();
+        }
+        let _ = serde_json::to_string(log);
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, FwSlotLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(fw_log) = FwSlotLog::from_bytes(data) {
+        for slot in 0..7 {
+            fw_log.get_slot(slot);
+        }
+        let _ = serde_json::to_string(fw_log);
+    }
+});
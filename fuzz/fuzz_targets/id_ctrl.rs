@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, IdCtrl};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(id_ctrl) = IdCtrl::from_bytes(data) {
+        id_ctrl.cntrltype();
+        let sanicap = id_ctrl.sanicap;
+        sanicap.nodmmas();
+        let sgls = id_ctrl.sgls;
+        sgls.support();
+        let _ = serde_json::to_string(id_ctrl);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, IdNmsp, IdNmspIndep};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(id_nmsp) = IdNmsp::from_bytes(data) {
+        id_nmsp.current_lbaf();
+        let _ = serde_json::to_string(id_nmsp);
+    }
+    if let Ok(id) = IdNmspIndep::from_bytes(data) {
+        id.nsfeat.rotational();
+        let _ = serde_json::to_string(id);
+    }
+});
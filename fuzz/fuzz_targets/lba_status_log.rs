@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{LbaStatusList, LbaStatusLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = LbaStatusLog::parse(data) {
+        for nmsp in log.nmsps() {
+            nmsp.elem.ratype();
+            nmsp.ranges.iter().count();
+        }
+        let _ = serde_json::to_string(&log);
+    }
+    if let Ok(list) = LbaStatusList::parse(data) {
+        list.header.cmpc();
+        list.next_slba();
+        let _ = serde_json::to_string(&list);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, LockdownLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = LockdownLog::from_bytes(data) {
+        log.scp();
+        log.cntype();
+        log.list().for_each(|id| {
+            log.contains(id);
+        });
+        let _ = serde_json::to_string(log);
+    }
+});
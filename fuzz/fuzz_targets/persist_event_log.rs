@@ -0,0 +1,20 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{PersistEventData, PersistEventLog, PersistEventType};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = PersistEventLog::parse(data) {
+        log.header().supports(PersistEventType::Smart);
+        for event in log.events() {
+            event.header.etype();
+            event.header.timestamp();
+            if let PersistEventData::SetFeature(event) = event.data {
+                event.cdws().count();
+                event.fid();
+                event.cqe_dw0();
+                event.mem_buf();
+            }
+        }
+        let _ = serde_json::to_string(&log);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, PredLatLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = PredLatLog::from_bytes(data) {
+        log.status();
+        log.event_type();
+        let _ = serde_json::to_string(log);
+    }
+});
@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, ReservationNotificationLog, ReservationStatus};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = ReservationNotificationLog::from_bytes(data) {
+        log.log_page_type();
+        let _ = serde_json::to_string(log);
+    }
+    for eds in [false, true] {
+        if let Ok(status) = ReservationStatus::parse(data, eds) {
+            status.header().rtype();
+            status.registrants().count();
+            status.holder();
+            let _ = serde_json::to_string(&status);
+        }
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, RotationalMediaLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = RotationalMediaLog::from_bytes(data) {
+        let _ = serde_json::to_string(log);
+    }
+});
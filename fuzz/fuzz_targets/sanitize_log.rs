@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, SanitizeLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = SanitizeLog::from_bytes(data) {
+        log.sstat();
+        log.state();
+        log.percent();
+        log.remaining();
+        let _ = serde_json::to_string(log);
+    }
+});
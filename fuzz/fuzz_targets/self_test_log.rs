@@ -0,0 +1,18 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, SelfTestLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = SelfTestLog::from_bytes(data) {
+        log.curr_op();
+        log.curr_completion();
+        for result in log.results() {
+            result.result();
+            result.code();
+            result.nsid();
+            result.failing_lba();
+            result.status_code();
+        }
+        let _ = serde_json::to_string(log);
+    }
+});
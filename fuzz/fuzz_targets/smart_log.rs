@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FromBytes, SmartLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(smart) = SmartLog::from_bytes(data) {
+        smart.crit_warning();
+        smart.endur_grp_crit_warning();
+        let _ = serde_json::to_string(smart);
+    }
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{FidEffectsLog, FromBytes, LidEffectsLog};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(log) = LidEffectsLog::from_bytes(data) {
+        log.supported().count();
+        let _ = serde_json::to_string(log);
+    }
+    if let Ok(log) = FidEffectsLog::from_bytes(data) {
+        log.supported().count();
+        let _ = serde_json::to_string(log);
+    }
+});
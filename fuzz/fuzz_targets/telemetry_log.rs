@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::{Telemetry, TelemetryDataArea};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(telemetry) = Telemetry::new(data.to_vec()) {
+        let header = telemetry.header();
+        header.ctrl_data_avail();
+        for area in TelemetryDataArea::ALL.iter() {
+            header.last_block(*area);
+            telemetry.data_area(*area);
+        }
+        telemetry.data_areas().count();
+        let _ = serde_json::to_string(header);
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use nvme::ZoneReport;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(report) = ZoneReport::parse(data) {
+        for desc in report.descs {
+            desc.ztype();
+            desc.state();
+        }
+        report.is_complete();
+        report.zone(0);
+        let _ = serde_json::to_string(&report);
+    }
+});
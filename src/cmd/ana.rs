@@ -0,0 +1,187 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use alloc::vec::Vec;
+
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    AnaLog, LogPageId,
+};
+
+/// Longest ANA log `get_ana_log` will grow its buffer to.
+pub const ANA_LOG_MAX_LEN: usize = 16 << 20;
+
+/// Read the ANA log into `buf`, growing it and reading again until every
+/// group descriptor fits. Since descriptors beyond the end of `buf` can't be
+/// sized, it at least doubles each time. Sizing `buf` with
+/// `IdCtrl::ana_log_len` up front avoids the extra reads. With `groups_only`,
+/// the RGO log specific parameter is set so descriptors omit their namespace
+/// identifiers. A log which would exceed `ANA_LOG_MAX_LEN` is rejected with
+/// `CmdErr::Invalid`.
+pub fn get_ana_log<'b, T: Transport>(
+    transport: &mut T,
+    groups_only: bool,
+    buf: &'b mut Vec<u8>,
+) -> Result<AnaLog<'b>, CmdErr<T::Err>> {
+    let log = GetLogPage::new(LogPageId::Ana as u8).with_lsp(groups_only as u8);
+    if buf.len() < 4096 {
+        buf.resize(4096, 0);
+    }
+    loop {
+        log.read(transport, buf)?;
+        match AnaLog::parse(buf) {
+            Ok(_) => break,
+            Err(len) if len > ANA_LOG_MAX_LEN => return Err(CmdErr::Invalid),
+            Err(len) => buf.resize(((len + 3) & !3).max(buf.len() * 2).min(ANA_LOG_MAX_LEN), 0),
+        }
+    }
+    Ok(AnaLog::parse(buf).expect("complete log"))
+}
+
+/// ANA groups described by (grpid, state, nsids), and the length of each read.
+#[cfg(test)]
+struct MockAna {
+    chgcnt: u64,
+    groups: Vec<(u32, u8, Vec<u32>)>,
+    lens: Vec<usize>,
+    /// Report each group with this many namespaces, whatever it holds.
+    nnsids: Option<u32>,
+}
+
+#[cfg(test)]
+fn mock_ctrl(ana: MockAna) -> crate::cmd::mock::MockCtrl<MockAna> {
+    crate::cmd::mock::MockCtrl::new(ana).on_log(LogPageId::Ana, |ana, cmd, data| {
+        let groups_only = cmd.cdw10 >> 8 & 1 == 1;
+        let mut log = Vec::new();
+        log.extend_from_slice(&ana.chgcnt.to_le_bytes());
+        log.extend_from_slice(&(ana.groups.len() as u16).to_le_bytes());
+        log.resize(16, 0);
+        for (grpid, state, nsids) in &ana.groups {
+            let nsids: &[u32] = if groups_only { &[] } else { nsids };
+            let mut desc = [0u8; 32];
+            desc[0..4].copy_from_slice(&grpid.to_le_bytes());
+            let nnsids = ana.nnsids.unwrap_or(nsids.len() as u32);
+            desc[4..8].copy_from_slice(&nnsids.to_le_bytes());
+            desc[8..16].copy_from_slice(&ana.chgcnt.to_le_bytes());
+            desc[16] = *state;
+            log.extend_from_slice(&desc);
+            log.extend(nsids.iter().flat_map(|nsid| nsid.to_le_bytes()));
+        }
+        log.resize(log.len().max(data.len()), 0);
+        data.copy_from_slice(&log[..data.len()]);
+        ana.lens.push(data.len());
+        Ok(())
+    })
+}
+
+#[test]
+fn ana_state_tracking() {
+    use crate::{AnaState, AnaTracker, AnaTransition};
+
+    let mut ctrl = mock_ctrl(MockAna {
+        chgcnt: 1,
+        groups: alloc::vec![(1, 0x1, (1..=1100).collect()), (2, 0x2, alloc::vec![1101])],
+        lens: Vec::new(),
+        nnsids: None,
+    });
+    let mut tracker = AnaTracker::new();
+    let mut buf = Vec::new();
+
+    let log = get_ana_log(&mut ctrl, false, &mut buf).unwrap();
+    assert_eq!(ctrl.state.lens, [4096, 8192]);
+    assert_eq!(log.len(), 16 + 32 * 2 + 4 * 1101);
+    assert_eq!(
+        tracker.update(&log, false),
+        [
+            AnaTransition {
+                grpid: 1,
+                from: None,
+                to: Some(AnaState::Optimized),
+            },
+            AnaTransition {
+                grpid: 2,
+                from: None,
+                to: Some(AnaState::NonOptimized),
+            },
+        ]
+    );
+    assert_eq!(tracker.nsid_state(1101), Some(AnaState::NonOptimized));
+
+    let ana = &mut ctrl.state;
+    ana.chgcnt = 2;
+    ana.groups[0].1 = 0x3;
+    ana.groups[1].1 = 0xf;
+    ana.groups.push((3, 0x1, alloc::vec![1102]));
+    let log = get_ana_log(&mut ctrl, true, &mut buf).unwrap();
+    assert!(log.groups().all(|group| group.nsids().next().is_none()));
+    let transitions = tracker.update(&log, true);
+    assert_eq!(
+        transitions
+            .iter()
+            .map(|transition| (transition.grpid, transition.to))
+            .collect::<Vec<_>>(),
+        [
+            (1, Some(AnaState::Inaccessible)),
+            (2, Some(AnaState::Change)),
+            (3, Some(AnaState::Optimized)),
+        ]
+    );
+    assert_eq!(tracker.chgcnt(), 2);
+    assert_eq!(tracker.nsid_state(500), Some(AnaState::Inaccessible));
+    assert_eq!(tracker.nsid_state(1102), None);
+
+    let ana = &mut ctrl.state;
+    ana.chgcnt = 3;
+    ana.groups.remove(0);
+    ana.groups[0].1 = 0x2;
+    let log = get_ana_log(&mut ctrl, false, &mut buf).unwrap();
+    assert_eq!(
+        tracker.update(&log, false),
+        [
+            AnaTransition {
+                grpid: 1,
+                from: Some(AnaState::Inaccessible),
+                to: None,
+            },
+            AnaTransition {
+                grpid: 2,
+                from: Some(AnaState::Change),
+                to: Some(AnaState::NonOptimized),
+            },
+        ]
+    );
+    assert_eq!(tracker.nsid_state(1102), Some(AnaState::Optimized));
+    assert_eq!(tracker.nsid_state(500), None);
+}
+
+#[test]
+fn ana_log_len_capped() {
+    let mut ctrl = mock_ctrl(MockAna {
+        chgcnt: 1,
+        groups: alloc::vec![(1, 0x1, alloc::vec![1])],
+        lens: Vec::new(),
+        nnsids: Some(u32::MAX),
+    });
+    let mut buf = Vec::new();
+    match get_ana_log(&mut ctrl, false, &mut buf) {
+        Err(CmdErr::Invalid) => (),
+        _ => panic!("expected oversized log to be rejected"),
+    }
+    assert_eq!(ctrl.state.lens, [4096]);
+}
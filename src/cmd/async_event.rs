@@ -0,0 +1,120 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+use crate::{
+    cmd::{AdminOpcode, Cmd, Completion},
+    LogPageId,
+};
+
+/// Asynchronous Event Request command. It completes only once the controller
+/// reports an event, described by [`AsyncEvent`].
+pub fn async_event_req() -> Cmd {
+    Cmd::new(AdminOpcode::AsyncEventReq)
+}
+
+/// Dword 0 of an Asynchronous Event Request completion.
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct AsyncEvent {
+    pub etype: B3,
+    #[skip]
+    __rsvd3: B5,
+    pub info: u8,
+    pub lid: u8,
+    #[skip]
+    __rsvd24: u8,
+}
+
+impl AsyncEvent {
+    pub fn from_completion(completion: &Completion) -> Self {
+        AsyncEvent::from(completion.dw0)
+    }
+
+    pub fn event_type(&self) -> Option<AsyncEventType> {
+        FromPrimitive::from_u8(self.etype())
+    }
+
+    /// Log page to read for details, which clears the event unless read with
+    /// RAE set.
+    pub fn log_page(&self) -> Option<LogPageId> {
+        FromPrimitive::from_u8(self.lid())
+    }
+
+    /// Kind of notice, if this is a notice event.
+    pub fn notice(&self) -> Option<AsyncNotice> {
+        match self.event_type()? {
+            AsyncEventType::Notice => FromPrimitive::from_u8(self.info()),
+            _ => None,
+        }
+    }
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AsyncEventType {
+    ErrStatus = 0x0,
+    SmartHealthStatus = 0x1,
+    Notice = 0x2,
+    IoCmdSpecific = 0x6,
+    VndrSpecific = 0x7,
+}
+
+/// Asynchronous Event Information for notice events
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AsyncNotice {
+    NmspAttrChanged = 0x00,
+    FwActivationStarting = 0x01,
+    TelemetryLogChanged = 0x02,
+    AsymNmspAccessChange = 0x03,
+    PredLatEventAggregateChange = 0x04,
+    LbaStatusInfoAlert = 0x05,
+    EndurGrpEventAggregateChange = 0x06,
+    /// Zone descriptors of a zoned namespace changed, as listed in the
+    /// Changed Zone List.
+    ZoneDescChanged = 0xef,
+}
+
+#[test]
+fn async_event_notice() {
+    let event = AsyncEvent::from(0x000f_0602);
+    assert_eq!(event.event_type(), Some(AsyncEventType::Notice));
+    assert_eq!(
+        event.notice(),
+        Some(AsyncNotice::EndurGrpEventAggregateChange)
+    );
+    assert_eq!(event.log_page(), Some(LogPageId::EndurGrpEventAggregate));
+
+    let event = AsyncEvent::from(0x0002_0101);
+    assert_eq!(event.event_type(), Some(AsyncEventType::SmartHealthStatus));
+    assert_eq!(event.notice(), None);
+    assert_eq!(event.log_page(), Some(LogPageId::Smart));
+}
@@ -0,0 +1,172 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use alloc::{vec, vec::Vec};
+use core::mem::size_of;
+
+use crate::{
+    cmd::{download_fw, CmdErr, FwCommit, FwCommitAction, GetLogPage, Transport},
+    BootPartitionLog, LogPageId,
+};
+
+/// Header of the Boot Partition log for boot partition `bpid`.
+pub fn get_boot_partition_log<T: Transport>(
+    transport: &mut T,
+    bpid: bool,
+) -> Result<BootPartitionLog, CmdErr<T::Err>> {
+    GetLogPage::new(LogPageId::BootPartition as u8)
+        .with_lsp(bpid as u8)
+        .read_log(transport)
+}
+
+/// Read the contents of boot partition `bpid` through the Boot Partition
+/// log, `chunk_len` bytes per command. Reading past the header relies on the
+/// log page offset, so the controller must support extended Get Log Page.
+pub fn read_boot_partition<T: Transport>(
+    transport: &mut T,
+    bpid: bool,
+    chunk_len: usize,
+) -> Result<Vec<u8>, CmdErr<T::Err>> {
+    let header_len = size_of::<BootPartitionLog>();
+    let end = get_boot_partition_log(transport, bpid)?.full_len();
+    let log = GetLogPage::new(LogPageId::BootPartition as u8).with_lsp(bpid as u8);
+    let chunk_len = (chunk_len / 4).max(1) * 4;
+    let mut data = vec![0u8; end - header_len];
+    let mut lpo = header_len;
+    while lpo < end {
+        let len = chunk_len.min(end - lpo);
+        let start = lpo - header_len;
+        log.with_lpo(lpo as u64)
+            .read(transport, &mut data[start..start + len])?;
+        lpo += len;
+    }
+    Ok(data)
+}
+
+/// Replace the contents of boot partition `bpid` with `image`, and make it
+/// the active boot partition if `activate` is set. The commit fails with
+/// `BootPartitionWriteProhibited` while boot partitions are write protected.
+pub fn write_boot_partition<T: Transport>(
+    transport: &mut T,
+    bpid: bool,
+    image: &[u8],
+    chunk_len: usize,
+    activate: bool,
+) -> Result<(), CmdErr<T::Err>> {
+    download_fw(transport, image, chunk_len)?;
+    FwCommit::boot_partition(FwCommitAction::ReplaceBootPartition, bpid).exec(transport)?;
+    if activate {
+        FwCommit::boot_partition(FwCommitAction::ActivateBootPartition, bpid).exec(transport)?;
+    }
+    Ok(())
+}
+
+/// Boot partitions of 128 KiB, with the image downloaded so far staged for
+/// the next replace. Writes fail while `write_protected`.
+#[cfg(test)]
+struct MockBoot {
+    staged: Vec<u8>,
+    partitions: [Vec<u8>; 2],
+    abpid: bool,
+    write_protected: bool,
+}
+
+#[cfg(test)]
+fn mock_ctrl() -> crate::cmd::mock::MockCtrl<MockBoot> {
+    use crate::{
+        cmd::{
+            mock::{failure, lpo, MockCtrl},
+            AdminOpcode,
+        },
+        CmdSpecificStatus, StatusCodeType,
+    };
+
+    let boot = MockBoot {
+        staged: Vec::new(),
+        partitions: [vec![0xaa; 128 * 1024], vec![0xbb; 128 * 1024]],
+        abpid: false,
+        write_protected: false,
+    };
+    MockCtrl::new(boot)
+        .on_admin(AdminOpcode::FwImageDownload, |boot, cmd, data| {
+            assert_eq!((cmd.cdw10 as usize + 1) * 4, data.len());
+            assert_eq!(cmd.cdw11 as usize * 4, boot.staged.len());
+            boot.staged.extend_from_slice(data);
+            Ok(())
+        })
+        .on_admin(AdminOpcode::FwCommit, |boot, cmd, _| {
+            let cdw10 = cmd.cdw10;
+            let bpid = (cdw10 >> 31) as usize;
+            match (cdw10 >> 3) & 0x7 {
+                6 if boot.write_protected => {
+                    let sc = CmdSpecificStatus::BootPartitionWriteProhibited as u8;
+                    return Err(failure(StatusCodeType::CmdSpecific, sc));
+                }
+                6 => {
+                    let mut image = core::mem::take(&mut boot.staged);
+                    image.resize(128 * 1024, 0);
+                    boot.partitions[bpid] = image;
+                }
+                7 => boot.abpid = bpid == 1,
+                ca => panic!("unexpected commit action {}", ca),
+            }
+            Ok(())
+        })
+        .on_log(LogPageId::BootPartition, |boot, cmd, data| {
+            let (bpid, lpo) = ((cmd.cdw10 >> 8 & 0x7f) as usize, lpo(cmd));
+            let mut log = vec![0u8; 16];
+            log[0] = 0x15;
+            log[4] = 1;
+            log[7] = (boot.abpid as u8) << 7;
+            log.extend_from_slice(&boot.partitions[bpid]);
+            data.copy_from_slice(&log[lpo..lpo + data.len()]);
+            Ok(())
+        })
+}
+
+#[test]
+fn boot_partition_update() {
+    use crate::CmdSpecificStatus;
+
+    let mut ctrl = mock_ctrl();
+    let log = get_boot_partition_log(&mut ctrl, false).unwrap();
+    assert_eq!(log.bpinfo.bp_size(), 128 * 1024);
+    assert!(!log.bpinfo.abpid());
+
+    let image: Vec<u8> = (0..10_001u32).map(|i| i as u8).collect();
+    write_boot_partition(&mut ctrl, true, &image, 4096, true).unwrap();
+    assert!(ctrl.state.abpid);
+    let read = read_boot_partition(&mut ctrl, true, 8192).unwrap();
+    assert_eq!(read.len(), 128 * 1024);
+    assert_eq!(read[..image.len()], image[..]);
+    assert!(read[image.len()..].iter().all(|&b| b == 0));
+    assert!(read_boot_partition(&mut ctrl, false, 8192)
+        .unwrap()
+        .iter()
+        .all(|&b| b == 0xaa));
+
+    ctrl.state.write_protected = true;
+    match write_boot_partition(&mut ctrl, false, &image, 4096, false) {
+        Err(CmdErr::Status(status)) => assert_eq!(
+            status.sc(),
+            CmdSpecificStatus::BootPartitionWriteProhibited as u8
+        ),
+        _ => panic!("write should be prohibited"),
+    }
+}
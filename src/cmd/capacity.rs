@@ -0,0 +1,273 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::cmd::{exec_admin, AdminOpcode, Cmd, CmdErr, Completion, Transport};
+#[cfg(feature = "alloc")]
+use crate::{cmd::GetLogPage, LogPageId, MediaUnitStatusLog, SuppCapConfigList};
+
+/// Operation of the Capacity Management command
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum CapacityMgmtOp {
+    SelectCapConfig = 0x0,
+    CreateEndurGrp = 0x1,
+    DeleteEndurGrp = 0x2,
+    CreateNvmSet = 0x3,
+    DeleteNvmSet = 0x4,
+}
+
+/// Capacity Management command. Creating an endurance group or NVM set
+/// completes with its identifier in bits 15:0 of dword 0.
+#[derive(Clone, Copy, Debug)]
+pub struct CapacityMgmt {
+    pub op: CapacityMgmtOp,
+    /// Element Identifier: the capacity configuration, endurance group or
+    /// NVM set the operation applies to.
+    pub elemid: u16,
+    /// Capacity of the endurance group or NVM set to create.
+    pub cap: u64,
+}
+
+impl CapacityMgmt {
+    /// Apply the capacity configuration `capid` from the Supported Capacity
+    /// Configuration List.
+    pub fn select(capid: u16) -> Self {
+        CapacityMgmt {
+            op: CapacityMgmtOp::SelectCapConfig,
+            elemid: capid,
+            cap: 0,
+        }
+    }
+
+    pub fn create_endur_grp(cap: u64) -> Self {
+        CapacityMgmt {
+            op: CapacityMgmtOp::CreateEndurGrp,
+            elemid: 0,
+            cap,
+        }
+    }
+
+    pub fn delete_endur_grp(endgid: u16) -> Self {
+        CapacityMgmt {
+            op: CapacityMgmtOp::DeleteEndurGrp,
+            elemid: endgid,
+            cap: 0,
+        }
+    }
+
+    /// Create an NVM set within endurance group `endgid`.
+    pub fn create_nvm_set(endgid: u16, cap: u64) -> Self {
+        CapacityMgmt {
+            op: CapacityMgmtOp::CreateNvmSet,
+            elemid: endgid,
+            cap,
+        }
+    }
+
+    pub fn delete_nvm_set(nvmsetid: u16) -> Self {
+        CapacityMgmt {
+            op: CapacityMgmtOp::DeleteNvmSet,
+            elemid: nvmsetid,
+            cap: 0,
+        }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            cdw10: self.op as u32 | (self.elemid as u32) << 16,
+            cdw11: self.cap as u32,
+            cdw12: (self.cap >> 32) as u32,
+            ..Cmd::new(AdminOpcode::CapacityMgmt)
+        }
+    }
+
+    pub fn exec<T: Transport>(&self, transport: &mut T) -> Result<Completion, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(), &mut [])
+    }
+}
+
+/// Longest Media Unit Status or Supported Capacity Configuration List log
+/// read by `get_media_unit_status` and `get_supp_cap_config_list`.
+pub const CAPACITY_LOG_MAX_LEN: usize = 16 << 20;
+
+/// Read a log of `lid` for domain `domainid` into `buf`, growing it and
+/// reading again until `parse` accepts it. A log longer than
+/// `CAPACITY_LOG_MAX_LEN` is rejected with `CmdErr::Invalid`.
+#[cfg(feature = "alloc")]
+fn read_grown<T: Transport>(
+    transport: &mut T,
+    lid: LogPageId,
+    domainid: u16,
+    buf: &mut Vec<u8>,
+    parse: fn(&[u8]) -> Result<(), usize>,
+) -> Result<(), CmdErr<T::Err>> {
+    let log = GetLogPage::new(lid as u8).with_nsid(0).with_lsi(domainid);
+    if buf.len() < 4096 {
+        buf.resize(4096, 0);
+    }
+    loop {
+        log.read(transport, buf)?;
+        match parse(buf) {
+            Ok(()) => return Ok(()),
+            Err(len) if len > CAPACITY_LOG_MAX_LEN => return Err(CmdErr::Invalid),
+            Err(len) => buf.resize(
+                ((len + 3) & !3)
+                    .max(buf.len() * 2)
+                    .min(CAPACITY_LOG_MAX_LEN),
+                0,
+            ),
+        }
+    }
+}
+
+/// Read the Media Unit Status log of domain `domainid` into `buf`, growing
+/// it until every descriptor fits.
+#[cfg(feature = "alloc")]
+pub fn get_media_unit_status<'b, T: Transport>(
+    transport: &mut T,
+    domainid: u16,
+    buf: &'b mut Vec<u8>,
+) -> Result<MediaUnitStatusLog<'b>, CmdErr<T::Err>> {
+    read_grown(
+        transport,
+        LogPageId::MediaUnitStatus,
+        domainid,
+        buf,
+        |bytes| MediaUnitStatusLog::parse(bytes).map(|_| ()),
+    )?;
+    Ok(MediaUnitStatusLog::parse(buf).expect("complete log"))
+}
+
+/// Read the Supported Capacity Configuration List of domain `domainid` into
+/// `buf`, growing it until every descriptor fits.
+#[cfg(feature = "alloc")]
+pub fn get_supp_cap_config_list<'b, T: Transport>(
+    transport: &mut T,
+    domainid: u16,
+    buf: &'b mut Vec<u8>,
+) -> Result<SuppCapConfigList<'b>, CmdErr<T::Err>> {
+    read_grown(
+        transport,
+        LogPageId::SuppCapConfigList,
+        domainid,
+        buf,
+        |bytes| SuppCapConfigList::parse(bytes).map(|_| ()),
+    )?;
+    Ok(SuppCapConfigList::parse(buf).expect("complete log"))
+}
+
+#[test]
+fn capacity_mgmt_cmd() {
+    let cmd = CapacityMgmt::select(5).cmd();
+    assert_eq!((cmd.opcode, { cmd.cdw10 }), (0x20, 0x0005_0000));
+    let cmd = CapacityMgmt::create_nvm_set(2, 0x1_0000_0200).cmd();
+    assert_eq!(
+        ({ cmd.cdw10 }, { cmd.cdw11 }, { cmd.cdw12 }),
+        (0x0002_0003, 0x200, 1)
+    );
+}
+
+/// Controller offering capacity configurations 1 and 2 in domain 3, each of
+/// one endurance group on one channel. Configuration 2 pads its endurance
+/// group with enough NVM sets to need more than 4096 bytes. Its state is the
+/// selected configuration and the number of list reads. A garbled list is
+/// all ones.
+#[cfg(all(test, feature = "alloc"))]
+fn mock_ctrl(garbled: bool) -> crate::cmd::mock::MockCtrl<(Option<u16>, usize)> {
+    use crate::cmd::mock::{copy_log, MockCtrl};
+    use alloc::vec;
+
+    let mut list = vec![0u8; 16];
+    list[0] = 2;
+    for &(capid, egsets, tegcap) in [(1u16, 1u16, 1u128 << 30), (2, 2100, 1 << 40)].iter() {
+        let mut config = vec![0u8; 32];
+        config[0..2].copy_from_slice(&capid.to_le_bytes());
+        config[2..4].copy_from_slice(&3u16.to_le_bytes());
+        config[4..6].copy_from_slice(&1u16.to_le_bytes());
+        let mut eg = vec![0u8; 82];
+        eg[0..2].copy_from_slice(&1u16.to_le_bytes());
+        eg[16..32].copy_from_slice(&tegcap.to_le_bytes());
+        eg[80..82].copy_from_slice(&egsets.to_le_bytes());
+        for nvmsetid in 1..=egsets {
+            eg.extend_from_slice(&nvmsetid.to_le_bytes());
+        }
+        eg.extend_from_slice(&1u16.to_le_bytes());
+        eg.extend_from_slice(&[0, 0, 1, 0]);
+        eg.extend_from_slice(&[0x10, 0, 0, 0, 0, 0, 0, 0]);
+        list.extend(config);
+        list.extend(eg);
+    }
+
+    MockCtrl::new((None, 0))
+        .on_log(
+            LogPageId::SuppCapConfigList,
+            move |(_, reads), cmd, data| {
+                assert_eq!(cmd.cdw11 >> 16, 3);
+                *reads += 1;
+                if garbled {
+                    data.fill(0xff);
+                } else {
+                    copy_log(&list, 0, data);
+                }
+                Ok(())
+            },
+        )
+        .on_admin(AdminOpcode::CapacityMgmt, |(selected, _), cmd, _| {
+            assert_eq!(cmd.cdw10 & 0xf, 0);
+            *selected = Some((cmd.cdw10 >> 16) as u16);
+            Ok(())
+        })
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn capacity_config_select() {
+    let mut ctrl = mock_ctrl(false);
+    let mut buf = Vec::new();
+    let list = get_supp_cap_config_list(&mut ctrl, 3, &mut buf).unwrap();
+    assert_eq!(ctrl.state.1, 2);
+    let largest = list
+        .configs()
+        .max_by_key(|config| config.total_cap())
+        .unwrap();
+    assert_eq!({ largest.desc.capid }, 2);
+    let eg = largest.endur_grps().next().unwrap();
+    assert_eq!(eg.nvmsetids().count(), 2100);
+    assert_eq!(eg.muids().collect::<Vec<_>>(), [0x10]);
+
+    CapacityMgmt::select(largest.desc.capid)
+        .exec(&mut ctrl)
+        .unwrap();
+    assert_eq!(ctrl.state.0, Some(2));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn capacity_log_len_capped() {
+    let mut ctrl = mock_ctrl(true);
+    assert!(matches!(
+        get_supp_cap_config_list(&mut ctrl, 3, &mut Vec::new()),
+        Err(CmdErr::Invalid)
+    ));
+    assert!(ctrl.state.1 < 16);
+}
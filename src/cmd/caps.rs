@@ -0,0 +1,206 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{get_log, AdminOpcode, CmdErr, IoOpcode, Transport},
+    CmdEffectsLog, FidEffectsLog, IdCtrl, LidEffectsLog, LogPageId, Lpa, MiCmdEffectsLog, MiOpcode,
+};
+
+/// Log pages, features and commands a controller supports, read once from
+/// the logs which report them.
+pub struct CtrlCaps {
+    lpa: Lpa,
+    lids: Option<LidEffectsLog>,
+    fids: Option<FidEffectsLog>,
+    cmds: Option<CmdEffectsLog>,
+    mi_cmds: Option<MiCmdEffectsLog>,
+}
+
+impl CtrlCaps {
+    /// Read the Supported Log Pages log, and the Feature Identifiers,
+    /// Commands and NVMe-MI Commands Supported and Effects logs where
+    /// supported. Controllers predating the Supported Log Pages log fall
+    /// back to `id_ctrl.lpa`.
+    pub fn read<T: Transport>(transport: &mut T, id_ctrl: &IdCtrl) -> Result<Self, CmdErr<T::Err>> {
+        let mut caps = CtrlCaps {
+            lpa: id_ctrl.lpa,
+            lids: optional(get_log(transport))?,
+            fids: None,
+            cmds: None,
+            mi_cmds: None,
+        };
+        if caps.supports_log(LogPageId::FidEffects) {
+            caps.fids = Some(get_log(transport)?);
+        }
+        if caps.supports_log(LogPageId::CmdEffects) {
+            caps.cmds = Some(get_log(transport)?);
+        }
+        if caps.supports_log(LogPageId::MiCmdEffects) {
+            caps.mi_cmds = Some(get_log(transport)?);
+        }
+        Ok(caps)
+    }
+
+    pub fn lids(&self) -> Option<&LidEffectsLog> {
+        self.lids.as_ref()
+    }
+
+    pub fn fids(&self) -> Option<&FidEffectsLog> {
+        self.fids.as_ref()
+    }
+
+    pub fn cmds(&self) -> Option<&CmdEffectsLog> {
+        self.cmds.as_ref()
+    }
+
+    pub fn mi_cmds(&self) -> Option<&MiCmdEffectsLog> {
+        self.mi_cmds.as_ref()
+    }
+
+    pub fn supports_log(&self, lid: LogPageId) -> bool {
+        match &self.lids {
+            Some(lids) => lids.supports(lid),
+            None => match lid {
+                LogPageId::ErrInfo | LogPageId::Smart | LogPageId::FwSlot => true,
+                LogPageId::CmdEffects => self.lpa.cmd_supp_eff(),
+                LogPageId::TelemetryHostInit | LogPageId::TelemetryCtrlInit => self.lpa.telem(),
+                LogPageId::PersistEvent => self.lpa.persist_event(),
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether feature `fid` is supported, or `None` if the controller doesn't
+    /// report supported features.
+    pub fn supports_feature(&self, fid: u8) -> Option<bool> {
+        Some(self.fids.as_ref()?.fid(fid).fsupp())
+    }
+
+    /// Whether an admin command is supported, or `None` if the controller
+    /// doesn't report supported commands.
+    pub fn supports_admin(&self, opcode: AdminOpcode) -> Option<bool> {
+        Some(self.cmds.as_ref()?.admin(opcode as u8).csupp())
+    }
+
+    /// Whether an I/O command is supported, or `None` if the controller
+    /// doesn't report supported commands.
+    pub fn supports_io(&self, opcode: IoOpcode) -> Option<bool> {
+        Some(self.cmds.as_ref()?.io(opcode as u8).csupp())
+    }
+
+    /// Whether an NVMe-MI command is supported, or `None` if the controller
+    /// doesn't report supported NVMe-MI commands.
+    pub fn supports_mi(&self, opcode: MiOpcode) -> Option<bool> {
+        Some(self.mi_cmds.as_ref()?.mi(opcode as u8).csupp())
+    }
+}
+
+/// Treat an unsuccessful status as the log page being unsupported.
+fn optional<L, E>(log: Result<L, CmdErr<E>>) -> Result<Option<L>, CmdErr<E>> {
+    match log {
+        Ok(log) => Ok(Some(log)),
+        Err(CmdErr::Status(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Controller reporting its capabilities through the Supported Log Pages and
+/// effects logs if `supported`, otherwise only through the Identify
+/// Controller LPA field. Its state is the LID of each log read.
+#[cfg(test)]
+fn mock_ctrl(supported: bool) -> crate::cmd::mock::MockCtrl<Vec<u8>> {
+    use crate::{
+        cmd::{
+            mock::{failure, MockCtrl, Reply},
+            Cmd,
+        },
+        StatusCodeType,
+    };
+
+    fn read_log(log: Option<Vec<u8>>) -> impl FnMut(&mut Vec<u8>, &Cmd, &mut [u8]) -> Reply {
+        move |lids, cmd, data| {
+            lids.push(cmd.cdw10 as u8);
+            let log = log
+                .as_ref()
+                .ok_or_else(|| failure(StatusCodeType::CmdSpecific, 0x09))?;
+            data.copy_from_slice(log);
+            Ok(())
+        }
+    }
+
+    let mut lids = vec![0u8; 1024];
+    for lid in [0x00, 0x01, 0x02, 0x03, 0x05, 0x0c, 0x12, 0x13] {
+        lids[lid * 4] = 1;
+    }
+    let mut fids = vec![0u8; 1024];
+    fids[0x0b * 4] = 1;
+    fids[0x0b * 4 + 2] = 0x20;
+    let mut mi_cmds = vec![0u8; 4096];
+    mi_cmds[4] = 1;
+    mi_cmds[0x07 * 4] = 0x1f;
+    let mut cmds = vec![0u8; 4096];
+    cmds[0x84 * 4] = 1;
+    cmds[1024 + 0x0d * 4] = 1;
+
+    let effects = |log| Some(log).filter(|_| supported);
+    MockCtrl::new(Vec::new())
+        .on_log(LogPageId::SupportedLogPages, read_log(effects(lids)))
+        .on_log(LogPageId::FidEffects, read_log(effects(fids)))
+        .on_log(LogPageId::MiCmdEffects, read_log(effects(mi_cmds)))
+        .on_log(LogPageId::CmdEffects, read_log(Some(cmds)))
+}
+
+#[test]
+fn ctrl_caps() {
+    use crate::FromBytes;
+
+    let mut id_ctrl = vec![0u8; 4096];
+    id_ctrl[261] = 0x02;
+    let id_ctrl = IdCtrl::from_bytes(&id_ctrl).unwrap();
+
+    let mut ctrl = mock_ctrl(true);
+    let caps = CtrlCaps::read(&mut ctrl, id_ctrl).unwrap();
+    assert_eq!(ctrl.state, [0x00, 0x12, 0x05, 0x13]);
+    assert!(caps.supports_log(LogPageId::Ana));
+    assert!(!caps.supports_log(LogPageId::PersistEvent));
+    assert_eq!(caps.supports_feature(0x0b), Some(true));
+    assert!(caps.fids().unwrap().fid(0x0b).ctrl_scope());
+    assert_eq!(caps.supports_feature(0x0c), Some(false));
+    assert_eq!(caps.supports_admin(AdminOpcode::Sanitize), Some(true));
+    assert_eq!(caps.supports_admin(AdminOpcode::FormatNvm), Some(false));
+    assert_eq!(caps.supports_io(IoOpcode::ReservationRegister), Some(true));
+    assert_eq!(
+        caps.supports_mi(MiOpcode::NvmSubsysHealthStatusPoll),
+        Some(true)
+    );
+    assert_eq!(caps.supports_mi(MiOpcode::VpdWrite), Some(false));
+    assert!(!caps.mi_cmds().unwrap().mi(0x01).has_effects());
+    assert!(caps.mi_cmds().unwrap().mi(0x07).has_effects());
+
+    let mut ctrl = mock_ctrl(false);
+    let caps = CtrlCaps::read(&mut ctrl, id_ctrl).unwrap();
+    assert_eq!(ctrl.state, [0x00, 0x05]);
+    assert!(caps.lids().is_none());
+    assert!(caps.supports_log(LogPageId::Smart));
+    assert!(caps.supports_log(LogPageId::CmdEffects));
+    assert!(!caps.supports_log(LogPageId::Ana));
+    assert_eq!(caps.supports_feature(0x0b), None);
+    assert_eq!(caps.supports_mi(MiOpcode::Reset), None);
+    assert_eq!(caps.supports_admin(AdminOpcode::Sanitize), Some(true));
+}
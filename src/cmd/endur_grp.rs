@@ -0,0 +1,120 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::{
+    cmd::{get_event_aggregate, AsyncEvent, AsyncNotice},
+    LogPageId,
+};
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    EndurGrpLog,
+};
+
+/// Read the Endurance Group Information log of endurance group `endgid`.
+pub fn get_endur_grp_log<T: Transport>(
+    transport: &mut T,
+    endgid: u16,
+) -> Result<EndurGrpLog, CmdErr<T::Err>> {
+    GetLogPage::of::<EndurGrpLog>()
+        .with_nsid(0)
+        .with_lsi(endgid)
+        .read_log(transport)
+}
+
+/// Endurance group reported by the Endurance Group Event Aggregate log.
+#[cfg(feature = "alloc")]
+pub struct EndurGrpEvent {
+    pub endgid: u16,
+    pub log: EndurGrpLog,
+}
+
+/// Handle an asynchronous event by reading the Endurance Group Event
+/// Aggregate log, which clears the event, and then the Endurance Group
+/// Information log of each group it lists. Any other event is ignored and
+/// nothing is read.
+#[cfg(feature = "alloc")]
+pub fn endur_grp_events<T: Transport>(
+    transport: &mut T,
+    event: AsyncEvent,
+) -> Result<Vec<EndurGrpEvent>, CmdErr<T::Err>> {
+    if event.notice() != Some(AsyncNotice::EndurGrpEventAggregateChange) {
+        return Ok(Vec::new());
+    }
+    let mut buf = Vec::new();
+    let aggregate = get_event_aggregate(transport, LogPageId::EndurGrpEventAggregate, &mut buf)?;
+    aggregate
+        .ids()
+        .map(|endgid| {
+            Ok(EndurGrpEvent {
+                endgid,
+                log: get_endur_grp_log(transport, endgid)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn endur_grp_event_escalation() {
+    use crate::cmd::mock::{copy_log, MockCtrl};
+
+    // Endurance groups 1 and 3 have events outstanding, group 3 having gone
+    // read-only. Each (LID, LSI) read is recorded.
+    let mock_ctrl = || {
+        MockCtrl::new(Vec::new())
+            .on_log(LogPageId::EndurGrpEventAggregate, |lids, cmd, data| {
+                lids.push((cmd.cdw10 as u8, (cmd.cdw11 >> 16) as u16));
+                copy_log(&[2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 3, 0], 0, data);
+                Ok(())
+            })
+            .on_log(LogPageId::EndurGrpInfo, |lids, cmd, data| {
+                let lsi = (cmd.cdw11 >> 16) as u16;
+                lids.push((cmd.cdw10 as u8, lsi));
+                data.fill(0);
+                data[0] = if lsi == 3 { 0x08 } else { 0 };
+                data[5] = lsi as u8 * 10;
+                Ok(())
+            })
+    };
+
+    let mut ctrl = mock_ctrl();
+    let event = AsyncEvent::from(0x000f_0602);
+    let events = endur_grp_events(&mut ctrl, event).unwrap();
+    let crit: Vec<_> = events
+        .iter()
+        .map(|event| {
+            (
+                event.endgid,
+                event.log.percent_used,
+                event.log.crit_warning().readonly(),
+            )
+        })
+        .collect();
+    assert_eq!(crit, [(1, 10, false), (3, 30, true)]);
+    assert_eq!(ctrl.state, [(0x0f, 0), (0x0f, 0), (0x09, 1), (0x09, 3)]);
+
+    let mut ctrl = mock_ctrl();
+    let event = AsyncEvent::from(0x000c_0302);
+    assert!(endur_grp_events(&mut ctrl, event).unwrap().is_empty());
+    assert!(ctrl.state.is_empty());
+}
@@ -0,0 +1,82 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    EventAggregate, LogPageId,
+};
+
+/// Read the event aggregate log `lid` into `buf`, e.g.
+/// `LogPageId::PredLatEventAggregate`.
+///
+/// The entry count is first read with RAE set to size `buf`. The entries are
+/// then read with RAE cleared, which clears the asynchronous event, growing
+/// `buf` and reading again if more were added in between.
+pub fn get_event_aggregate<'b, T: Transport>(
+    transport: &mut T,
+    lid: LogPageId,
+    buf: &'b mut Vec<u8>,
+) -> Result<EventAggregate<'b>, CmdErr<T::Err>> {
+    let log = GetLogPage::new(lid as u8).with_nsid(0);
+    let mut num_entries = [0u8; size_of::<u64>()];
+    log.with_rae(true).read(transport, &mut num_entries)?;
+    let mut len = EventAggregate::parse(&num_entries)
+        .expect("complete entry count")
+        .full_len();
+    loop {
+        buf.resize((len + 3) & !3, 0);
+        log.read(transport, buf)?;
+        let full_len = EventAggregate::parse(buf)
+            .expect("complete entry count")
+            .full_len();
+        if full_len <= buf.len() {
+            break;
+        }
+        len = full_len;
+    }
+    Ok(EventAggregate::parse(buf).expect("complete entry count"))
+}
+
+#[test]
+fn event_aggregate_grows() {
+    // Events are outstanding for NVM Sets 1 and 2, and another is added after
+    // the first read. Each read's length and RAE bit is recorded.
+    let mut ctrl = crate::cmd::mock::MockCtrl::new((alloc::vec![1u16, 2], Vec::new())).on_log(
+        LogPageId::PredLatEventAggregate,
+        |(ids, reads), cmd, data| {
+            let mut log = (ids.len() as u64).to_le_bytes().to_vec();
+            log.extend(ids.iter().flat_map(|id| id.to_le_bytes()));
+            log.resize(log.len().max(data.len()), 0);
+            data.copy_from_slice(&log[..data.len()]);
+            reads.push((data.len(), cmd.cdw10 >> 15 & 1 == 1));
+            if reads.len() == 1 {
+                ids.push(3);
+            }
+            Ok(())
+        },
+    );
+    let mut buf = Vec::new();
+    let log = get_event_aggregate(&mut ctrl, LogPageId::PredLatEventAggregate, &mut buf).unwrap();
+    assert!(log.is_complete());
+    assert_eq!(log.ids().collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(ctrl.state.1, [(8, true), (12, false), (16, false)]);
+}
@@ -0,0 +1,452 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::{
+    cmd::{exec_io, Cmd, CmdErr, Completion, GetLogPage, IoOpcode, Transport},
+    FdpEventsLog, FdpStatsLog, FromBytes, Reserved, TransmuteSafe,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    FdpConfigLog, FdpConfigLogHeader, LogPageId, RuhUsageDesc, RuhUsageLog, RuhUsageLogHeader,
+};
+
+/// Longest FDP Configurations log `get_fdp_config_log` will read.
+pub const FDP_CONFIG_LOG_MAX_LEN: usize = 16 << 20;
+
+/// Read the FDP Configurations log of endurance group `endgid` into `buf`,
+/// sized by the log size its header reports. A log longer than
+/// `FDP_CONFIG_LOG_MAX_LEN` is rejected with `CmdErr::Invalid`.
+#[cfg(feature = "alloc")]
+pub fn get_fdp_config_log<'b, T: Transport>(
+    transport: &mut T,
+    endgid: u16,
+    buf: &'b mut Vec<u8>,
+) -> Result<FdpConfigLog<'b>, CmdErr<T::Err>> {
+    let log = GetLogPage::new(LogPageId::FdpConfigs as u8)
+        .with_nsid(0)
+        .with_lsi(endgid);
+    let header: FdpConfigLogHeader = log.read_log(transport)?;
+    let mut len = (header.sze as usize).max(size_of::<FdpConfigLogHeader>());
+    loop {
+        if len > FDP_CONFIG_LOG_MAX_LEN {
+            return Err(CmdErr::Invalid);
+        }
+        buf.resize((len + 3) & !3, 0);
+        log.read(transport, buf)?;
+        match FdpConfigLog::parse(buf) {
+            Ok(_) => break,
+            Err(need) => len = need.max(buf.len() + 4),
+        }
+    }
+    Ok(FdpConfigLog::parse(buf).expect("complete log"))
+}
+
+/// Read the Reclaim Unit Handle Usage log of endurance group `endgid` into
+/// `buf`, sized by the handle count its header reports. If the count grows
+/// in between, `CmdErr::Changed` is returned and the read should be retried.
+#[cfg(feature = "alloc")]
+pub fn get_ruh_usage_log<'b, T: Transport>(
+    transport: &mut T,
+    endgid: u16,
+    buf: &'b mut Vec<u8>,
+) -> Result<RuhUsageLog<'b>, CmdErr<T::Err>> {
+    let log = GetLogPage::new(LogPageId::RuhUsage as u8)
+        .with_nsid(0)
+        .with_lsi(endgid);
+    let header: RuhUsageLogHeader = log.read_log(transport)?;
+    let len = size_of::<RuhUsageLogHeader>() + header.nruh as usize * size_of::<RuhUsageDesc>();
+    buf.resize((len + 3) & !3, 0);
+    log.read(transport, buf)?;
+    RuhUsageLog::parse(buf).map_err(|_| CmdErr::Changed)
+}
+
+/// Read the FDP Statistics log of endurance group `endgid`.
+pub fn get_fdp_stats_log<T: Transport>(
+    transport: &mut T,
+    endgid: u16,
+) -> Result<FdpStatsLog, CmdErr<T::Err>> {
+    GetLogPage::of::<FdpStatsLog>()
+        .with_nsid(0)
+        .with_lsi(endgid)
+        .read_log(transport)
+}
+
+/// Read the FDP Events log of endurance group `endgid`, holding host events
+/// if `host` is set and controller events otherwise.
+pub fn get_fdp_events_log<T: Transport>(
+    transport: &mut T,
+    endgid: u16,
+    host: bool,
+) -> Result<FdpEventsLog, CmdErr<T::Err>> {
+    GetLogPage::of::<FdpEventsLog>()
+        .with_nsid(0)
+        .with_lsp(host as u8)
+        .with_lsi(endgid)
+        .read_log(transport)
+}
+
+/// Management Operation of the I/O Management Receive command
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum IoMgmtRecvOp {
+    RuhStatus = 0x1,
+}
+
+/// I/O Management Receive command.
+#[derive(Clone, Copy, Debug)]
+pub struct IoMgmtRecv {
+    pub nsid: u32,
+    pub mo: IoMgmtRecvOp,
+    /// Management Operation Specific value
+    pub mos: u16,
+}
+
+impl IoMgmtRecv {
+    /// Reclaim Unit Handle Status of each placement handle of `nsid`.
+    pub fn ruh_status(nsid: u32) -> Self {
+        IoMgmtRecv {
+            nsid,
+            mo: IoMgmtRecvOp::RuhStatus,
+            mos: 0,
+        }
+    }
+
+    pub fn cmd(&self, len: usize) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.mo as u32 | (self.mos as u32) << 16,
+            cdw11: (len / 4).saturating_sub(1) as u32,
+            ..Cmd::new_io(IoOpcode::IoMgmtRecv)
+        }
+    }
+
+    /// Receive into `buf`, whose length is a non-zero multiple of 4.
+    pub fn read<T: Transport>(
+        &self,
+        transport: &mut T,
+        buf: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(buf.len()), buf)
+    }
+}
+
+/// Reclaim Unit Handle Status header
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuhStatusHeader {
+    #[loc(0:13)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd0: Reserved<14>,
+    /// Number of Reclaim Unit Handle Status Descriptors
+    #[loc(14:15)]
+    pub nruhsd: u16,
+}
+
+impl TransmuteSafe for RuhStatusHeader {}
+
+/// Reclaim Unit Handle Status Descriptor
+#[test_structure(size = 32)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuhStatusDesc {
+    #[loc(0:1)]
+    pub pid: u16,
+    #[loc(2:3)]
+    pub ruhid: u16,
+    /// Estimated Active Reclaim Unit Time Remaining in seconds
+    #[loc(4:7)]
+    pub earutr: u32,
+    /// Reclaim Unit Available Media Writes in logical blocks
+    #[loc(8:15)]
+    pub ruamw: u64,
+    #[loc(16:31)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd16: Reserved<16>,
+}
+
+impl TransmuteSafe for RuhStatusDesc {}
+
+/// Reclaim Unit Handle Status returned by I/O Management Receive. A status
+/// truncated by a short buffer holds the descriptors which fit.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RuhStatus<'a> {
+    pub header: &'a RuhStatusHeader,
+    pub descs: &'a [RuhStatusDesc],
+}
+
+impl<'a> RuhStatus<'a> {
+    /// Parse the header and as many descriptors as it counts and `bytes`
+    /// holds. On error, the length of the header is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<RuhStatusHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| RuhStatusHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let descs = &bytes[header_len..];
+        let nruhsd = (header.nruhsd as usize).min(descs.len() / size_of::<RuhStatusDesc>());
+        Ok(RuhStatus {
+            header,
+            descs: <[RuhStatusDesc]>::from_bytes(&descs[..nruhsd * size_of::<RuhStatusDesc>()])
+                .expect("align 1"),
+        })
+    }
+
+    /// Length needed to hold every descriptor.
+    pub fn full_len(&self) -> usize {
+        size_of::<RuhStatusHeader>() + self.header.nruhsd as usize * size_of::<RuhStatusDesc>()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.descs.len() == self.header.nruhsd as usize
+    }
+
+    pub fn pid(&self, pid: u16) -> Option<&'a RuhStatusDesc> {
+        self.descs.iter().find(|desc| desc.pid == pid)
+    }
+}
+
+/// Read the Reclaim Unit Handle Status of `nsid` into `buf`, growing it until
+/// every descriptor fits.
+#[cfg(feature = "alloc")]
+pub fn get_ruh_status<'b, T: Transport>(
+    transport: &mut T,
+    nsid: u32,
+    buf: &'b mut Vec<u8>,
+) -> Result<RuhStatus<'b>, CmdErr<T::Err>> {
+    let recv = IoMgmtRecv::ruh_status(nsid);
+    let mut len = size_of::<RuhStatusHeader>();
+    loop {
+        buf.resize(len, 0);
+        recv.read(transport, buf)?;
+        let status = RuhStatus::parse(buf).expect("complete header");
+        if status.is_complete() {
+            break;
+        }
+        len = status.full_len();
+    }
+    Ok(RuhStatus::parse(buf).expect("complete header"))
+}
+
+/// Management Operation of the I/O Management Send command
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum IoMgmtSendOp {
+    RuhUpdate = 0x1,
+}
+
+/// I/O Management Send command.
+#[derive(Clone, Copy, Debug)]
+pub struct IoMgmtSend {
+    pub nsid: u32,
+    pub mo: IoMgmtSendOp,
+    /// Management Operation Specific value
+    pub mos: u16,
+}
+
+impl IoMgmtSend {
+    /// Point each of `npids` placement identifiers of `nsid`, sent as
+    /// little endian u16 values, at a new reclaim unit.
+    pub fn ruh_update(nsid: u32, npids: u16) -> Self {
+        IoMgmtSend {
+            nsid,
+            mo: IoMgmtSendOp::RuhUpdate,
+            mos: npids.saturating_sub(1),
+        }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.mo as u32 | (self.mos as u32) << 16,
+            ..Cmd::new_io(IoOpcode::IoMgmtSend)
+        }
+    }
+
+    pub fn exec<T: Transport>(
+        &self,
+        transport: &mut T,
+        data: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(), data)
+    }
+}
+
+/// Update the reclaim unit handles referenced by placement identifiers
+/// `pids` of `nsid`, so subsequent writes go to new reclaim units. An empty
+/// `pids`, or one holding more than `u16::MAX` identifiers, can't be sent and
+/// is rejected with `CmdErr::Invalid`.
+#[cfg(feature = "alloc")]
+pub fn update_ruhs<T: Transport>(
+    transport: &mut T,
+    nsid: u32,
+    pids: &[u16],
+) -> Result<Completion, CmdErr<T::Err>> {
+    if pids.is_empty() || pids.len() > u16::MAX as usize {
+        return Err(CmdErr::Invalid);
+    }
+    let mut data: Vec<u8> = pids.iter().flat_map(|pid| pid.to_le_bytes()).collect();
+    data.resize((data.len() + 3) & !3, 0);
+    IoMgmtSend::ruh_update(nsid, pids.len() as u16).exec(transport, &mut data)
+}
+
+/// Placement recorded by a controller with one FDP configuration of two
+/// reclaim groups and two reclaim unit handles, exposing placement handles 0
+/// and 1 of each group to namespace 1.
+#[cfg(all(test, feature = "alloc"))]
+#[derive(Default)]
+struct MockFdp {
+    /// (SLBA, DTYPE, DSPEC) of each write.
+    writes: Vec<(u64, u32, u16)>,
+    recv_lens: Vec<usize>,
+    updated: Vec<u16>,
+}
+
+/// The configuration log reports an impossible size if `huge`.
+#[cfg(all(test, feature = "alloc"))]
+fn mock_ctrl(huge: bool) -> crate::cmd::mock::MockCtrl<MockFdp> {
+    use crate::cmd::mock::{copy_log, MockCtrl};
+    use alloc::vec;
+    use core::convert::TryInto;
+
+    MockCtrl::new(MockFdp::default())
+        .on_log(LogPageId::FdpConfigs, move |_, cmd, data| {
+            assert_eq!(cmd.cdw11 >> 16, 1);
+            let mut log = [0u8; 16 + 72];
+            log[4] = 88;
+            if huge {
+                log[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+            }
+            log[16] = 72;
+            log[18] = 0x81;
+            log[20] = 2;
+            log[24] = 2;
+            log[80] = 1;
+            log[84] = 1;
+            copy_log(&log, 0, data);
+            Ok(())
+        })
+        .on_io(IoOpcode::Write, |fdp, cmd, _| {
+            assert_eq!({ cmd.nsid }, 1);
+            fdp.writes.push((
+                cmd.cdw10 as u64 | (cmd.cdw11 as u64) << 32,
+                cmd.cdw12 >> 20 & 0xf,
+                (cmd.cdw13 >> 16) as u16,
+            ));
+            Ok(())
+        })
+        .on_io(IoOpcode::IoMgmtRecv, |fdp, cmd, data| {
+            assert_eq!(({ cmd.nsid }, { cmd.cdw10 }), (1, 1));
+            assert_eq!((cmd.cdw11 as usize + 1) * 4, data.len());
+            fdp.recv_lens.push(data.len());
+            let mut status = vec![0u8; 16];
+            status[14] = 4;
+            for &pid in [0x0000u16, 0x0001, 0x8000, 0x8001].iter() {
+                let mut desc = vec![0u8; 32];
+                desc[0..2].copy_from_slice(&pid.to_le_bytes());
+                desc[2] = pid as u8;
+                desc[8..16].copy_from_slice(&(0x10_0000 - pid as u64).to_le_bytes());
+                status.extend(desc);
+            }
+            copy_log(&status, 0, data);
+            Ok(())
+        })
+        .on_io(IoOpcode::IoMgmtSend, |fdp, cmd, data| {
+            assert_eq!(({ cmd.nsid }, cmd.cdw10 & 0xff), (1, 1));
+            let npids = (cmd.cdw10 >> 16) as usize + 1;
+            fdp.updated.extend(
+                data[..npids * 2]
+                    .chunks_exact(2)
+                    .map(|pid| u16::from_le_bytes(pid.try_into().unwrap())),
+            );
+            Ok(())
+        })
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn fdp_placement() {
+    use crate::cmd::Write;
+
+    let mut ctrl = mock_ctrl(false);
+    let mut buf = Vec::new();
+    let log = get_fdp_config_log(&mut ctrl, 1, &mut buf).unwrap();
+    let config = log.config(0).unwrap();
+    assert!(config.desc.fdpa.fdpcv());
+    assert_eq!(config.ruhs.len(), 2);
+    let pid = config.desc.pid(1, 1);
+    assert_eq!(pid, 0x8001);
+
+    Write::new(1, 0x200, 7)
+        .with_placement(pid)
+        .exec(&mut ctrl, &mut [0u8; 4096])
+        .unwrap();
+    assert_eq!(ctrl.state.writes, [(0x200, 2, 0x8001)]);
+
+    let mut buf = Vec::new();
+    let status = get_ruh_status(&mut ctrl, 1, &mut buf).unwrap();
+    assert_eq!(ctrl.state.recv_lens, [16, 144]);
+    assert_eq!(status.descs.len(), 4);
+    assert_eq!({ status.pid(pid).unwrap().ruamw }, 0x10_0000 - 0x8001);
+
+    update_ruhs(&mut ctrl, 1, &[pid, 0x0001]).unwrap();
+    assert_eq!(ctrl.state.updated, [0x8001, 0x0001]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn fdp_config_log_len_capped() {
+    let mut ctrl = mock_ctrl(true);
+    assert!(matches!(
+        get_fdp_config_log(&mut ctrl, 1, &mut Vec::new()),
+        Err(CmdErr::Invalid)
+    ));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn update_ruhs_empty() {
+    let mut ctrl = mock_ctrl(false);
+    assert!(matches!(
+        update_ruhs(&mut ctrl, 1, &[]),
+        Err(CmdErr::Invalid)
+    ));
+    assert!(ctrl.state.updated.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn ruh_status_round_trip() {
+    use crate::util::{pattern, round_trip};
+
+    let mut bytes = pattern(16, 23);
+    bytes[0..14].fill(0);
+    round_trip::<RuhStatusHeader>(&bytes);
+    let mut bytes = pattern(32, 23);
+    bytes[16..32].fill(0);
+    round_trip::<RuhStatusDesc>(&bytes);
+}
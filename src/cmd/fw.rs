@@ -0,0 +1,142 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::cmd::{exec_admin, AdminOpcode, Cmd, CmdErr, Completion, Transport};
+
+/// Firmware Image Download command, transferring one piece of an image.
+#[derive(Clone, Copy, Debug)]
+pub struct FwImageDownload {
+    /// Byte offset of the piece within the image, a multiple of 4.
+    pub ofst: u32,
+}
+
+impl FwImageDownload {
+    pub fn new(ofst: u32) -> Self {
+        FwImageDownload { ofst }
+    }
+
+    /// Command transferring `len` bytes, a non-zero multiple of 4.
+    pub fn cmd(&self, len: usize) -> Cmd {
+        Cmd {
+            cdw10: (len / 4).saturating_sub(1) as u32,
+            cdw11: self.ofst / 4,
+            ..Cmd::new(AdminOpcode::FwImageDownload)
+        }
+    }
+
+    pub fn exec<T: Transport>(
+        &self,
+        transport: &mut T,
+        data: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(data.len()), data)
+    }
+}
+
+/// Download `image` with as many Firmware Image Download commands as
+/// needed. `chunk_len` is rounded down to a multiple of 4 and must satisfy
+/// the controller's update granularity (`fwug`) and maximum data transfer
+/// size. An image whose length isn't a multiple of 4 is zero padded.
+#[cfg(feature = "alloc")]
+pub fn download_fw<T: Transport>(
+    transport: &mut T,
+    image: &[u8],
+    chunk_len: usize,
+) -> Result<(), CmdErr<T::Err>> {
+    let chunk_len = (chunk_len / 4).max(1) * 4;
+    let mut buf = Vec::with_capacity(chunk_len);
+    for (idx, chunk) in image.chunks(chunk_len).enumerate() {
+        buf.clear();
+        buf.extend_from_slice(chunk);
+        buf.resize(chunk.len().div_ceil(4) * 4, 0);
+        FwImageDownload::new((idx * chunk_len) as u32).exec(transport, &mut buf)?;
+    }
+    Ok(())
+}
+
+/// Commit Action of the Firmware Commit command
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FwCommitAction {
+    /// Replace the image in the slot without activating it.
+    Replace = 0x0,
+    /// Replace the image in the slot and activate it at the next reset.
+    ReplaceActivate = 0x1,
+    /// Activate the image in the slot at the next reset.
+    Activate = 0x2,
+    /// Replace and activate the image in the slot without a reset.
+    ReplaceActivateNow = 0x3,
+    /// Replace the boot partition selected by `bpid`.
+    ReplaceBootPartition = 0x6,
+    /// Mark the boot partition selected by `bpid` active.
+    ActivateBootPartition = 0x7,
+}
+
+/// Firmware Commit command.
+#[derive(Clone, Copy, Debug)]
+pub struct FwCommit {
+    pub ca: FwCommitAction,
+    /// Firmware slot, or 0 for the controller to choose one.
+    pub fs: u8,
+    /// Boot partition for the boot partition commit actions.
+    pub bpid: bool,
+}
+
+impl FwCommit {
+    pub fn new(ca: FwCommitAction, fs: u8) -> Self {
+        FwCommit {
+            ca,
+            fs,
+            bpid: false,
+        }
+    }
+
+    pub fn boot_partition(ca: FwCommitAction, bpid: bool) -> Self {
+        FwCommit { ca, fs: 0, bpid }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            cdw10: (self.fs as u32 & 0x7) | (self.ca as u32) << 3 | (self.bpid as u32) << 31,
+            ..Cmd::new(AdminOpcode::FwCommit)
+        }
+    }
+
+    pub fn exec<T: Transport>(&self, transport: &mut T) -> Result<Completion, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(), &mut [])
+    }
+}
+
+#[test]
+fn fw_cmds() {
+    let cmd = FwImageDownload::new(0x2000).cmd(4096);
+    assert_eq!(({ cmd.cdw10 }, { cmd.cdw11 }), (0x3ff, 0x800));
+    assert_eq!({ FwImageDownload::new(0).cmd(0).cdw10 }, 0);
+
+    let cmd = FwCommit::new(FwCommitAction::ReplaceActivate, 2).cmd();
+    assert_eq!({ cmd.cdw10 }, 0x0a);
+    let cmd = FwCommit::boot_partition(FwCommitAction::ActivateBootPartition, true).cmd();
+    assert_eq!({ cmd.cdw10 }, 0x8000_0038);
+}
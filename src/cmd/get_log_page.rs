@@ -0,0 +1,153 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{exec_admin, AdminOpcode, Cmd, CmdErr, Completion, Transport, NSID_ALL},
+    AsBytes, LogPage, TransmuteSafe,
+};
+
+/// Get Log Page command.
+#[derive(Clone, Copy, Debug)]
+pub struct GetLogPage {
+    pub nsid: u32,
+    pub lid: u8,
+    pub lsp: u8,
+    pub rae: bool,
+    pub lsi: u16,
+    pub lpo: u64,
+    pub uuid_idx: u8,
+    /// Command Set Identifier, for logs specific to an I/O command set.
+    pub csi: u8,
+}
+
+impl GetLogPage {
+    pub fn new(lid: u8) -> Self {
+        GetLogPage {
+            nsid: NSID_ALL,
+            lid,
+            lsp: 0,
+            rae: false,
+            lsi: 0,
+            lpo: 0,
+            uuid_idx: 0,
+            csi: 0,
+        }
+    }
+
+    /// Get the log page decoded by `L`.
+    pub fn of<L: LogPage>() -> Self {
+        Self::new(L::LID as u8)
+    }
+
+    pub fn with_nsid(self, nsid: u32) -> Self {
+        GetLogPage { nsid, ..self }
+    }
+
+    pub fn with_lsp(self, lsp: u8) -> Self {
+        GetLogPage { lsp, ..self }
+    }
+
+    pub fn with_rae(self, rae: bool) -> Self {
+        GetLogPage { rae, ..self }
+    }
+
+    pub fn with_lsi(self, lsi: u16) -> Self {
+        GetLogPage { lsi, ..self }
+    }
+
+    pub fn with_lpo(self, lpo: u64) -> Self {
+        GetLogPage { lpo, ..self }
+    }
+
+    pub fn with_uuid_idx(self, uuid_idx: u8) -> Self {
+        GetLogPage { uuid_idx, ..self }
+    }
+
+    pub fn with_csi(self, csi: u8) -> Self {
+        GetLogPage { csi, ..self }
+    }
+
+    /// Build the command to transfer `len` bytes, which must be a non-zero
+    /// multiple of 4.
+    pub fn cmd(&self, len: usize) -> Cmd {
+        let numd = (len / 4).saturating_sub(1) as u32;
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.lid as u32
+                | (self.lsp as u32 & 0x7f) << 8
+                | (self.rae as u32) << 15
+                | (numd & 0xffff) << 16,
+            cdw11: numd >> 16 | (self.lsi as u32) << 16,
+            cdw12: self.lpo as u32,
+            cdw13: (self.lpo >> 32) as u32,
+            cdw14: self.uuid_idx as u32 & 0x7f | (self.csi as u32) << 24,
+            ..Cmd::new(AdminOpcode::GetLogPage)
+        }
+    }
+
+    /// Read the log page into `buf`.
+    pub fn read<T: Transport>(
+        &self,
+        transport: &mut T,
+        buf: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(buf.len()), buf)
+    }
+
+    /// Read a fixed size log page in its entirety.
+    pub fn read_log<L, T>(&self, transport: &mut T) -> Result<L, CmdErr<T::Err>>
+    where
+        L: TransmuteSafe + Sized,
+        T: Transport,
+    {
+        // SAFETY: Any bit pattern is valid for a TransmuteSafe structure.
+        let mut log: L = unsafe { core::mem::zeroed() };
+        self.read(transport, log.as_bytes_mut())?;
+        Ok(log)
+    }
+}
+
+/// Read the log page decoded by `L` for all namespaces.
+pub fn get_log<L, T>(transport: &mut T) -> Result<L, CmdErr<T::Err>>
+where
+    L: LogPage + Sized,
+    T: Transport,
+{
+    GetLogPage::of::<L>().read_log(transport)
+}
+
+#[test]
+fn get_log_page_cmd() {
+    let cmd = GetLogPage::new(0x0d)
+        .with_lsp(0x01)
+        .with_rae(true)
+        .with_lsi(0x1234)
+        .with_lpo(0x1_0000_0200)
+        .with_csi(0x2)
+        .cmd(0x40_0000);
+    let (opcode, nsid) = (cmd.opcode, cmd.nsid);
+    let (cdw10, cdw11, cdw12, cdw13) = (cmd.cdw10, cmd.cdw11, cmd.cdw12, cmd.cdw13);
+    assert_eq!(opcode, 0x02);
+    assert_eq!(nsid, NSID_ALL);
+    assert_eq!(cdw10, 0xffff_810d);
+    assert_eq!(cdw11, 0x1234_000f);
+    assert_eq!(cdw12, 0x0000_0200);
+    assert_eq!(cdw13, 0x0000_0001);
+    assert_eq!({ cmd.cdw14 }, 0x0200_0000);
+}
@@ -0,0 +1,154 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{exec_admin, AdminOpcode, Cmd, CmdErr, Completion, Transport},
+    AsBytes, TransmuteSafe,
+};
+
+/// Controller or Namespace Structure
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum Cns {
+    Nmsp = 0x00,
+    Ctrl = 0x01,
+    ActiveNmspList = 0x02,
+    NmspIdDescList = 0x03,
+    NvmSetList = 0x04,
+    IoCmdSetNmsp = 0x05,
+    IoCmdSetCtrl = 0x06,
+    IoCmdSetActiveNmspList = 0x07,
+    IoCmdSetIndepNmsp = 0x08,
+    AllocNmspList = 0x10,
+    AllocNmsp = 0x11,
+    NmspCtrlList = 0x12,
+    CtrlList = 0x13,
+    PrimaryCtrlCaps = 0x14,
+    SecondaryCtrlList = 0x15,
+    NmspGranularityList = 0x16,
+    UuidList = 0x17,
+    DomainList = 0x18,
+    EndurGrpList = 0x19,
+    IoCmdSetAllocNmspList = 0x1a,
+    IoCmdSetAllocNmsp = 0x1b,
+    IoCmdSet = 0x1c,
+}
+
+/// Command Set Identifier
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum Csi {
+    Nvm = 0x0,
+    KeyValue = 0x1,
+    Zoned = 0x2,
+}
+
+/// Identify command. Every data structure it returns is 4096 bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct Identify {
+    pub cns: u8,
+    pub nsid: u32,
+    pub cntid: u16,
+    pub cns_specific_id: u16,
+    pub csi: u8,
+    pub uuid_idx: u8,
+}
+
+impl Identify {
+    pub fn new(cns: Cns) -> Self {
+        Identify {
+            cns: cns as u8,
+            nsid: 0,
+            cntid: 0,
+            cns_specific_id: 0,
+            csi: 0,
+            uuid_idx: 0,
+        }
+    }
+
+    pub fn with_nsid(self, nsid: u32) -> Self {
+        Identify { nsid, ..self }
+    }
+
+    pub fn with_cntid(self, cntid: u16) -> Self {
+        Identify { cntid, ..self }
+    }
+
+    pub fn with_cns_specific_id(self, cns_specific_id: u16) -> Self {
+        Identify {
+            cns_specific_id,
+            ..self
+        }
+    }
+
+    pub fn with_csi(self, csi: u8) -> Self {
+        Identify { csi, ..self }
+    }
+
+    pub fn with_uuid_idx(self, uuid_idx: u8) -> Self {
+        Identify { uuid_idx, ..self }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.cns as u32 | (self.cntid as u32) << 16,
+            cdw11: self.cns_specific_id as u32 | (self.csi as u32) << 24,
+            cdw14: self.uuid_idx as u32 & 0x7f,
+            ..Cmd::new(AdminOpcode::Identify)
+        }
+    }
+
+    /// Read the data structure into `buf`, which should be 4096 bytes.
+    pub fn read<T: Transport>(
+        &self,
+        transport: &mut T,
+        buf: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(), buf)
+    }
+
+    /// Read the data structure decoded by `D`, e.g. `IdCtrl` for `Cns::Ctrl`.
+    pub fn read_data<D, T>(&self, transport: &mut T) -> Result<D, CmdErr<T::Err>>
+    where
+        D: TransmuteSafe + Sized,
+        T: Transport,
+    {
+        // SAFETY: Any bit pattern is valid for a TransmuteSafe structure.
+        let mut data: D = unsafe { core::mem::zeroed() };
+        self.read(transport, data.as_bytes_mut())?;
+        Ok(data)
+    }
+}
+
+#[test]
+fn identify_cmd() {
+    let cmd = Identify::new(Cns::ActiveNmspList)
+        .with_nsid(0x400)
+        .with_cntid(0x12)
+        .with_csi(0x2)
+        .cmd();
+    let (opcode, nsid, cdw10, cdw11) = (cmd.opcode, cmd.nsid, cmd.cdw10, cmd.cdw11);
+    assert_eq!(opcode, 0x06);
+    assert_eq!(nsid, 0x400);
+    assert_eq!(cdw10, 0x0012_0002);
+    assert_eq!(cdw11, 0x0200_0000);
+}
@@ -0,0 +1,380 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::mem::size_of;
+
+use num_traits::FromPrimitive;
+
+#[cfg(feature = "alloc")]
+use crate::{cmd::GetLogPage, LbaStatusLog, LbaStatusLogHeader, LogPageId};
+use crate::{
+    cmd::{exec_admin, AdminOpcode, Cmd, CmdErr, Transport},
+    FromBytes, LbaStatusAtype, Reserved, TransmuteSafe,
+};
+
+/// LBA Status Descriptor List header, which is followed by `nlsd` LBA Status
+/// Descriptors
+#[test_structure(size = 8)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LbaStatusListHeader {
+    #[loc(0:3)]
+    pub nlsd: u32,
+    #[loc(4:4)]
+    pub cmpc: u8,
+    #[loc(5:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd5: Reserved<3>,
+}
+
+impl TransmuteSafe for LbaStatusListHeader {}
+
+impl LbaStatusListHeader {
+    pub fn cmpc(&self) -> Option<LbaStatusCmpc> {
+        FromPrimitive::from_u8(self.cmpc)
+    }
+}
+
+/// LBA Status Descriptor
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LbaStatusDesc {
+    #[loc(0:7)]
+    pub dslba: u64,
+    #[loc(8:11)]
+    pub nlb: u32,
+    #[loc(12:12)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd12: Reserved<1>,
+    #[loc(13:13)]
+    pub status: u8,
+    #[loc(14:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd14: Reserved<2>,
+}
+
+impl TransmuteSafe for LbaStatusDesc {}
+
+/// Completion Condition of Get LBA Status
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LbaStatusCmpc {
+    /// Stopped once MNDW was reached; descriptors may follow the last one
+    /// returned.
+    Incomplete = 0x1,
+    /// Every LBA in the range was examined.
+    Complete = 0x2,
+}
+
+/// LBA Status Descriptor List returned by Get LBA Status.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LbaStatusList<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub header: &'a LbaStatusListHeader,
+    pub descs: &'a [LbaStatusDesc],
+}
+
+impl<'a> LbaStatusList<'a> {
+    /// Parse the header and whichever descriptors it counts that fit in
+    /// `bytes`. On error, the length of the header is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<LbaStatusListHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| LbaStatusListHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let descs = &bytes[header_len..];
+        let len = (header.nlsd as usize).min(descs.len() / size_of::<LbaStatusDesc>());
+        let descs = <[LbaStatusDesc]>::from_bytes(&descs[..len * size_of::<LbaStatusDesc>()])
+            .expect("align 1");
+        Ok(LbaStatusList { header, descs })
+    }
+
+    /// LBA following the last descriptor, from which to continue when the
+    /// list is incomplete.
+    pub fn next_slba(&self) -> Option<u64> {
+        let last = self.descs.last()?;
+        Some(last.dslba.saturating_add(last.nlb as u64))
+    }
+}
+
+/// Get LBA Status command.
+#[derive(Clone, Copy, Debug)]
+pub struct GetLbaStatus {
+    pub nsid: u32,
+    pub slba: u64,
+    /// Range Length, where 0 examines every LBA from `slba` to the end of the
+    /// namespace.
+    pub rl: u16,
+    pub atype: LbaStatusAtype,
+}
+
+impl GetLbaStatus {
+    pub fn new(nsid: u32, slba: u64, atype: LbaStatusAtype) -> Self {
+        GetLbaStatus {
+            nsid,
+            slba,
+            rl: 0,
+            atype,
+        }
+    }
+
+    pub fn with_rl(self, rl: u16) -> Self {
+        GetLbaStatus { rl, ..self }
+    }
+
+    /// Build the command to transfer at most `len` bytes, which must be a
+    /// non-zero multiple of 4.
+    pub fn cmd(&self, len: usize) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.slba as u32,
+            cdw11: (self.slba >> 32) as u32,
+            cdw12: (len / 4).saturating_sub(1) as u32,
+            cdw13: self.rl as u32 | (self.atype as u32) << 24,
+            ..Cmd::new(AdminOpcode::GetLbaStatus)
+        }
+    }
+
+    /// Read the LBA Status Descriptor List into `buf`, whose length bounds
+    /// the number of descriptors returned. `buf` must hold at least the
+    /// header.
+    pub fn read<'b, T: Transport>(
+        &self,
+        transport: &mut T,
+        buf: &'b mut [u8],
+    ) -> Result<LbaStatusList<'b>, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(buf.len()), buf)?;
+        Ok(LbaStatusList::parse(buf).expect("buffer holds the header"))
+    }
+}
+
+/// Longest LBA Status Information log `get_lba_status_log` will read.
+pub const LBA_STATUS_LOG_MAX_LEN: usize = 16 << 20;
+
+/// Read the LBA Status Information log into `buf`.
+///
+/// The header is first read with RAE set to size `buf`. The log is then read
+/// with RAE cleared, which clears the asynchronous event, growing `buf` and
+/// reading again if it grew in between. A log longer than
+/// `LBA_STATUS_LOG_MAX_LEN` is rejected with `CmdErr::Invalid`.
+#[cfg(feature = "alloc")]
+pub fn get_lba_status_log<'b, T: Transport>(
+    transport: &mut T,
+    buf: &'b mut Vec<u8>,
+) -> Result<LbaStatusLog<'b>, CmdErr<T::Err>> {
+    let log = GetLogPage::new(LogPageId::LbaStatusInfo as u8).with_nsid(0);
+    let mut header = [0u8; size_of::<LbaStatusLogHeader>()];
+    log.with_rae(true).read(transport, &mut header)?;
+    let header = LbaStatusLogHeader::from_bytes(&header[..]).expect("complete header");
+    let mut len = (header.lslplen as usize).max(size_of::<LbaStatusLogHeader>());
+    loop {
+        if len > LBA_STATUS_LOG_MAX_LEN {
+            return Err(CmdErr::Invalid);
+        }
+        buf.resize((len + 3) & !3, 0);
+        log.read(transport, buf)?;
+        match LbaStatusLog::parse(buf) {
+            Ok(_) => break,
+            Err(need) => len = need.max(buf.len() + 4),
+        }
+    }
+    Ok(LbaStatusLog::parse(buf).expect("complete log"))
+}
+
+/// Range of potentially unrecoverable LBAs reported by Get LBA Status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnrecoverableLbas {
+    pub nsid: u32,
+    pub slba: u64,
+    pub nlb: u32,
+}
+
+/// Locate potentially unrecoverable LBAs after the controller reports an LBA
+/// Status Information Alert. Every range in the LBA Status Information log is
+/// examined with Get LBA Status using the recommended action type, with at
+/// most `chunk_len` bytes of descriptors returned per command.
+///
+/// If the log changed while the ranges were examined, `CmdErr::Changed` is
+/// returned and the search should be retried.
+#[cfg(feature = "alloc")]
+pub fn get_unrecoverable_lbas<T: Transport>(
+    transport: &mut T,
+    chunk_len: usize,
+) -> Result<Vec<UnrecoverableLbas>, CmdErr<T::Err>> {
+    let mut buf = Vec::new();
+    let log = get_lba_status_log(transport, &mut buf)?;
+    let lsgc = log.header().lsgc;
+    let mut descs = vec![0u8; chunk_len.max(size_of::<LbaStatusListHeader>()) & !3];
+    let mut lbas = Vec::new();
+    for nmsp in log.nmsps() {
+        let (nsid, atype) = match nmsp.elem.ratype() {
+            Some(atype) => (nmsp.elem.neid, atype),
+            None => continue,
+        };
+        for range in nmsp.ranges {
+            let end = range.rslba.saturating_add(range.rnlb as u64);
+            let mut slba = range.rslba;
+            while slba < end {
+                let rl = (end - slba).min(u16::MAX as u64) as u16;
+                let list = GetLbaStatus::new(nsid, slba, atype)
+                    .with_rl(rl)
+                    .read(transport, &mut descs)?;
+                lbas.extend(list.descs.iter().map(|desc| UnrecoverableLbas {
+                    nsid,
+                    slba: desc.dslba,
+                    nlb: desc.nlb,
+                }));
+                slba = match (list.header.cmpc(), list.next_slba()) {
+                    (Some(LbaStatusCmpc::Incomplete), Some(next)) if next > slba => next,
+                    _ => slba + rl as u64,
+                };
+            }
+        }
+    }
+
+    let mut header = [0u8; size_of::<LbaStatusLogHeader>()];
+    GetLogPage::new(LogPageId::LbaStatusInfo as u8)
+        .with_nsid(0)
+        .with_rae(true)
+        .read(transport, &mut header)?;
+    let header = LbaStatusLogHeader::from_bytes(&header[..]).expect("complete header");
+    if header.lsgc != lsgc {
+        return Err(CmdErr::Changed);
+    }
+    Ok(lbas)
+}
+
+#[test]
+fn get_lba_status_cmd() {
+    let cmd = GetLbaStatus::new(1, 0x1_2345_6789, LbaStatusAtype::Tracked)
+        .with_rl(0x100)
+        .cmd(4096);
+    assert_eq!(cmd.opcode, 0x86);
+    assert_eq!({ cmd.nsid }, 1);
+    assert_eq!({ cmd.cdw10 }, 0x2345_6789);
+    assert_eq!({ cmd.cdw11 }, 0x1);
+    assert_eq!({ cmd.cdw12 }, 1023);
+    assert_eq!({ cmd.cdw13 }, 0x1100_0100);
+}
+
+/// Controller tracking unrecoverable LBAs 0x100-0x107 and 0x20000-0x20003 of
+/// namespace 1, returning one descriptor per Get LBA Status. Its state is the
+/// (SLBA, RL) of each command. A garbled log is all ones.
+#[cfg(all(test, feature = "alloc"))]
+fn mock_ctrl(garbled: bool) -> crate::cmd::mock::MockCtrl<Vec<(u64, u16)>> {
+    const TRACKED: [(u64, u32); 2] = [(0x100, 8), (0x2_0000, 4)];
+
+    crate::cmd::mock::MockCtrl::new(Vec::new())
+        .on_log(LogPageId::LbaStatusInfo, move |_, _, data| {
+            if garbled {
+                data.fill(0xff);
+                return Ok(());
+            }
+            let mut log = vec![0u8; 48];
+            log[0..4].copy_from_slice(&48u32.to_le_bytes());
+            log[4] = 1;
+            log[8] = 12;
+            log[14..16].copy_from_slice(&1u16.to_le_bytes());
+            log[16] = 1;
+            log[20] = 1;
+            log[24] = 0x11;
+            log[32..40].copy_from_slice(&0u64.to_le_bytes());
+            log[40..44].copy_from_slice(&0x3_0000u32.to_le_bytes());
+            log.resize(log.len().max(data.len()), 0);
+            data.copy_from_slice(&log[..data.len()]);
+            Ok(())
+        })
+        .on_admin(AdminOpcode::GetLbaStatus, |cmds, cmd, data| {
+            assert_eq!(cmd.cdw13 >> 24, 0x11);
+            let slba = cmd.cdw10 as u64 | (cmd.cdw11 as u64) << 32;
+            let rl = cmd.cdw13 as u16;
+            cmds.push((slba, rl));
+            let found: Vec<_> = TRACKED
+                .iter()
+                .filter(|(dslba, _)| (slba..slba + rl as u64).contains(dslba))
+                .collect();
+            data.fill(0);
+            data[0] = found.len().min(1) as u8;
+            data[4] = if found.len() > 1 { 0x1 } else { 0x2 };
+            if let Some((dslba, nlb)) = found.first() {
+                data[8..16].copy_from_slice(&dslba.to_le_bytes());
+                data[16..20].copy_from_slice(&nlb.to_le_bytes());
+            }
+            Ok(())
+        })
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn unrecoverable_lbas() {
+    let mut ctrl = mock_ctrl(false);
+    let lbas = get_unrecoverable_lbas(&mut ctrl, 4096).unwrap();
+    assert_eq!(
+        lbas,
+        [
+            UnrecoverableLbas {
+                nsid: 1,
+                slba: 0x100,
+                nlb: 8
+            },
+            UnrecoverableLbas {
+                nsid: 1,
+                slba: 0x2_0000,
+                nlb: 4
+            },
+        ]
+    );
+    assert_eq!(
+        ctrl.state,
+        [
+            (0, 0xffff),
+            (0xffff, 0xffff),
+            (0x1_fffe, 0xffff),
+            (0x2_fffd, 3)
+        ]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn lba_status_log_len_capped() {
+    let mut ctrl = mock_ctrl(true);
+    assert!(matches!(
+        get_lba_status_log(&mut ctrl, &mut Vec::new()),
+        Err(CmdErr::Invalid)
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn lba_status_list_header_round_trip() {
+    let mut bytes = crate::util::pattern(8, 14);
+    bytes[5..8].fill(0);
+    crate::util::round_trip::<LbaStatusListHeader>(&bytes);
+}
@@ -0,0 +1,165 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{exec_admin, AdminOpcode, Cmd, CmdErr, Completion, GetLogPage, Transport},
+    LockdownContent, LockdownLog, LockdownScope,
+};
+
+/// Interface from which a lockdown prohibits a command or feature
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockdownInterface {
+    /// In-band, through the admin submission queue.
+    AdminSq = 0x0,
+    AdminSqAndMgmtEndpoint = 0x1,
+    /// Out-of-band, through NVMe-MI management endpoints.
+    MgmtEndpoint = 0x2,
+}
+
+/// Lockdown command.
+#[derive(Clone, Copy, Debug)]
+pub struct Lockdown {
+    pub scp: LockdownScope,
+    /// Prohibit the command or feature if set, otherwise allow it.
+    pub prhbt: bool,
+    pub ifc: LockdownInterface,
+    /// Opcode or FID within `scp`.
+    pub ofi: u8,
+    pub uuid_idx: u8,
+}
+
+impl Lockdown {
+    pub fn prohibit(scp: LockdownScope, ofi: u8) -> Self {
+        Lockdown {
+            scp,
+            prhbt: true,
+            ifc: LockdownInterface::AdminSq,
+            ofi,
+            uuid_idx: 0,
+        }
+    }
+
+    pub fn allow(scp: LockdownScope, ofi: u8) -> Self {
+        Lockdown {
+            prhbt: false,
+            ..Self::prohibit(scp, ofi)
+        }
+    }
+
+    /// Interface the lockdown applies to. Defaults to the admin submission
+    /// queue.
+    pub fn with_ifc(self, ifc: LockdownInterface) -> Self {
+        Lockdown { ifc, ..self }
+    }
+
+    pub fn with_uuid_idx(self, uuid_idx: u8) -> Self {
+        Lockdown { uuid_idx, ..self }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            cdw10: self.scp as u32
+                | (self.prhbt as u32) << 4
+                | (self.ifc as u32) << 5
+                | (self.ofi as u32) << 8,
+            cdw14: self.uuid_idx as u32 & 0x7f,
+            ..Cmd::new(AdminOpcode::Lockdown)
+        }
+    }
+
+    pub fn exec<T: Transport>(&self, transport: &mut T) -> Result<Completion, CmdErr<T::Err>> {
+        exec_admin(transport, &self.cmd(), &mut [])
+    }
+}
+
+/// Read the Command and Feature Lockdown log listing the commands or
+/// features within `scp` which are prohibited, or which may be.
+pub fn get_lockdown_log<T: Transport>(
+    transport: &mut T,
+    scp: LockdownScope,
+    cntype: LockdownContent,
+) -> Result<LockdownLog, CmdErr<T::Err>> {
+    GetLogPage::of::<LockdownLog>()
+        .with_nsid(0)
+        .with_lsp(scp as u8 | (cntype as u8) << 4)
+        .read_log(transport)
+}
+
+#[test]
+fn lockdown_cmd() {
+    let cmd = Lockdown::prohibit(LockdownScope::AdminCmd, AdminOpcode::FormatNvm as u8)
+        .with_ifc(LockdownInterface::AdminSqAndMgmtEndpoint)
+        .cmd();
+    assert_eq!(cmd.opcode, 0x24);
+    assert_eq!({ cmd.cdw10 }, 0x8030);
+
+    let cmd = Lockdown::allow(LockdownScope::SetFeatures, 0x0b)
+        .with_uuid_idx(2)
+        .cmd();
+    assert_eq!(({ cmd.cdw10 }, { cmd.cdw14 }), (0x0b02, 2));
+}
+
+#[test]
+fn lockdown_in_band() {
+    use crate::{cmd::mock::MockCtrl, LockdownContent, LogPageId};
+
+    // The state is the opcodes prohibited so far.
+    let mut ctrl = MockCtrl::new(Vec::new())
+        .on_admin(AdminOpcode::Lockdown, |prohibited, cmd, _| {
+            let cdw10 = cmd.cdw10;
+            assert_eq!(cdw10 & 0xff, 0x10);
+            let ofi = (cdw10 >> 8) as u8;
+            if !prohibited.contains(&ofi) {
+                prohibited.push(ofi);
+            }
+            Ok(())
+        })
+        .on_log(LogPageId::Lockdown, |prohibited, cmd, data| {
+            assert_eq!((cmd.cdw10 >> 8) as u8, 0x20);
+            data.fill(0);
+            data[0] = 0x20;
+            data[3] = prohibited.len() as u8;
+            data[4..4 + prohibited.len()].copy_from_slice(prohibited);
+            Ok(())
+        });
+    let opcodes = [
+        AdminOpcode::DeleteIoSq,
+        AdminOpcode::FormatNvm,
+        AdminOpcode::Sanitize,
+        AdminOpcode::FwCommit,
+    ];
+    for &opcode in opcodes.iter() {
+        Lockdown::prohibit(LockdownScope::AdminCmd, opcode as u8)
+            .exec(&mut ctrl)
+            .unwrap();
+    }
+
+    let log = get_lockdown_log(
+        &mut ctrl,
+        LockdownScope::AdminCmd,
+        LockdownContent::Prohibited,
+    )
+    .unwrap();
+    assert_eq!(log.scp(), Some(LockdownScope::AdminCmd));
+    assert_eq!(log.cntype(), Some(LockdownContent::Prohibited));
+    assert!(opcodes.iter().all(|&opcode| log.contains(opcode as u8)));
+    assert!(!log.contains(AdminOpcode::GetLogPage as u8));
+    assert_eq!(log.list().count(), 4);
+}
@@ -0,0 +1,147 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+//! Controller double shared by the command tests.
+
+use crate::{
+    cmd::{AdminOpcode, Cmd, Completion, IoOpcode, Transport},
+    LogPageId, StatusCodeType, StatusField,
+};
+
+/// Outcome of a command: `Err` completes it with that status.
+pub(crate) type Reply = Result<(), StatusField>;
+
+type Hook<S> = Box<dyn FnMut(&mut S, &Cmd, &mut [u8]) -> Reply>;
+
+/// Controller which answers each command with the hook registered for its
+/// opcode, or for Get Log Page, for its log identifier. Hooks share `state`,
+/// which tests inspect afterwards. A command without a hook panics.
+pub(crate) struct MockCtrl<S> {
+    pub state: S,
+    admin: Vec<(u8, Hook<S>)>,
+    logs: Vec<(u8, Hook<S>)>,
+    io: Vec<(u8, Hook<S>)>,
+}
+
+impl<S> MockCtrl<S> {
+    pub fn new(state: S) -> Self {
+        MockCtrl {
+            state,
+            admin: Vec::new(),
+            logs: Vec::new(),
+            io: Vec::new(),
+        }
+    }
+
+    pub fn on_admin(
+        mut self,
+        opcode: AdminOpcode,
+        hook: impl FnMut(&mut S, &Cmd, &mut [u8]) -> Reply + 'static,
+    ) -> Self {
+        self.admin.push((opcode as u8, Box::new(hook)));
+        self
+    }
+
+    /// Answer Get Log Page for `lid`, whose transfer length has already been
+    /// checked against the buffer.
+    pub fn on_log(
+        mut self,
+        lid: LogPageId,
+        hook: impl FnMut(&mut S, &Cmd, &mut [u8]) -> Reply + 'static,
+    ) -> Self {
+        self.logs.push((lid as u8, Box::new(hook)));
+        self
+    }
+
+    pub fn on_io(
+        mut self,
+        opcode: IoOpcode,
+        hook: impl FnMut(&mut S, &Cmd, &mut [u8]) -> Reply + 'static,
+    ) -> Self {
+        self.io.push((opcode as u8, Box::new(hook)));
+        self
+    }
+}
+
+fn dispatch<S>(
+    hooks: &mut [(u8, Hook<S>)],
+    state: &mut S,
+    key: u8,
+    cmd: &Cmd,
+    data: &mut [u8],
+) -> Option<Completion> {
+    let (_, hook) = hooks.iter_mut().find(|(id, _)| *id == key)?;
+    let status_field = hook(state, cmd, data)
+        .err()
+        .unwrap_or_else(StatusField::new);
+    Some(completion(status_field))
+}
+
+impl<S> Transport for MockCtrl<S> {
+    type Err = ();
+
+    fn admin(&mut self, cmd: &Cmd, data: &mut [u8]) -> Result<Completion, ()> {
+        let opcode = cmd.opcode;
+        if opcode == AdminOpcode::GetLogPage as u8 {
+            let lid = cmd.cdw10 as u8;
+            let numd = (cmd.cdw10 >> 16 | (cmd.cdw11 & 0xffff) << 16) as usize;
+            assert_eq!(numd + 1, data.len() / 4, "log page {:#x} length", lid);
+            let completion = dispatch(&mut self.logs, &mut self.state, lid, cmd, data);
+            Ok(completion.unwrap_or_else(|| panic!("unexpected log page {:#x}", lid)))
+        } else {
+            let completion = dispatch(&mut self.admin, &mut self.state, opcode, cmd, data);
+            Ok(completion.unwrap_or_else(|| panic!("unexpected admin opcode {:#x}", opcode)))
+        }
+    }
+
+    fn io(&mut self, cmd: &Cmd, data: &mut [u8]) -> Result<Completion, ()> {
+        let opcode = cmd.opcode;
+        let completion = dispatch(&mut self.io, &mut self.state, opcode, cmd, data);
+        Ok(completion.unwrap_or_else(|| panic!("unexpected I/O opcode {:#x}", opcode)))
+    }
+}
+
+pub(crate) fn completion(status_field: StatusField) -> Completion {
+    Completion {
+        dw0: 0,
+        dw1: 0,
+        sqhd: 0,
+        sqid: 0,
+        cid: 0,
+        status_field,
+    }
+}
+
+/// Status of a failed command.
+pub(crate) fn failure(sct: StatusCodeType, sc: u8) -> StatusField {
+    StatusField::new().with_sct(sct).with_sc(sc)
+}
+
+/// Log page offset of a Get Log Page command.
+pub(crate) fn lpo(cmd: &Cmd) -> usize {
+    ((cmd.cdw13 as u64) << 32 | cmd.cdw12 as u64) as usize
+}
+
+/// Copy `log` from `lpo` into `data`, zero filling past its end.
+pub(crate) fn copy_log(log: &[u8], lpo: usize, data: &mut [u8]) {
+    let log = log.get(lpo..).unwrap_or(&[]);
+    let len = log.len().min(data.len());
+    data[..len].copy_from_slice(&log[..len]);
+    data[len..].fill(0);
+}
@@ -0,0 +1,320 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+//! Admin and I/O command construction and submission.
+//!
+//! Commands are built as submission queue entries and handed to a
+//! [`Transport`], which is implemented by the caller for whichever interface
+//! reaches the controller (e.g. the Linux NVMe passthrough ioctl).
+
+use crate::{GenericStatus, StatusCodeType, StatusField, TransmuteSafe};
+
+#[cfg(feature = "alloc")]
+mod ana;
+#[cfg(feature = "alloc")]
+pub use ana::*;
+mod async_event;
+pub use async_event::*;
+#[cfg(feature = "alloc")]
+mod boot_partition;
+#[cfg(feature = "alloc")]
+pub use boot_partition::*;
+mod capacity;
+pub use capacity::*;
+mod caps;
+pub use caps::*;
+mod endur_grp;
+pub use endur_grp::*;
+#[cfg(feature = "alloc")]
+mod event_agg;
+#[cfg(feature = "alloc")]
+pub use event_agg::*;
+mod fdp;
+pub use fdp::*;
+mod fw;
+pub use fw::*;
+mod get_log_page;
+pub use get_log_page::*;
+mod identify;
+pub use identify::*;
+mod lba_status;
+pub use lba_status::*;
+mod lockdown;
+pub use lockdown::*;
+#[cfg(test)]
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+mod mock;
+#[cfg(feature = "alloc")]
+mod nmsp_change;
+#[cfg(feature = "alloc")]
+pub use nmsp_change::*;
+#[cfg(feature = "alloc")]
+mod persist_event;
+#[cfg(feature = "alloc")]
+pub use persist_event::*;
+mod pred_lat;
+pub use pred_lat::*;
+mod reservation;
+pub use reservation::*;
+mod rotational;
+pub use rotational::*;
+mod sanitize;
+pub use sanitize::*;
+mod self_test;
+pub use self_test::*;
+#[cfg(feature = "alloc")]
+mod telemetry;
+#[cfg(feature = "alloc")]
+pub use telemetry::*;
+mod write;
+pub use write::*;
+mod zone_mgmt;
+pub use zone_mgmt::*;
+
+/// Namespace identifier which applies a command to all namespaces.
+pub const NSID_ALL: u32 = 0xffff_ffff;
+
+/// Submission Queue Entry
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[derive(Clone, Copy, Default)]
+pub struct Cmd {
+    #[loc(0:0)]
+    pub opcode: u8,
+    #[loc(1:1)]
+    pub flags: u8,
+    #[loc(2:3)]
+    pub cid: u16,
+    #[loc(4:7)]
+    pub nsid: u32,
+    #[loc(8:11)]
+    pub cdw2: u32,
+    #[loc(12:15)]
+    pub cdw3: u32,
+    #[loc(16:23)]
+    pub mptr: u64,
+    #[loc(24:39)]
+    pub dptr: [u64; 2],
+    #[loc(40:43)]
+    pub cdw10: u32,
+    #[loc(44:47)]
+    pub cdw11: u32,
+    #[loc(48:51)]
+    pub cdw12: u32,
+    #[loc(52:55)]
+    pub cdw13: u32,
+    #[loc(56:59)]
+    pub cdw14: u32,
+    #[loc(60:63)]
+    pub cdw15: u32,
+}
+
+impl TransmuteSafe for Cmd {}
+
+impl Cmd {
+    pub fn new(opcode: AdminOpcode) -> Self {
+        Cmd {
+            opcode: opcode as u8,
+            ..Default::default()
+        }
+    }
+
+    pub fn new_io(opcode: IoOpcode) -> Self {
+        Cmd {
+            opcode: opcode as u8,
+            ..Default::default()
+        }
+    }
+}
+
+/// Completion Queue Entry
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct Completion {
+    #[loc(0:3)]
+    pub dw0: u32,
+    #[loc(4:7)]
+    pub dw1: u32,
+    #[loc(8:9)]
+    pub sqhd: u16,
+    #[loc(10:11)]
+    pub sqid: u16,
+    #[loc(12:13)]
+    pub cid: u16,
+    #[loc(14:15)]
+    pub status_field: StatusField,
+}
+
+impl TransmuteSafe for Completion {}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum AdminOpcode {
+    DeleteIoSq = 0x00,
+    CreateIoSq = 0x01,
+    GetLogPage = 0x02,
+    DeleteIoCq = 0x04,
+    CreateIoCq = 0x05,
+    Identify = 0x06,
+    Abort = 0x08,
+    SetFeatures = 0x09,
+    GetFeatures = 0x0a,
+    AsyncEventReq = 0x0c,
+    NmspMgmt = 0x0d,
+    FwCommit = 0x10,
+    FwImageDownload = 0x11,
+    DevSelfTest = 0x14,
+    NmspAttachment = 0x15,
+    KeepAlive = 0x18,
+    DirectiveSend = 0x19,
+    DirectiveReceive = 0x1a,
+    VirtMgmt = 0x1c,
+    NvmeMiSend = 0x1d,
+    NvmeMiReceive = 0x1e,
+    CapacityMgmt = 0x20,
+    Lockdown = 0x24,
+    DoorbellBufConf = 0x7c,
+    FormatNvm = 0x80,
+    SecuritySend = 0x81,
+    SecurityReceive = 0x82,
+    Sanitize = 0x84,
+    GetLbaStatus = 0x86,
+}
+
+/// NVM Command Set I/O opcodes
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum IoOpcode {
+    Flush = 0x00,
+    Write = 0x01,
+    Read = 0x02,
+    WriteUncorrectable = 0x04,
+    Compare = 0x05,
+    WriteZeros = 0x08,
+    DatasetMgmt = 0x09,
+    Verify = 0x0c,
+    ReservationRegister = 0x0d,
+    ReservationReport = 0x0e,
+    ReservationAcquire = 0x11,
+    IoMgmtRecv = 0x12,
+    ReservationRelease = 0x15,
+    Copy = 0x19,
+    IoMgmtSend = 0x1d,
+    ZoneMgmtRecv = 0x7a,
+}
+
+/// Interface to submit commands to a controller.
+pub trait Transport {
+    type Err;
+
+    /// Execute an admin command, transferring `data` to or from the
+    /// controller as implied by the opcode. A completion must be returned
+    /// regardless of its status; only failures to reach the controller are
+    /// errors.
+    fn admin(&mut self, cmd: &Cmd, data: &mut [u8]) -> Result<Completion, Self::Err>;
+
+    /// Execute an I/O command on any I/O queue, with the same semantics as
+    /// [`Transport::admin`]. Transports which only reach the admin queue
+    /// needn't implement this; every I/O command then completes with Invalid
+    /// Command Opcode.
+    fn io(&mut self, cmd: &Cmd, data: &mut [u8]) -> Result<Completion, Self::Err> {
+        let _ = (cmd, data);
+        Ok(Completion {
+            dw0: 0,
+            dw1: 0,
+            sqhd: 0,
+            sqid: 0,
+            cid: 0,
+            status_field: StatusField::new()
+                .with_sct(StatusCodeType::Generic)
+                .with_sc(GenericStatus::InvalidCmdOpcode as u8),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum CmdErr<E> {
+    Transport(E),
+    Status(StatusField),
+    /// Data spanning multiple commands changed before it was fully read.
+    Changed,
+    /// An operation did not finish before its deadline.
+    Timeout,
+    /// The request can't be expressed by the command, or the controller
+    /// returned data which is inconsistent with it.
+    Invalid,
+}
+
+/// Execute an admin command, treating any unsuccessful status as an error.
+pub fn exec_admin<T: Transport>(
+    transport: &mut T,
+    cmd: &Cmd,
+    data: &mut [u8],
+) -> Result<Completion, CmdErr<T::Err>> {
+    check_status(transport.admin(cmd, data).map_err(CmdErr::Transport)?)
+}
+
+/// Execute an I/O command, treating any unsuccessful status as an error.
+pub fn exec_io<T: Transport>(
+    transport: &mut T,
+    cmd: &Cmd,
+    data: &mut [u8],
+) -> Result<Completion, CmdErr<T::Err>> {
+    check_status(transport.io(cmd, data).map_err(CmdErr::Transport)?)
+}
+
+fn check_status<E>(completion: Completion) -> Result<Completion, CmdErr<E>> {
+    let status_field = completion.status_field;
+    if status_field.successful() {
+        Ok(completion)
+    } else {
+        Err(CmdErr::Status(status_field))
+    }
+}
+
+#[test]
+fn io_unsupported() {
+    use crate::StatusCode;
+
+    struct AdminOnly;
+
+    impl Transport for AdminOnly {
+        type Err = ();
+
+        fn admin(&mut self, _cmd: &Cmd, _data: &mut [u8]) -> Result<Completion, ()> {
+            panic!("no admin command expected");
+        }
+    }
+
+    let release = ReservationRelease::new(
+        1,
+        ReservationReleaseAction::Release,
+        ReservationType::WriteExclusive,
+    );
+    match release.exec(&mut AdminOnly) {
+        Err(CmdErr::Status(status)) => assert_eq!(
+            status.status_code(),
+            StatusCode::Generic(GenericStatus::InvalidCmdOpcode)
+        ),
+        _ => panic!("expected invalid opcode"),
+    }
+}
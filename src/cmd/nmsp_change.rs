@@ -0,0 +1,121 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use alloc::vec::Vec;
+
+use crate::{
+    cmd::{CmdErr, Cns, GetLogPage, Identify, Transport},
+    ChangedNmspList, NmspDiff, NmspInventory, NmspList,
+};
+
+/// Read every active namespace identifier, continuing past the 1024 returned
+/// by each Identify command. If the controller returns identifiers out of
+/// ascending order, or not above the one requested, `CmdErr::Invalid` is
+/// returned rather than requesting the same list again.
+pub fn active_nmsps<T: Transport>(transport: &mut T) -> Result<Vec<u32>, CmdErr<T::Err>> {
+    let mut nsids: Vec<u32> = Vec::new();
+    loop {
+        let start = nsids.last().copied().unwrap_or(0);
+        let list: NmspList = Identify::new(Cns::ActiveNmspList)
+            .with_nsid(start)
+            .read_data(transport)?;
+        for nsid in list.nsids() {
+            if nsid <= nsids.last().copied().unwrap_or(start) {
+                return Err(CmdErr::Invalid);
+            }
+            nsids.push(nsid);
+        }
+        if !list.is_full() {
+            return Ok(nsids);
+        }
+    }
+}
+
+/// Handle a namespace attribute notice: read the Changed Namespace List,
+/// which clears the event, then diff it against `inventory` using the
+/// current active namespaces and bring `inventory` up to date.
+pub fn nmsp_changes<T: Transport>(
+    transport: &mut T,
+    inventory: &mut NmspInventory,
+) -> Result<NmspDiff, CmdErr<T::Err>> {
+    let changes: ChangedNmspList = GetLogPage::of::<ChangedNmspList>().read_log(transport)?;
+    let diff = inventory.diff(&changes, active_nmsps(transport)?);
+    inventory.apply(&diff);
+    Ok(diff)
+}
+
+/// Controller with namespaces 1 through 1500 active, except 2, which has just
+/// been detached. Namespaces 2 and 1500 are reported as changed. Its state is
+/// the starting identifier of each Identify, which returns the first 1024
+/// namespaces whatever that is if `ignore_start`.
+#[cfg(test)]
+fn mock_ctrl(ignore_start: bool) -> crate::cmd::mock::MockCtrl<Vec<u32>> {
+    use crate::{
+        cmd::{mock::MockCtrl, AdminOpcode},
+        LogPageId,
+    };
+
+    fn nmsp_list(nsids: impl Iterator<Item = u32>, data: &mut [u8]) {
+        let mut bytes: Vec<u8> = nsids.flat_map(|nsid| nsid.to_le_bytes()).collect();
+        bytes.resize(4096, 0);
+        data.copy_from_slice(&bytes);
+    }
+
+    MockCtrl::new(Vec::new())
+        .on_log(LogPageId::ChangedNmspList, |_, cmd, data| {
+            assert_eq!(cmd.cdw10 >> 15 & 1, 0);
+            nmsp_list([2, 1500].iter().copied(), data);
+            Ok(())
+        })
+        .on_admin(AdminOpcode::Identify, move |identifies, cmd, data| {
+            assert_eq!({ cmd.cdw10 }, 0x02);
+            identifies.push(cmd.nsid);
+            let start = if ignore_start { 0 } else { cmd.nsid };
+            nmsp_list(
+                (start + 1..=1500).filter(|&nsid| nsid != 2).take(1024),
+                data,
+            );
+            Ok(())
+        })
+}
+
+#[test]
+fn nmsp_change_rescan() {
+    let mut ctrl = mock_ctrl(false);
+    let mut inventory: NmspInventory = (1..1500).collect();
+    let diff = nmsp_changes(&mut ctrl, &mut inventory).unwrap();
+    assert_eq!(ctrl.state, [0, 1025]);
+    assert_eq!(diff.attached, [1500]);
+    assert_eq!(diff.detached, [2]);
+    assert!(diff.changed.is_empty());
+    assert_eq!(diff.rescan().collect::<Vec<_>>(), [1500]);
+    assert!(!inventory.contains(2));
+    assert!(inventory.contains(1500));
+    assert_eq!(inventory.len(), 1499);
+}
+
+#[test]
+fn active_nmsps_ignored_start() {
+    let mut ctrl = mock_ctrl(true);
+    match active_nmsps(&mut ctrl) {
+        Err(CmdErr::Invalid) => (),
+        _ => panic!("expected invalid namespace list"),
+    }
+    assert_eq!(ctrl.state, [0, 1025]);
+}
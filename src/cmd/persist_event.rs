@@ -0,0 +1,274 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    LogPageId, PersistEventLog, PersistEventLogHeader,
+};
+
+/// Action taken on the Persistent Event Log's reporting context, given as
+/// the log specific parameter.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PersistEventAction {
+    /// Read from the established context.
+    Read = 0b00,
+    /// Establish a context, capturing the events logged so far, and read
+    /// from it.
+    EstablishCtx = 0b01,
+    ReleaseCtx = 0b10,
+}
+
+impl GetLogPage {
+    /// Get Log Page for the Persistent Event Log with `action` applied to the
+    /// reporting context.
+    pub fn persist_event(action: PersistEventAction) -> Self {
+        GetLogPage::new(LogPageId::PersistEvent as u8).with_lsp(action as u8)
+    }
+}
+
+/// Longest Persistent Event Log `get_persist_event_log` will read.
+pub const PERSIST_EVENT_LOG_MAX_LEN: usize = 64 << 20;
+
+/// Read the Persistent Event Log into `buf` in chunks of at most `chunk_len`
+/// bytes, which should be a multiple of 4 within the controller's maximum
+/// data transfer size. A log whose total length exceeds
+/// `PERSIST_EVENT_LOG_MAX_LEN` is rejected with `CmdErr::Invalid`.
+///
+/// A reporting context is established while reading the header, so that
+/// later chunks are consistent with it, and released afterwards even if a
+/// read fails.
+pub fn get_persist_event_log<'b, T: Transport>(
+    transport: &mut T,
+    chunk_len: usize,
+    buf: &'b mut Vec<u8>,
+) -> Result<PersistEventLog<'b>, CmdErr<T::Err>> {
+    let read = read_persist_event_log(transport, chunk_len, buf);
+    let mut header = [0u8; size_of::<PersistEventLogHeader>()];
+    let release =
+        GetLogPage::persist_event(PersistEventAction::ReleaseCtx).read(transport, &mut header);
+    read?;
+    release?;
+    PersistEventLog::parse(buf).map_err(|_| CmdErr::Invalid)
+}
+
+fn read_persist_event_log<T: Transport>(
+    transport: &mut T,
+    chunk_len: usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), CmdErr<T::Err>> {
+    let header_len = size_of::<PersistEventLogHeader>();
+    buf.clear();
+    buf.resize(header_len, 0);
+    GetLogPage::persist_event(PersistEventAction::EstablishCtx).read(transport, buf)?;
+    let len = match PersistEventLog::parse(buf) {
+        Ok(_) => return Ok(()),
+        Err(len) if len > PERSIST_EVENT_LOG_MAX_LEN => return Err(CmdErr::Invalid),
+        Err(len) => len,
+    };
+
+    // Transfers are in whole dwords, so read up to the next one and trim.
+    let chunk_len = (chunk_len & !3).max(4);
+    let padded_len = len.checked_add(3).ok_or(CmdErr::Invalid)? & !3;
+    buf.resize(padded_len, 0);
+    let mut lpo = header_len;
+    while lpo < padded_len {
+        let end = (lpo + chunk_len).min(padded_len);
+        GetLogPage::persist_event(PersistEventAction::Read)
+            .with_lpo(lpo as u64)
+            .read(transport, &mut buf[lpo..end])?;
+        lpo = end;
+    }
+    buf.truncate(len);
+    Ok(())
+}
+
+/// Persistent Event Log holding a SMART snapshot, a firmware commit and a
+/// power on event, which requires a reporting context to read. Each action
+/// taken on the context is recorded, and reading at `fail_at` fails.
+#[cfg(test)]
+struct MockPersistEvent {
+    log: Vec<u8>,
+    ctx: bool,
+    actions: Vec<u8>,
+    fail_at: Option<usize>,
+}
+
+#[cfg(test)]
+fn mock_ctrl() -> crate::cmd::mock::MockCtrl<MockPersistEvent> {
+    use crate::{
+        cmd::mock::{failure, lpo, MockCtrl},
+        StatusCodeType,
+    };
+    use alloc::vec;
+
+    fn persist_event(etype: u8, ets: u64, data: &[u8]) -> Vec<u8> {
+        let mut event = vec![0u8; 24];
+        event[0] = etype;
+        event[2] = 21;
+        event[6..14].copy_from_slice(&ets.to_le_bytes());
+        event[22..24].copy_from_slice(&(data.len() as u16).to_le_bytes());
+        event.extend_from_slice(data);
+        event
+    }
+
+    let mut smart = vec![0u8; 512];
+    smart[1..3].copy_from_slice(&310u16.to_le_bytes());
+    let mut fw_commit = vec![0u8; 22];
+    fw_commit[..8].copy_from_slice(b"1.0.0   ");
+    fw_commit[8..16].copy_from_slice(b"1.1.0   ");
+    fw_commit[16] = 0x3;
+    fw_commit[17] = 2;
+    let mut pwr_on = b"1.1.0   ".to_vec();
+    for cntlid in 1..=2u16 {
+        let mut info = [0u8; 36];
+        info[0..2].copy_from_slice(&cntlid.to_le_bytes());
+        info[16..20].copy_from_slice(&42u32.to_le_bytes());
+        pwr_on.extend_from_slice(&info);
+    }
+
+    let mut log = vec![0u8; 512];
+    log[0] = 0x0d;
+    log[4] = 3;
+    log[56..76].copy_from_slice(b"S4EWNX0R123456      ");
+    log[480] = 0b0001_0110;
+    log.extend(persist_event(0x01, 1000, &smart));
+    log.extend(persist_event(0x02, 2000, &fw_commit));
+    log.extend(persist_event(0x04, 3000, &pwr_on));
+    let tll = log.len() as u64;
+    log[8..16].copy_from_slice(&tll.to_le_bytes());
+    let persist_event = MockPersistEvent {
+        log,
+        ctx: false,
+        actions: Vec::new(),
+        fail_at: None,
+    };
+
+    MockCtrl::new(persist_event).on_log(LogPageId::PersistEvent, |persist_event, cmd, data| {
+        let (action, lpo) = ((cmd.cdw10 >> 8 & 0x3) as u8, lpo(cmd));
+        persist_event.actions.push(action);
+        match action {
+            0b01 => persist_event.ctx = true,
+            0b10 => {
+                persist_event.ctx = false;
+                return Ok(());
+            }
+            _ => {}
+        }
+        if !persist_event.ctx || persist_event.fail_at == Some(lpo) {
+            return Err(failure(StatusCodeType::Generic, 0x02));
+        }
+        let mut log = persist_event.log.clone();
+        log.resize(log.len().max(lpo + data.len()), 0);
+        data.copy_from_slice(&log[lpo..lpo + data.len()]);
+        Ok(())
+    })
+}
+
+#[test]
+fn persist_event_log() {
+    use crate::{PersistEventData, PersistEventType};
+
+    let mut ctrl = mock_ctrl();
+    let mut buf = Vec::new();
+    let log = get_persist_event_log(&mut ctrl, 256, &mut buf).unwrap();
+    let actions = &ctrl.state.actions;
+    assert!(!ctrl.state.ctx);
+    assert_eq!(actions.first(), Some(&0b01));
+    assert_eq!(actions.last(), Some(&0b10));
+    assert!(actions[1..actions.len() - 1]
+        .iter()
+        .all(|&action| action == 0b00));
+
+    let header = log.header();
+    assert!(header.supports(PersistEventType::Smart));
+    assert!(header.supports(PersistEventType::PwrOnReset));
+    assert!(!header.supports(PersistEventType::TimestampChange));
+    assert_eq!(header.sn.to_str().unwrap(), "S4EWNX0R123456      ");
+
+    let events: Vec<_> = log.events().collect();
+    assert_eq!(
+        events
+            .iter()
+            .map(|event| event.header.timestamp())
+            .collect::<Vec<_>>(),
+        [1000, 2000, 3000]
+    );
+    match events[0].data {
+        PersistEventData::Smart(smart) => assert_eq!({ smart.comp_temp }, 310),
+        _ => panic!("expected SMART snapshot"),
+    }
+    match events[1].data {
+        PersistEventData::FwCommit(event) => {
+            assert_eq!(event.new_fr.to_str().unwrap(), "1.1.0   ");
+            assert_eq!((event.ca, event.fs), (0x3, 2));
+        }
+        _ => panic!("expected firmware commit"),
+    }
+    match events[2].data {
+        PersistEventData::PwrOnReset { fr, infos } => {
+            assert_eq!(fr.to_str().unwrap(), "1.1.0   ");
+            assert_eq!(
+                infos
+                    .iter()
+                    .map(|info| (info.cntlid, info.ctrl_pwr_cycle))
+                    .collect::<Vec<_>>(),
+                [(1, 42), (2, 42)]
+            );
+        }
+        _ => panic!("expected power on or reset"),
+    }
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(log).unwrap();
+        assert_eq!(json["events"][1]["data"]["fw_commit"]["fs"], 2);
+    }
+}
+
+#[test]
+fn persist_event_log_releases_ctx() {
+    let mut ctrl = mock_ctrl();
+    ctrl.state.fail_at = Some(768);
+    let mut buf = Vec::new();
+    assert!(matches!(
+        get_persist_event_log(&mut ctrl, 256, &mut buf),
+        Err(CmdErr::Status(_))
+    ));
+    assert!(!ctrl.state.ctx);
+    assert_eq!(ctrl.state.actions, [0b01, 0b00, 0b00, 0b10]);
+}
+
+#[test]
+fn persist_event_log_bogus_len() {
+    for &tll in [u64::MAX, 1 << 40].iter() {
+        let mut ctrl = mock_ctrl();
+        ctrl.state.log[8..16].copy_from_slice(&tll.to_le_bytes());
+        let mut buf = Vec::new();
+        assert!(matches!(
+            get_persist_event_log(&mut ctrl, 256, &mut buf),
+            Err(CmdErr::Invalid)
+        ));
+        assert!(!ctrl.state.ctx);
+        assert_eq!(ctrl.state.actions, [0b01, 0b10]);
+        assert!(buf.len() <= PERSIST_EVENT_LOG_MAX_LEN);
+    }
+}
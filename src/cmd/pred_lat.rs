@@ -0,0 +1,53 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    PredLatLog,
+};
+
+/// Read the Predictable Latency Per NVM Set log of NVM Set `nvmsetid`.
+pub fn get_pred_lat_log<T: Transport>(
+    transport: &mut T,
+    nvmsetid: u16,
+) -> Result<PredLatLog, CmdErr<T::Err>> {
+    GetLogPage::of::<PredLatLog>()
+        .with_nsid(0)
+        .with_lsi(nvmsetid)
+        .read_log(transport)
+}
+
+#[test]
+fn pred_lat_log() {
+    use crate::{cmd::mock::MockCtrl, LogPageId, PredLatWindow};
+
+    let mut ctrl = MockCtrl::new(()).on_log(LogPageId::PredLat, |_, cmd, data| {
+        assert_eq!(cmd.cdw11 >> 16, 2);
+        data[0] = 0x1;
+        data[2] = 0x5;
+        data[48..56].copy_from_slice(&500u64.to_le_bytes());
+        Ok(())
+    });
+    let log = get_pred_lat_log(&mut ctrl, 2).unwrap();
+    assert_eq!(log.status(), Some(PredLatWindow::Dtwin));
+    assert!(log.event_type().dtwin_reads_warning());
+    assert!(log.event_type().dtwin_time_warning());
+    assert!(!log.event_type().dtwin_exceeded());
+    assert_eq!({ log.dtwin_tmax }, 500);
+}
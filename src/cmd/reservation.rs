@@ -0,0 +1,697 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use num_traits::FromPrimitive;
+
+use crate::{
+    cmd::{exec_io, Cmd, CmdErr, Completion, IoOpcode, Transport},
+    FromBytes, Reserved, TransmuteSafe,
+};
+
+/// Reservation Type
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReservationType {
+    WriteExclusive = 0x1,
+    ExclusiveAccess = 0x2,
+    WriteExclusiveRegistrantsOnly = 0x3,
+    ExclusiveAccessRegistrantsOnly = 0x4,
+    WriteExclusiveAllRegistrants = 0x5,
+    ExclusiveAccessAllRegistrants = 0x6,
+}
+
+/// Reservation Register Action
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservationRegisterAction {
+    Register = 0x0,
+    Unregister = 0x1,
+    Replace = 0x2,
+}
+
+/// Change Persist Through Power Loss State
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cptpl {
+    NoChange = 0x0,
+    Clear = 0x2,
+    Set = 0x3,
+}
+
+/// Reservation Acquire Action
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservationAcquireAction {
+    Acquire = 0x0,
+    Preempt = 0x1,
+    PreemptAndAbort = 0x2,
+}
+
+/// Reservation Release Action
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReservationReleaseAction {
+    Release = 0x0,
+    Clear = 0x1,
+}
+
+fn key_pair(first: u64, second: u64) -> [u8; 16] {
+    let mut data = [0u8; 16];
+    data[..8].copy_from_slice(&first.to_le_bytes());
+    data[8..].copy_from_slice(&second.to_le_bytes());
+    data
+}
+
+/// Reservation Register command.
+#[derive(Clone, Copy, Debug)]
+pub struct ReservationRegister {
+    pub nsid: u32,
+    pub rrega: ReservationRegisterAction,
+    /// Ignore Existing Key
+    pub iekey: bool,
+    pub cptpl: Cptpl,
+    /// Current Reservation Key, checked unless registering.
+    pub crkey: u64,
+    /// New Reservation Key, used when registering or replacing.
+    pub nrkey: u64,
+}
+
+impl ReservationRegister {
+    pub fn new(nsid: u32, rrega: ReservationRegisterAction) -> Self {
+        ReservationRegister {
+            nsid,
+            rrega,
+            iekey: false,
+            cptpl: Cptpl::NoChange,
+            crkey: 0,
+            nrkey: 0,
+        }
+    }
+
+    pub fn with_iekey(self, iekey: bool) -> Self {
+        ReservationRegister { iekey, ..self }
+    }
+
+    pub fn with_cptpl(self, cptpl: Cptpl) -> Self {
+        ReservationRegister { cptpl, ..self }
+    }
+
+    pub fn with_crkey(self, crkey: u64) -> Self {
+        ReservationRegister { crkey, ..self }
+    }
+
+    pub fn with_nrkey(self, nrkey: u64) -> Self {
+        ReservationRegister { nrkey, ..self }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.rrega as u32 | (self.iekey as u32) << 3 | (self.cptpl as u32) << 30,
+            ..Cmd::new_io(IoOpcode::ReservationRegister)
+        }
+    }
+
+    /// Reservation Register Data Structure
+    pub fn data(&self) -> [u8; 16] {
+        key_pair(self.crkey, self.nrkey)
+    }
+
+    pub fn exec<T: Transport>(&self, transport: &mut T) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(), &mut self.data())
+    }
+}
+
+/// Reservation Acquire command.
+#[derive(Clone, Copy, Debug)]
+pub struct ReservationAcquire {
+    pub nsid: u32,
+    pub racqa: ReservationAcquireAction,
+    pub rtype: ReservationType,
+    /// Ignore Existing Key
+    pub iekey: bool,
+    /// Current Reservation Key
+    pub crkey: u64,
+    /// Preempt Reservation Key, of the registrant to preempt.
+    pub prkey: u64,
+}
+
+impl ReservationAcquire {
+    pub fn new(nsid: u32, racqa: ReservationAcquireAction, rtype: ReservationType) -> Self {
+        ReservationAcquire {
+            nsid,
+            racqa,
+            rtype,
+            iekey: false,
+            crkey: 0,
+            prkey: 0,
+        }
+    }
+
+    pub fn with_iekey(self, iekey: bool) -> Self {
+        ReservationAcquire { iekey, ..self }
+    }
+
+    pub fn with_crkey(self, crkey: u64) -> Self {
+        ReservationAcquire { crkey, ..self }
+    }
+
+    pub fn with_prkey(self, prkey: u64) -> Self {
+        ReservationAcquire { prkey, ..self }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.racqa as u32 | (self.iekey as u32) << 3 | (self.rtype as u32) << 8,
+            ..Cmd::new_io(IoOpcode::ReservationAcquire)
+        }
+    }
+
+    /// Reservation Acquire Data Structure
+    pub fn data(&self) -> [u8; 16] {
+        key_pair(self.crkey, self.prkey)
+    }
+
+    pub fn exec<T: Transport>(&self, transport: &mut T) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(), &mut self.data())
+    }
+}
+
+/// Reservation Release command.
+#[derive(Clone, Copy, Debug)]
+pub struct ReservationRelease {
+    pub nsid: u32,
+    pub rrela: ReservationReleaseAction,
+    pub rtype: ReservationType,
+    /// Ignore Existing Key
+    pub iekey: bool,
+    /// Current Reservation Key
+    pub crkey: u64,
+}
+
+impl ReservationRelease {
+    pub fn new(nsid: u32, rrela: ReservationReleaseAction, rtype: ReservationType) -> Self {
+        ReservationRelease {
+            nsid,
+            rrela,
+            rtype,
+            iekey: false,
+            crkey: 0,
+        }
+    }
+
+    pub fn with_iekey(self, iekey: bool) -> Self {
+        ReservationRelease { iekey, ..self }
+    }
+
+    pub fn with_crkey(self, crkey: u64) -> Self {
+        ReservationRelease { crkey, ..self }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.rrela as u32 | (self.iekey as u32) << 3 | (self.rtype as u32) << 8,
+            ..Cmd::new_io(IoOpcode::ReservationRelease)
+        }
+    }
+
+    /// Reservation Release Data Structure
+    pub fn data(&self) -> [u8; 8] {
+        self.crkey.to_le_bytes()
+    }
+
+    pub fn exec<T: Transport>(&self, transport: &mut T) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(), &mut self.data())
+    }
+}
+
+/// Reservation Report command.
+#[derive(Clone, Copy, Debug)]
+pub struct ReservationReport {
+    pub nsid: u32,
+    /// Extended Data Structure, which reports 128-bit host identifiers.
+    pub eds: bool,
+}
+
+impl ReservationReport {
+    pub fn new(nsid: u32) -> Self {
+        ReservationReport { nsid, eds: false }
+    }
+
+    pub fn with_eds(self, eds: bool) -> Self {
+        ReservationReport { eds, ..self }
+    }
+
+    /// Build the command to transfer `len` bytes, which must be a non-zero
+    /// multiple of 4.
+    pub fn cmd(&self, len: usize) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: (len / 4).saturating_sub(1) as u32,
+            cdw11: self.eds as u32,
+            ..Cmd::new_io(IoOpcode::ReservationReport)
+        }
+    }
+
+    /// Read the Reservation Status Data Structure into `buf`, growing it and
+    /// reading again until every registrant fits.
+    #[cfg(feature = "alloc")]
+    pub fn read<'b, T: Transport>(
+        &self,
+        transport: &mut T,
+        buf: &'b mut Vec<u8>,
+    ) -> Result<ReservationStatus<'b>, CmdErr<T::Err>> {
+        let min_len = ReservationStatus::header_len(self.eds);
+        if buf.len() < min_len {
+            buf.resize(min_len, 0);
+        }
+        loop {
+            exec_io(transport, &self.cmd(buf.len()), buf)?;
+            match ReservationStatus::parse(buf, self.eds) {
+                Ok(_) => break,
+                Err(len) => buf.resize((len + 3) & !3, 0),
+            }
+        }
+        Ok(ReservationStatus::parse(buf, self.eds).expect("complete report"))
+    }
+}
+
+/// Reservation Status Data Structure header. The extended data structure
+/// pads it to 64 bytes.
+#[test_structure(size = 24)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReservationStatusHeader {
+    /// Generation, incremented on every registration change or preemption.
+    #[loc(0:3)]
+    pub gen: u32,
+    #[loc(4:4)]
+    pub rtype: u8,
+    /// Number of Registered Controllers
+    #[loc(5:6)]
+    pub regctl: u16,
+    #[loc(7:8)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd7: Reserved<2>,
+    /// Persist Through Power Loss State
+    #[loc(9:9)]
+    pub ptpls: u8,
+    #[loc(10:23)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd10: Reserved<14>,
+}
+
+impl TransmuteSafe for ReservationStatusHeader {}
+
+impl ReservationStatusHeader {
+    /// Type of the reservation held, or `None` if the namespace isn't
+    /// reserved.
+    pub fn rtype(&self) -> Option<ReservationType> {
+        FromPrimitive::from_u8(self.rtype)
+    }
+}
+
+/// Registered Controller Data Structure
+#[test_structure(size = 24)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisteredCtrl {
+    #[loc(0:1)]
+    pub cntlid: u16,
+    /// Reservation Status
+    #[loc(2:2)]
+    pub rcsts: u8,
+    #[loc(3:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd3: Reserved<5>,
+    #[loc(8:15)]
+    pub hostid: u64,
+    #[loc(16:23)]
+    pub rkey: u64,
+}
+
+impl TransmuteSafe for RegisteredCtrl {}
+
+/// Registered Controller Extended Data Structure
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegisteredCtrlExt {
+    #[loc(0:1)]
+    pub cntlid: u16,
+    /// Reservation Status
+    #[loc(2:2)]
+    pub rcsts: u8,
+    #[loc(3:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd3: Reserved<5>,
+    #[loc(8:15)]
+    pub rkey: u64,
+    #[loc(16:31)]
+    pub hostid: [u8; 16],
+    #[loc(32:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd32: Reserved<32>,
+}
+
+impl TransmuteSafe for RegisteredCtrlExt {}
+
+/// Registrant reported by either form of the Reservation Status Data
+/// Structure. A 64-bit host identifier occupies the first 8 bytes of
+/// `hostid`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Registrant {
+    pub cntlid: u16,
+    /// Whether the registrant holds the reservation.
+    pub holds: bool,
+    pub hostid: [u8; 16],
+    pub rkey: u64,
+}
+
+/// Reservation Status Data Structure returned by Reservation Report.
+#[derive(Clone, Copy)]
+pub struct ReservationStatus<'a> {
+    header: &'a ReservationStatusHeader,
+    eds: bool,
+    regctls: &'a [u8],
+}
+
+impl<'a> ReservationStatus<'a> {
+    fn header_len(eds: bool) -> usize {
+        if eds {
+            64
+        } else {
+            size_of::<ReservationStatusHeader>()
+        }
+    }
+
+    fn regctl_len(eds: bool) -> usize {
+        if eds {
+            size_of::<RegisteredCtrlExt>()
+        } else {
+            size_of::<RegisteredCtrl>()
+        }
+    }
+
+    /// Parse the header and validate that every registrant it counts is
+    /// present, with `eds` set for the extended data structure. On error, the
+    /// length needed to hold the entire structure is returned.
+    pub fn parse(bytes: &'a [u8], eds: bool) -> Result<Self, usize> {
+        let header_len = Self::header_len(eds);
+        let header = bytes
+            .get(..size_of::<ReservationStatusHeader>())
+            .filter(|_| bytes.len() >= header_len)
+            .and_then(|header| ReservationStatusHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let len = header_len + header.regctl as usize * Self::regctl_len(eds);
+        let regctls = bytes.get(header_len..len).ok_or(len)?;
+        Ok(ReservationStatus {
+            header,
+            eds,
+            regctls,
+        })
+    }
+
+    pub fn header(&self) -> &'a ReservationStatusHeader {
+        self.header
+    }
+
+    pub fn registrants(&self) -> impl Iterator<Item = Registrant> + 'a {
+        let eds = self.eds;
+        self.regctls
+            .chunks_exact(Self::regctl_len(eds))
+            .map(move |regctl| {
+                if eds {
+                    let regctl = RegisteredCtrlExt::from_bytes(regctl).expect("align 1");
+                    Registrant {
+                        cntlid: regctl.cntlid,
+                        holds: regctl.rcsts & 1 == 1,
+                        hostid: regctl.hostid,
+                        rkey: regctl.rkey,
+                    }
+                } else {
+                    let regctl = RegisteredCtrl::from_bytes(regctl).expect("align 1");
+                    let mut hostid = [0u8; 16];
+                    hostid[..8].copy_from_slice(&{ regctl.hostid }.to_le_bytes());
+                    Registrant {
+                        cntlid: regctl.cntlid,
+                        holds: regctl.rcsts & 1 == 1,
+                        hostid,
+                        rkey: regctl.rkey,
+                    }
+                }
+            })
+    }
+
+    /// Registrant holding the reservation, if any. With an all registrants
+    /// reservation type, this is the first of them.
+    pub fn holder(&self) -> Option<Registrant> {
+        self.registrants().find(|registrant| registrant.holds)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ReservationStatus<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let header = self.header;
+        let (gen, rtype, regctl, ptpls) = (header.gen, header.rtype, header.regctl, header.ptpls);
+        let mut status = serializer.serialize_struct("ReservationStatus", 5)?;
+        status.serialize_field("gen", &gen)?;
+        status.serialize_field("rtype", &rtype)?;
+        status.serialize_field("regctl", &regctl)?;
+        status.serialize_field("ptpls", &ptpls)?;
+        status.serialize_field(
+            "registrants",
+            &crate::util::SerializeIter(|| self.registrants()),
+        )?;
+        status.end()
+    }
+}
+
+#[test]
+fn reservation_cmds() {
+    let register = ReservationRegister::new(1, ReservationRegisterAction::Replace)
+        .with_iekey(true)
+        .with_cptpl(Cptpl::Set)
+        .with_crkey(0x1111)
+        .with_nrkey(0x2222);
+    let cmd = register.cmd();
+    assert_eq!((cmd.opcode, { cmd.nsid }), (0x0d, 1));
+    assert_eq!({ cmd.cdw10 }, 0xc000_000a);
+    assert_eq!(register.data()[..8], 0x1111u64.to_le_bytes());
+    assert_eq!(register.data()[8..], 0x2222u64.to_le_bytes());
+
+    let acquire = ReservationAcquire::new(
+        1,
+        ReservationAcquireAction::PreemptAndAbort,
+        ReservationType::ExclusiveAccessRegistrantsOnly,
+    );
+    let cmd = acquire.cmd();
+    assert_eq!((cmd.opcode, { cmd.cdw10 }), (0x11, 0x402));
+
+    let release = ReservationRelease::new(
+        1,
+        ReservationReleaseAction::Clear,
+        ReservationType::WriteExclusive,
+    );
+    let cmd = release.cmd();
+    assert_eq!((cmd.opcode, { cmd.cdw10 }), (0x15, 0x101));
+
+    let cmd = ReservationReport::new(1).with_eds(true).cmd(4096);
+    assert_eq!((cmd.opcode, { cmd.cdw10 }, { cmd.cdw11 }), (0x0e, 1023, 1));
+}
+
+#[test]
+fn reservation_status_parse() {
+    let mut bytes = [0u8; 64 + 2 * 64];
+    bytes[0] = 7;
+    bytes[4] = 0x3;
+    bytes[5] = 2;
+    bytes[64] = 1;
+    bytes[66] = 1;
+    bytes[72..80].copy_from_slice(&0xaau64.to_le_bytes());
+    bytes[80..96].copy_from_slice(&[0x11; 16]);
+    bytes[128] = 2;
+    bytes[136..144].copy_from_slice(&0xbbu64.to_le_bytes());
+    let status = ReservationStatus::parse(&bytes[..], true).unwrap();
+    assert_eq!(
+        status.header().rtype(),
+        Some(ReservationType::WriteExclusiveRegistrantsOnly)
+    );
+    let holder = status.holder().unwrap();
+    assert_eq!(
+        (holder.cntlid, holder.rkey, holder.hostid),
+        (1, 0xaa, [0x11; 16])
+    );
+    assert_eq!(
+        status.registrants().map(|reg| reg.rkey).collect::<Vec<_>>(),
+        [0xaa, 0xbb]
+    );
+    assert_eq!(ReservationStatus::parse(&bytes[..32], true).err(), Some(64));
+    assert_eq!(
+        ReservationStatus::parse(&bytes[..100], true).err(),
+        Some(192)
+    );
+
+    let status = ReservationStatus::parse(&bytes[..72], false).unwrap();
+    let regs: Vec<_> = status.registrants().collect();
+    assert_eq!(regs.len(), 2);
+    assert_eq!(regs[0].hostid[..8], [0; 8]);
+}
+
+/// Namespace 1 with reservation keys registered by (cntlid, rkey), tracking
+/// the holder by index and the length of each report.
+#[cfg(all(test, feature = "alloc"))]
+#[derive(Default)]
+struct MockReservations {
+    gen: u32,
+    regs: Vec<(u16, u64)>,
+    holder: Option<(usize, u8)>,
+    report_lens: Vec<usize>,
+}
+
+#[cfg(all(test, feature = "alloc"))]
+impl MockReservations {
+    fn status(&self) -> Vec<u8> {
+        let mut data = alloc::vec![0u8; 64];
+        data[0..4].copy_from_slice(&self.gen.to_le_bytes());
+        data[4] = self.holder.map_or(0, |(_, rtype)| rtype);
+        data[5..7].copy_from_slice(&(self.regs.len() as u16).to_le_bytes());
+        for (idx, (cntlid, rkey)) in self.regs.iter().enumerate() {
+            let mut regctl = [0u8; 64];
+            regctl[0..2].copy_from_slice(&cntlid.to_le_bytes());
+            regctl[2] = (self.holder.map(|(holder, _)| holder) == Some(idx)) as u8;
+            regctl[8..16].copy_from_slice(&rkey.to_le_bytes());
+            regctl[16..32].copy_from_slice(&[*cntlid as u8; 16]);
+            data.extend_from_slice(&regctl);
+        }
+        data
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn reservation_keys() {
+    use crate::{
+        cmd::mock::{failure, MockCtrl},
+        GenericStatus, StatusCode, StatusCodeType,
+    };
+    use core::convert::TryInto;
+
+    fn key(data: &[u8], idx: usize) -> u64 {
+        u64::from_le_bytes(data[idx * 8..idx * 8 + 8].try_into().unwrap())
+    }
+
+    let mut ctrl = MockCtrl::new(MockReservations::default())
+        .on_io(IoOpcode::ReservationRegister, |resv, cmd, data| {
+            assert_eq!(({ cmd.nsid }, cmd.cdw10 & 0x7), (1, 0));
+            resv.regs.push((resv.regs.len() as u16 + 1, key(data, 1)));
+            resv.gen += 1;
+            Ok(())
+        })
+        .on_io(IoOpcode::ReservationAcquire, |resv, cmd, data| {
+            assert_eq!({ cmd.nsid }, 1);
+            let idx = resv
+                .regs
+                .iter()
+                .position(|&(_, rkey)| rkey == key(data, 0))
+                .unwrap();
+            if resv.holder.is_some() && cmd.cdw10 & 0x7 == 0 {
+                return Err(failure(StatusCodeType::Generic, 0x83));
+            }
+            resv.holder = Some((idx, (cmd.cdw10 >> 8) as u8));
+            Ok(())
+        })
+        .on_io(IoOpcode::ReservationReport, |resv, cmd, data| {
+            assert_eq!(({ cmd.nsid }, { cmd.cdw11 }), (1, 1));
+            assert_eq!((cmd.cdw10 as usize + 1) * 4, data.len());
+            resv.report_lens.push(data.len());
+            let status = resv.status();
+            let len = data.len().min(status.len());
+            data[..len].copy_from_slice(&status[..len]);
+            Ok(())
+        });
+
+    for rkey in [0xa1, 0xb2] {
+        ReservationRegister::new(1, ReservationRegisterAction::Register)
+            .with_nrkey(rkey)
+            .exec(&mut ctrl)
+            .unwrap();
+    }
+    let rtype = ReservationType::WriteExclusive;
+    ReservationAcquire::new(1, ReservationAcquireAction::Acquire, rtype)
+        .with_crkey(0xa1)
+        .exec(&mut ctrl)
+        .unwrap();
+    let conflict = ReservationAcquire::new(1, ReservationAcquireAction::Acquire, rtype)
+        .with_crkey(0xb2)
+        .exec(&mut ctrl);
+    match conflict {
+        Err(CmdErr::Status(status)) => assert_eq!(
+            status.status_code(),
+            StatusCode::Generic(GenericStatus::ReservationConflict)
+        ),
+        _ => panic!("expected reservation conflict"),
+    }
+
+    let mut buf = Vec::new();
+    let status = ReservationReport::new(1)
+        .with_eds(true)
+        .read(&mut ctrl, &mut buf)
+        .unwrap();
+    assert_eq!({ status.header().gen }, 2);
+    assert_eq!(status.header().rtype(), Some(rtype));
+    let holder = status.holder().unwrap();
+    assert_eq!(
+        (holder.cntlid, holder.rkey, holder.hostid),
+        (1, 0xa1, [1; 16])
+    );
+    assert_eq!(status.registrants().count(), 2);
+    assert_eq!(ctrl.state.report_lens, [64, 192]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn reservation_status_round_trip() {
+    use crate::util::{pattern, round_trip};
+
+    let mut bytes = pattern(24, 16);
+    bytes[7..9].fill(0);
+    bytes[10..24].fill(0);
+    round_trip::<ReservationStatusHeader>(&bytes);
+    let mut bytes = pattern(24, 16);
+    bytes[3..8].fill(0);
+    round_trip::<RegisteredCtrl>(&bytes);
+    let mut bytes = pattern(64, 16);
+    bytes[3..8].fill(0);
+    bytes[32..64].fill(0);
+    round_trip::<RegisteredCtrlExt>(&bytes);
+}
@@ -0,0 +1,34 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    RotationalMediaLog,
+};
+
+/// Read the Rotational Media Information log of endurance group `endgid`.
+pub fn get_rotational_media_log<T: Transport>(
+    transport: &mut T,
+    endgid: u16,
+) -> Result<RotationalMediaLog, CmdErr<T::Err>> {
+    GetLogPage::of::<RotationalMediaLog>()
+        .with_nsid(0)
+        .with_lsi(endgid)
+        .read_log(transport)
+}
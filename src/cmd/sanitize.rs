@@ -0,0 +1,231 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::time::Duration;
+
+use crate::{
+    cmd::{exec_admin, get_log, AdminOpcode, Cmd, CmdErr, Transport},
+    SanitizeAction, SanitizeLog, SanitizeState,
+};
+
+/// Sanitize command.
+#[derive(Clone, Copy, Debug)]
+pub struct Sanitize {
+    pub sanact: SanitizeAction,
+    /// Allow Unrestricted Sanitize Exit
+    pub ause: bool,
+    /// Overwrite Pass Count, where 0 means 16 passes.
+    pub owpass: u8,
+    /// Overwrite Invert Pattern Between Passes
+    pub oipbp: bool,
+    /// No-Deallocate After Sanitize
+    pub ndas: bool,
+    /// Overwrite Pattern
+    pub ovrpat: u32,
+}
+
+impl Sanitize {
+    pub fn new(sanact: SanitizeAction) -> Self {
+        Sanitize {
+            sanact,
+            ause: false,
+            owpass: 1,
+            oipbp: false,
+            ndas: false,
+            ovrpat: 0,
+        }
+    }
+
+    pub fn with_ause(self, ause: bool) -> Self {
+        Sanitize { ause, ..self }
+    }
+
+    pub fn with_owpass(self, owpass: u8) -> Self {
+        Sanitize { owpass, ..self }
+    }
+
+    pub fn with_oipbp(self, oipbp: bool) -> Self {
+        Sanitize { oipbp, ..self }
+    }
+
+    pub fn with_ndas(self, ndas: bool) -> Self {
+        Sanitize { ndas, ..self }
+    }
+
+    pub fn with_ovrpat(self, ovrpat: u32) -> Self {
+        Sanitize { ovrpat, ..self }
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        Cmd {
+            cdw10: self.sanact as u32
+                | (self.ause as u32) << 3
+                | (self.owpass as u32 & 0xf) << 4
+                | (self.oipbp as u32) << 8
+                | (self.ndas as u32) << 9,
+            cdw11: self.ovrpat,
+            ..Cmd::new(AdminOpcode::Sanitize)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SanitizeProgress {
+    InProgress {
+        percent: u8,
+        remaining: Option<Duration>,
+    },
+    /// The operation is no longer in progress, with the state it ended in.
+    Done(Option<SanitizeState>),
+}
+
+/// A sanitize operation started on a controller, whose progress is tracked
+/// through the Sanitize Status log.
+///
+/// Iterating reads the log once per item, ending after the operation is no
+/// longer in progress; the caller decides how long to wait between items.
+pub struct SanitizeOp<'a, T: Transport> {
+    transport: &'a mut T,
+    done: bool,
+}
+
+impl<'a, T: Transport> SanitizeOp<'a, T> {
+    /// Start a sanitize operation, which continues in the background after
+    /// the command completes.
+    pub fn start(transport: &'a mut T, sanitize: &Sanitize) -> Result<Self, CmdErr<T::Err>> {
+        exec_admin(transport, &sanitize.cmd(), &mut [])?;
+        Ok(Self::resume(transport))
+    }
+
+    /// Track an operation which is already in progress, e.g. one started
+    /// before a reset.
+    pub fn resume(transport: &'a mut T) -> Self {
+        SanitizeOp {
+            transport,
+            done: false,
+        }
+    }
+
+    /// Read the current progress. Reading the log clears the sanitize
+    /// completion event.
+    pub fn poll(&mut self) -> Result<SanitizeProgress, CmdErr<T::Err>> {
+        let log: SanitizeLog = get_log(self.transport)?;
+        Ok(match log.state() {
+            Some(SanitizeState::InProgress) => SanitizeProgress::InProgress {
+                percent: log.percent(),
+                remaining: log.remaining(),
+            },
+            state => SanitizeProgress::Done(state),
+        })
+    }
+
+    /// Poll every `interval` until the operation is no longer in progress.
+    #[cfg(feature = "std")]
+    pub fn wait(mut self, interval: Duration) -> Result<Option<SanitizeState>, CmdErr<T::Err>> {
+        loop {
+            match self.poll()? {
+                SanitizeProgress::Done(state) => return Ok(state),
+                SanitizeProgress::InProgress { .. } => std::thread::sleep(interval),
+            }
+        }
+    }
+}
+
+impl<T: Transport> Iterator for SanitizeOp<'_, T> {
+    type Item = Result<SanitizeProgress, CmdErr<T::Err>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let progress = self.poll();
+        self.done = !matches!(progress, Ok(SanitizeProgress::InProgress { .. }));
+        Some(progress)
+    }
+}
+
+#[test]
+fn sanitize_cmd() {
+    let cmd = Sanitize::new(SanitizeAction::Overwrite)
+        .with_ause(true)
+        .with_owpass(3)
+        .with_oipbp(true)
+        .with_ndas(true)
+        .with_ovrpat(0xdead_beef)
+        .cmd();
+    assert_eq!(cmd.opcode, 0x84);
+    assert_eq!({ cmd.cdw10 }, 0x33b);
+    assert_eq!({ cmd.cdw11 }, 0xdead_beef);
+}
+
+#[test]
+fn sanitize_progress() {
+    use crate::{cmd::mock::MockCtrl, LogPageId};
+
+    // A crypto erase takes three reads of the Sanitize Status log. The state
+    // is the last Sanitize's CDW10 and the progress still to be reported.
+    let mut ctrl = MockCtrl::new((0u32, Vec::<u16>::new()))
+        .on_admin(AdminOpcode::Sanitize, |(cdw10, sprogs), cmd, _| {
+            *cdw10 = cmd.cdw10;
+            *sprogs = vec![0xffff, 0x8000, 0x0000];
+            Ok(())
+        })
+        .on_log(LogPageId::SanitizeStatus, |(cdw10, sprogs), _, data| {
+            data.fill(0);
+            let (sprog, sstat) = match sprogs.pop() {
+                Some(sprog) if sprog != 0xffff => (sprog, 0x2u16),
+                _ => (0xffff, 0x1),
+            };
+            data[0..2].copy_from_slice(&sprog.to_le_bytes());
+            data[2..4].copy_from_slice(&sstat.to_le_bytes());
+            data[4..8].copy_from_slice(&cdw10.to_le_bytes());
+            data[16..20].copy_from_slice(&60u32.to_le_bytes());
+            data[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+            Ok(())
+        });
+    let sanitize = Sanitize::new(SanitizeAction::CryptoErase);
+    let progress: Vec<_> = SanitizeOp::start(&mut ctrl, &sanitize)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        progress,
+        [
+            SanitizeProgress::InProgress {
+                percent: 0,
+                remaining: Some(Duration::from_secs(60)),
+            },
+            SanitizeProgress::InProgress {
+                percent: 50,
+                remaining: Some(Duration::from_secs(30)),
+            },
+            SanitizeProgress::Done(Some(SanitizeState::Completed)),
+        ]
+    );
+
+    let sanitize = sanitize.with_ndas(true);
+    let mut op = SanitizeOp::start(&mut ctrl, &sanitize).unwrap();
+    assert_eq!(
+        op.poll().unwrap(),
+        SanitizeProgress::InProgress {
+            percent: 0,
+            remaining: None,
+        }
+    );
+}
@@ -0,0 +1,249 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    cmd::{exec_admin, get_log, AdminOpcode, Cmd, CmdErr, Transport, NSID_ALL},
+    AsBytes, SelfTestCode, SelfTestLog, SelfTestResult,
+};
+
+/// Device Self-test command.
+pub fn dev_self_test(code: SelfTestCode, nsid: u32) -> Cmd {
+    Cmd {
+        nsid,
+        cdw10: code as u32,
+        ..Cmd::new(AdminOpcode::DevSelfTest)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SelfTestProgress {
+    InProgress {
+        code: Option<SelfTestCode>,
+        percent: u8,
+    },
+    Complete(SelfTestResult),
+}
+
+/// A device self-test started on a controller, whose progress is tracked
+/// through the Device Self-test log.
+pub struct SelfTest<'a, T: Transport> {
+    transport: &'a mut T,
+    code: SelfTestCode,
+    nsid: u32,
+    /// Results logged before the self-test started.
+    prev: [SelfTestResult; 20],
+}
+
+impl<'a, T: Transport> SelfTest<'a, T> {
+    /// Start a short or extended self-test of `nsid`, or of the controller
+    /// and all namespaces if `NSID_ALL`.
+    pub fn start(
+        transport: &'a mut T,
+        code: SelfTestCode,
+        nsid: u32,
+    ) -> Result<Self, CmdErr<T::Err>> {
+        let log: SelfTestLog = get_log(transport)?;
+        exec_admin(transport, &dev_self_test(code, nsid), &mut [])?;
+        Ok(SelfTest {
+            transport,
+            code,
+            nsid,
+            prev: log.results,
+        })
+    }
+
+    pub fn code(&self) -> SelfTestCode {
+        self.code
+    }
+
+    /// Read the current progress. Once the controller no longer reports a
+    /// self-test in progress, the result logged for this self-test since it
+    /// started is returned. Until one is logged, the self-test is reported
+    /// as not yet started.
+    pub fn poll(&mut self) -> Result<SelfTestProgress, CmdErr<T::Err>> {
+        let log: SelfTestLog = get_log(self.transport)?;
+        Ok(match log.curr_op() {
+            None => match log
+                .results()
+                .next()
+                .filter(|result| self.is_own(&log, result))
+            {
+                Some(result) => SelfTestProgress::Complete(*result),
+                None => SelfTestProgress::InProgress {
+                    code: None,
+                    percent: 0,
+                },
+            },
+            code => SelfTestProgress::InProgress {
+                code,
+                percent: log.curr_completion(),
+            },
+        })
+    }
+
+    /// Whether `result`, the newest in `log`, was logged for this self-test:
+    /// the results changed since it started, and it names the same test. A
+    /// test of a single namespace must also not report a different one.
+    fn is_own(&self, log: &SelfTestLog, result: &SelfTestResult) -> bool {
+        log.results.as_bytes() != self.prev.as_bytes()
+            && result.code() == Some(self.code)
+            && (self.nsid == NSID_ALL || result.nsid().unwrap_or(self.nsid) == self.nsid)
+    }
+
+    pub fn abort(self) -> Result<(), CmdErr<T::Err>> {
+        exec_admin(
+            self.transport,
+            &dev_self_test(SelfTestCode::Abort, 0),
+            &mut [],
+        )?;
+        Ok(())
+    }
+
+    /// Poll every `interval` until the self-test completes, or return
+    /// `CmdErr::Timeout` once `timeout` has passed. An extended self-test
+    /// may take as long as `IdCtrl::edst` minutes.
+    #[cfg(feature = "std")]
+    pub fn wait(
+        mut self,
+        interval: std::time::Duration,
+        timeout: std::time::Duration,
+    ) -> Result<SelfTestResult, CmdErr<T::Err>> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            match self.poll()? {
+                SelfTestProgress::Complete(result) => return Ok(result),
+                SelfTestProgress::InProgress { .. } if std::time::Instant::now() >= deadline => {
+                    return Err(CmdErr::Timeout)
+                }
+                SelfTestProgress::InProgress { .. } => std::thread::sleep(interval),
+            }
+        }
+    }
+}
+
+/// Controller which runs a self-test in two steps, failing an extended test
+/// on an unrecovered read. A stalled controller accepts the command but
+/// never starts the self-test. Its state is the self-test log.
+#[cfg(test)]
+fn mock_ctrl(stall: bool) -> crate::cmd::mock::MockCtrl<Vec<u8>> {
+    use crate::{
+        cmd::mock::{failure, MockCtrl},
+        LogPageId, StatusCodeType,
+    };
+
+    let mut steps = 0;
+    MockCtrl::new(vec![0; 564])
+        .on_admin(AdminOpcode::DevSelfTest, move |log, cmd, _| {
+            if log[0] != 0 {
+                return Err(failure(StatusCodeType::CmdSpecific, 0x1d));
+            }
+            if !stall {
+                log[0] = cmd.cdw10 as u8;
+                log[1] = 0;
+            }
+            Ok(())
+        })
+        .on_log(LogPageId::DevSelfTest, move |log, _, data| {
+            if log[0] != 0 {
+                steps += 1;
+                log[1] = steps * 50;
+                if steps == 2 {
+                    let code = log[0];
+                    let result = &mut log[4..32];
+                    result[0] = code << 4 | 0x7;
+                    result[1] = 2;
+                    result[2] = 0x0f;
+                    result[12..16].copy_from_slice(&1u32.to_le_bytes());
+                    result[16..24].copy_from_slice(&0x1000u64.to_le_bytes());
+                    result[24] = 0x2;
+                    result[25] = 0x81;
+                    log[0] = 0;
+                    log[1] = 0;
+                }
+            }
+            data.copy_from_slice(&log[..data.len()]);
+            Ok(())
+        })
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn self_test_lifecycle() {
+    use crate::{MadIntegrityStatus, SelfTestResultCode, StatusCode};
+    use std::time::Duration;
+
+    let mut ctrl = mock_ctrl(false);
+    let mut test = SelfTest::start(&mut ctrl, SelfTestCode::Extended, NSID_ALL).unwrap();
+    match test.poll().unwrap() {
+        SelfTestProgress::InProgress { code, percent } => {
+            assert_eq!(code, Some(SelfTestCode::Extended));
+            assert_eq!(percent, 50);
+        }
+        SelfTestProgress::Complete(_) => panic!("completed early"),
+    }
+    let result = test
+        .wait(Duration::from_millis(0), Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(result.result(), Some(SelfTestResultCode::SegmentsFailed));
+    assert_eq!(result.code(), Some(SelfTestCode::Extended));
+    assert_eq!(result.seg_num, 2);
+    assert_eq!(result.failing_lba(), Some(0x1000));
+    assert_eq!(
+        result.status_code(),
+        Some(StatusCode::MadIntegrity(
+            MadIntegrityStatus::UnrecoveredReadErr
+        ))
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn self_test_ignores_earlier_result() {
+    use std::time::Duration;
+
+    let mut ctrl = mock_ctrl(true);
+    // A passed short self-test of namespace 1 from before this one.
+    ctrl.state[4] = 0x10;
+    ctrl.state[6] = 0x01;
+    ctrl.state[16..20].copy_from_slice(&1u32.to_le_bytes());
+    let mut test = SelfTest::start(&mut ctrl, SelfTestCode::Short, 1).unwrap();
+    match test.poll().unwrap() {
+        SelfTestProgress::InProgress { code, percent } => assert_eq!((code, percent), (None, 0)),
+        SelfTestProgress::Complete(_) => panic!("reported an earlier result"),
+    }
+    match test.wait(Duration::from_millis(1), Duration::from_millis(5)) {
+        Err(CmdErr::Timeout) => (),
+        _ => panic!("expected timeout"),
+    }
+}
+
+#[test]
+fn self_test_in_progress() {
+    use crate::{CmdSpecificStatus, StatusCode};
+
+    let mut ctrl = mock_ctrl(false);
+    SelfTest::start(&mut ctrl, SelfTestCode::Short, 1).unwrap();
+    match SelfTest::start(&mut ctrl, SelfTestCode::Short, 1) {
+        Err(CmdErr::Status(status_field)) => assert_eq!(
+            status_field.status_code(),
+            StatusCode::CmdSpecific(CmdSpecificStatus::DeviceSelfTestInProgress)
+        ),
+        _ => panic!("expected self-test in progress"),
+    }
+}
@@ -0,0 +1,253 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use alloc::vec;
+
+use crate::{
+    cmd::{CmdErr, GetLogPage, Transport},
+    FromBytes, LogPageId, Telemetry, TelemetryDataArea, TelemetryLog, TELEMETRY_BLOCK_SIZE,
+};
+
+/// Longest telemetry log `GetTelemetry::read` will read. Data Areas 1 to 3
+/// always fit; a larger Data Area 4 is rejected.
+pub const TELEMETRY_MAX_LEN: usize = 64 << 20;
+
+/// Retrieval of a telemetry log up to some data area, split into as many Get
+/// Log Page commands as needed.
+#[derive(Clone, Copy, Debug)]
+pub struct GetTelemetry {
+    lid: LogPageId,
+    create: bool,
+    area: TelemetryDataArea,
+    chunk_len: usize,
+}
+
+impl GetTelemetry {
+    /// Telemetry Host-Initiated log. If `create` is set, the controller
+    /// captures new data before it is read; otherwise, the data from the last
+    /// capture is returned.
+    pub fn host(create: bool) -> Self {
+        GetTelemetry {
+            lid: LogPageId::TelemetryHostInit,
+            create,
+            area: TelemetryDataArea::Three,
+            chunk_len: 4096,
+        }
+    }
+
+    /// Telemetry Controller-Initiated log.
+    pub fn ctrl() -> Self {
+        GetTelemetry {
+            lid: LogPageId::TelemetryCtrlInit,
+            create: false,
+            area: TelemetryDataArea::Three,
+            chunk_len: 4096,
+        }
+    }
+
+    /// Last data area to retrieve. Defaults to Data Area 3.
+    pub fn with_area(self, area: TelemetryDataArea) -> Self {
+        GetTelemetry { area, ..self }
+    }
+
+    /// Bytes transferred per command, rounded down to whole blocks. Must not
+    /// exceed the controller's maximum data transfer size.
+    pub fn with_chunk_len(self, chunk_len: usize) -> Self {
+        GetTelemetry { chunk_len, ..self }
+    }
+
+    /// Read the header and every data area up to and including the chosen
+    /// one. Reading past the header relies on the log page offset, so the
+    /// controller must support extended Get Log Page (`Lpa::ext_get_log`).
+    ///
+    /// Controller-initiated data may be replaced while it is read, in which
+    /// case `CmdErr::Changed` is returned and the read should be retried. A
+    /// log longer than `TELEMETRY_MAX_LEN` is rejected with `CmdErr::Invalid`.
+    pub fn read<T: Transport>(&self, transport: &mut T) -> Result<Telemetry, CmdErr<T::Err>> {
+        let log = GetLogPage::new(self.lid as u8).with_rae(true);
+        let mut bytes = vec![0u8; TELEMETRY_BLOCK_SIZE];
+        log.with_lsp(self.create as u8)
+            .read(transport, &mut bytes)?;
+        let header = TelemetryLog::from_bytes(&bytes).expect("complete header");
+        let (gen_num, end) = (self.gen_num(header), header.data_area(self.area).end);
+        if end > TELEMETRY_MAX_LEN {
+            return Err(CmdErr::Invalid);
+        }
+
+        let chunk_len = (self.chunk_len / TELEMETRY_BLOCK_SIZE).max(1) * TELEMETRY_BLOCK_SIZE;
+        bytes.resize(end, 0);
+        let mut lpo = TELEMETRY_BLOCK_SIZE;
+        while lpo < end {
+            let len = chunk_len.min(end - lpo);
+            log.with_lpo(lpo as u64)
+                .read(transport, &mut bytes[lpo..lpo + len])?;
+            lpo += len;
+        }
+
+        if self.lid == LogPageId::TelemetryCtrlInit {
+            // Re-reading the header confirms the data wasn't replaced midway
+            // and clears the Telemetry Log Changed event.
+            let mut check = [0u8; TELEMETRY_BLOCK_SIZE];
+            log.with_rae(false).read(transport, &mut check)?;
+            let check = TelemetryLog::from_bytes(&check[..]).expect("complete header");
+            if self.gen_num(check) != gen_num {
+                return Err(CmdErr::Changed);
+            }
+        }
+        Ok(Telemetry::new(bytes).expect("complete header"))
+    }
+
+    /// Generation number of the data held by the log being read.
+    fn gen_num(&self, header: &TelemetryLog) -> u8 {
+        if self.lid == LogPageId::TelemetryCtrlInit {
+            header.ctrl_data_gen_num
+        } else {
+            header.gen_num
+        }
+    }
+}
+
+/// Telemetry with three data areas, and the (LSP, LPO, length) of each read.
+/// Controller-initiated data is optionally replaced after the header is
+/// first read.
+#[cfg(test)]
+struct MockTelemetry {
+    log: vec::Vec<u8>,
+    reads: vec::Vec<(u8, usize, usize)>,
+    replace: bool,
+}
+
+#[cfg(test)]
+fn mock_ctrl(replace: bool) -> crate::cmd::mock::MockCtrl<MockTelemetry> {
+    use crate::cmd::{
+        mock::{lpo, MockCtrl, Reply},
+        Cmd,
+    };
+
+    fn read_log(telemetry: &mut MockTelemetry, cmd: &Cmd, data: &mut [u8]) -> Reply {
+        let (lsp, lpo) = ((cmd.cdw10 >> 8 & 0x7f) as u8, lpo(cmd));
+        telemetry.log[0] = cmd.cdw10 as u8;
+        if lsp == 1 {
+            telemetry.log[381] += 1;
+        }
+        if telemetry.replace && lpo > 0 {
+            telemetry.log[383] += 1;
+        }
+        telemetry.reads.push((lsp, lpo, data.len()));
+        data.copy_from_slice(&telemetry.log[lpo..lpo + data.len()]);
+        Ok(())
+    }
+
+    let mut log: vec::Vec<u8> = (0..512 * 7).map(|idx| (idx / 512) as u8).collect();
+    log[..512].fill(0);
+    log[8..10].copy_from_slice(&2u16.to_le_bytes());
+    log[10..12].copy_from_slice(&5u16.to_le_bytes());
+    log[12..14].copy_from_slice(&6u16.to_le_bytes());
+    log[381] = 1;
+    log[383] = 1;
+    log[384..388].copy_from_slice(b"test");
+    let telemetry = MockTelemetry {
+        log,
+        reads: vec![],
+        replace,
+    };
+    MockCtrl::new(telemetry)
+        .on_log(LogPageId::TelemetryHostInit, read_log)
+        .on_log(LogPageId::TelemetryCtrlInit, read_log)
+}
+
+#[test]
+fn telemetry_host_create() {
+    let mut ctrl = mock_ctrl(false);
+    let telemetry = GetTelemetry::host(true)
+        .with_area(TelemetryDataArea::Two)
+        .with_chunk_len(1024)
+        .read(&mut ctrl)
+        .unwrap();
+    assert_eq!(
+        ctrl.state.reads,
+        [(1, 0, 512), (0, 512, 1024), (0, 1536, 1024), (0, 2560, 512)]
+    );
+    let header = telemetry.header();
+    let reason_id = header.reason_id;
+    assert_eq!(header.lid, 0x07);
+    assert_eq!(header.gen_num, 2);
+    assert_eq!(&reason_id[..4], b"test");
+    let da2 = telemetry.data_area(TelemetryDataArea::Two).unwrap();
+    assert_eq!(da2.len(), 1536);
+    assert_eq!((da2[0], da2[1535]), (3, 5));
+    assert_eq!(telemetry.data_area(TelemetryDataArea::Three), None);
+    assert_eq!(telemetry.as_bytes().len(), 512 * 6);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn telemetry_ctrl_data_areas() {
+    let mut ctrl = mock_ctrl(false);
+    let telemetry = GetTelemetry::ctrl().read(&mut ctrl).unwrap();
+    assert_eq!(ctrl.state.reads, [(0, 0, 512), (0, 512, 3072), (0, 0, 512)]);
+    let areas: vec::Vec<_> = telemetry
+        .data_areas()
+        .map(|(area, data)| (area, data.len()))
+        .collect();
+    assert_eq!(
+        areas,
+        [
+            (TelemetryDataArea::One, 1024),
+            (TelemetryDataArea::Two, 1536),
+            (TelemetryDataArea::Three, 512),
+            (TelemetryDataArea::Four, 0),
+        ]
+    );
+
+    let dir = std::env::temp_dir().join(format!("nvme-telemetry-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    telemetry.export(&dir).unwrap();
+    assert_eq!(std::fs::read(dir.join("header.bin")).unwrap().len(), 512);
+    assert_eq!(
+        std::fs::read(dir.join("data-area-3.bin")).unwrap(),
+        [6u8; 512]
+    );
+    assert!(std::fs::read(dir.join("data-area-4.bin"))
+        .unwrap()
+        .is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn telemetry_len_capped() {
+    let mut ctrl = mock_ctrl(false);
+    ctrl.state.log[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        GetTelemetry::ctrl()
+            .with_area(TelemetryDataArea::Four)
+            .read(&mut ctrl),
+        Err(CmdErr::Invalid)
+    ));
+    assert_eq!(ctrl.state.reads, [(0, 0, 512)]);
+}
+
+#[test]
+fn telemetry_ctrl_changed() {
+    let mut ctrl = mock_ctrl(true);
+    assert!(matches!(
+        GetTelemetry::ctrl().read(&mut ctrl),
+        Err(CmdErr::Changed)
+    ));
+}
@@ -0,0 +1,108 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::cmd::{exec_io, Cmd, CmdErr, Completion, IoOpcode, Transport};
+
+/// Directive Type
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum DirectiveType {
+    Identify = 0x0,
+    Streams = 0x1,
+    /// Flexible Data Placement, whose directive specific value is a
+    /// placement identifier.
+    DataPlacement = 0x2,
+}
+
+/// Write command.
+#[derive(Clone, Copy, Debug)]
+pub struct Write {
+    pub nsid: u32,
+    pub slba: u64,
+    /// Number of logical blocks, 0's based.
+    pub nlb: u16,
+    pub fua: bool,
+    pub dtype: Option<DirectiveType>,
+    /// Directive Specific value for `dtype`.
+    pub dspec: u16,
+}
+
+impl Write {
+    /// Write `nlb` + 1 logical blocks starting at `slba`.
+    pub fn new(nsid: u32, slba: u64, nlb: u16) -> Self {
+        Write {
+            nsid,
+            slba,
+            nlb,
+            fua: false,
+            dtype: None,
+            dspec: 0,
+        }
+    }
+
+    pub fn with_fua(self, fua: bool) -> Self {
+        Write { fua, ..self }
+    }
+
+    pub fn with_directive(self, dtype: DirectiveType, dspec: u16) -> Self {
+        Write {
+            dtype: Some(dtype),
+            dspec,
+            ..self
+        }
+    }
+
+    /// Place the data in the reclaim unit referenced by placement identifier
+    /// `pid`, as built by `FdpConfigDesc::pid`.
+    pub fn with_placement(self, pid: u16) -> Self {
+        self.with_directive(DirectiveType::DataPlacement, pid)
+    }
+
+    pub fn cmd(&self) -> Cmd {
+        let dtype = self.dtype.map_or(0, |dtype| dtype as u32);
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.slba as u32,
+            cdw11: (self.slba >> 32) as u32,
+            cdw12: self.nlb as u32 | (dtype & 0xf) << 20 | (self.fua as u32) << 30,
+            cdw13: (self.dspec as u32) << 16,
+            ..Cmd::new_io(IoOpcode::Write)
+        }
+    }
+
+    pub fn exec<T: Transport>(
+        &self,
+        transport: &mut T,
+        data: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(), data)
+    }
+}
+
+#[test]
+fn write_cmd() {
+    let cmd = Write::new(1, 0x1_0000_0010, 7)
+        .with_fua(true)
+        .with_placement(0x4003)
+        .cmd();
+    assert_eq!(cmd.opcode, 0x01);
+    assert_eq!(({ cmd.cdw10 }, { cmd.cdw11 }), (0x10, 1));
+    assert_eq!(({ cmd.cdw12 }, { cmd.cdw13 }), (0x4020_0007, 0x4003_0000));
+}
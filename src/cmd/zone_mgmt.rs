@@ -0,0 +1,504 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec};
+use core::mem::size_of;
+
+use num_traits::FromPrimitive;
+
+use crate::{
+    cmd::{exec_io, Cmd, CmdErr, Completion, Csi, GetLogPage, IoOpcode, Transport},
+    ChangedZoneList, FromBytes, LogPageId, Reserved, TransmuteSafe,
+};
+
+/// Zone Descriptor
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneDesc {
+    /// Zone Type
+    #[loc(0:0)]
+    pub zt: u8,
+    /// Zone State, in bits 7:4
+    #[loc(1:1)]
+    pub zs: u8,
+    /// Zone Attributes
+    #[loc(2:2)]
+    pub za: u8,
+    /// Zone Attributes Information
+    #[loc(3:3)]
+    pub zai: u8,
+    #[loc(4:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd4: Reserved<4>,
+    /// Zone Capacity in logical blocks
+    #[loc(8:15)]
+    pub zcap: u64,
+    /// Zone Start Logical Block Address
+    #[loc(16:23)]
+    pub zslba: u64,
+    /// Write Pointer
+    #[loc(24:31)]
+    pub wp: u64,
+    #[loc(32:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd32: Reserved<32>,
+}
+
+impl TransmuteSafe for ZoneDesc {}
+
+impl ZoneDesc {
+    /// Type of the zone, or `None` if reserved.
+    pub fn ztype(&self) -> Option<ZoneType> {
+        FromPrimitive::from_u8(self.zt & 0xf)
+    }
+
+    /// State of the zone, or `None` if reserved.
+    pub fn state(&self) -> Option<ZoneState> {
+        FromPrimitive::from_u8(self.zs >> 4)
+    }
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ZoneType {
+    SeqWriteRequired = 0x2,
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ZoneState {
+    Empty = 0x1,
+    ImplicitlyOpened = 0x2,
+    ExplicitlyOpened = 0x3,
+    Closed = 0x4,
+    ReadOnly = 0xd,
+    Full = 0xe,
+    Offline = 0xf,
+}
+
+/// Report Zones data structure header
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneReportHeader {
+    /// Number of Zones: those matching the request from the start LBA on,
+    /// or only those returned for a partial report
+    #[loc(0:7)]
+    pub nz: u64,
+    #[loc(8:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd8: Reserved<56>,
+}
+
+impl TransmuteSafe for ZoneReportHeader {}
+
+/// Report Zones data returned by Zone Management Receive. A report truncated
+/// by a short buffer holds the descriptors which fit.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ZoneReport<'a> {
+    pub header: &'a ZoneReportHeader,
+    pub descs: &'a [ZoneDesc],
+}
+
+impl<'a> ZoneReport<'a> {
+    /// Parse the header and as many descriptors as it counts and `bytes`
+    /// holds. On error, the length of the header is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<ZoneReportHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| ZoneReportHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let descs = &bytes[header_len..];
+        let nz =
+            (header.nz.min(usize::MAX as u64) as usize).min(descs.len() / size_of::<ZoneDesc>());
+        Ok(ZoneReport {
+            header,
+            descs: <[ZoneDesc]>::from_bytes(&descs[..nz * size_of::<ZoneDesc>()]).expect("align 1"),
+        })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.descs.len() as u64 == self.header.nz
+    }
+
+    /// Descriptor of the zone starting at `zslba`.
+    pub fn zone(&self, zslba: u64) -> Option<&'a ZoneDesc> {
+        self.descs.iter().find(|desc| desc.zslba == zslba)
+    }
+}
+
+/// Zone Receive Action Specific Field of a report, selecting zones by state
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+pub enum ZoneReportFilter {
+    All = 0x0,
+    Empty = 0x1,
+    ImplicitlyOpened = 0x2,
+    ExplicitlyOpened = 0x3,
+    Closed = 0x4,
+    Full = 0x5,
+    ReadOnly = 0x6,
+    Offline = 0x7,
+}
+
+/// Zone Management Receive command, reporting zones.
+#[derive(Clone, Copy, Debug)]
+pub struct ZoneMgmtRecv {
+    pub nsid: u32,
+    pub slba: u64,
+    pub zrasf: ZoneReportFilter,
+    /// Report only the zones returned rather than every matching zone.
+    pub partial: bool,
+}
+
+impl ZoneMgmtRecv {
+    /// Report every zone of `nsid` from the one containing `slba` on.
+    pub fn report(nsid: u32, slba: u64) -> Self {
+        ZoneMgmtRecv {
+            nsid,
+            slba,
+            zrasf: ZoneReportFilter::All,
+            partial: false,
+        }
+    }
+
+    pub fn with_filter(self, zrasf: ZoneReportFilter) -> Self {
+        ZoneMgmtRecv { zrasf, ..self }
+    }
+
+    pub fn with_partial(self, partial: bool) -> Self {
+        ZoneMgmtRecv { partial, ..self }
+    }
+
+    pub fn cmd(&self, len: usize) -> Cmd {
+        Cmd {
+            nsid: self.nsid,
+            cdw10: self.slba as u32,
+            cdw11: (self.slba >> 32) as u32,
+            cdw12: (len / 4).saturating_sub(1) as u32,
+            cdw13: (self.zrasf as u32) << 8 | (self.partial as u32) << 16,
+            ..Cmd::new_io(IoOpcode::ZoneMgmtRecv)
+        }
+    }
+
+    /// Receive into `buf`, whose length is a non-zero multiple of 4.
+    pub fn read<T: Transport>(
+        &self,
+        transport: &mut T,
+        buf: &mut [u8],
+    ) -> Result<Completion, CmdErr<T::Err>> {
+        exec_io(transport, &self.cmd(buf.len()), buf)
+    }
+}
+
+/// Read the Changed Zone List of zoned namespace `nsid`, which clears the
+/// Zone Descriptor Changed event.
+pub fn get_changed_zone_list<T: Transport>(
+    transport: &mut T,
+    nsid: u32,
+) -> Result<ChangedZoneList, CmdErr<T::Err>> {
+    GetLogPage::new(LogPageId::ChangedZoneList as u8)
+        .with_nsid(nsid)
+        .with_csi(Csi::Zoned as u8)
+        .read_log(transport)
+}
+
+/// Zone descriptors of a zoned namespace, kept coherent with the controller
+/// through the Changed Zone List.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct ZoneCache {
+    nsid: u32,
+    zsze: u64,
+    zones: BTreeMap<u64, ZoneDesc>,
+}
+
+#[cfg(feature = "alloc")]
+impl ZoneCache {
+    /// Empty cache of `nsid`, whose zones are `zsze` logical blocks apart.
+    pub fn new(nsid: u32, zsze: u64) -> Self {
+        ZoneCache {
+            nsid,
+            zsze,
+            zones: BTreeMap::new(),
+        }
+    }
+
+    pub fn nsid(&self) -> u32 {
+        self.nsid
+    }
+
+    pub fn zone(&self, zslba: u64) -> Option<&ZoneDesc> {
+        self.zones.get(&zslba)
+    }
+
+    /// Zones in ascending order of start LBA.
+    pub fn zones(&self) -> impl Iterator<Item = &ZoneDesc> {
+        self.zones.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.zones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// Replace the cache with a report of every zone, transferring at most
+    /// `chunk_len` bytes per command. A report which doesn't advance past
+    /// the requested LBA is an error.
+    pub fn report_all<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        chunk_len: usize,
+    ) -> Result<(), CmdErr<T::Err>> {
+        let header_len = size_of::<ZoneReportHeader>();
+        let per_chunk = (chunk_len.saturating_sub(header_len) / size_of::<ZoneDesc>()).max(1);
+        let mut buf = vec![0u8; header_len + per_chunk * size_of::<ZoneDesc>()];
+        let mut zones = BTreeMap::new();
+        let mut slba = 0;
+        loop {
+            ZoneMgmtRecv::report(self.nsid, slba)
+                .with_partial(true)
+                .read(transport, &mut buf)?;
+            let report = ZoneReport::parse(&buf).expect("complete header");
+            let last = match report.descs.last() {
+                Some(last) => last.zslba,
+                None => break,
+            };
+            if last < slba {
+                return Err(CmdErr::Invalid);
+            }
+            zones.extend(report.descs.iter().map(|desc| (desc.zslba, *desc)));
+            if report.descs.len() < per_chunk {
+                break;
+            }
+            slba = match last.checked_add(self.zsze.max(1)) {
+                Some(slba) => slba,
+                None => break,
+            };
+        }
+        self.zones = zones;
+        Ok(())
+    }
+
+    /// Bring the cache up to date with `changes`, reporting each listed
+    /// zone again, or every zone in 4 KiB transfers if the list overflowed.
+    /// Listed zones which are no longer reported are removed.
+    pub fn apply<T: Transport>(
+        &mut self,
+        transport: &mut T,
+        changes: &ChangedZoneList,
+    ) -> Result<(), CmdErr<T::Err>> {
+        if changes.overflowed() {
+            return self.report_all(transport, 4096);
+        }
+        let mut buf = [0u8; size_of::<ZoneReportHeader>() + size_of::<ZoneDesc>()];
+        for zid in changes.zids() {
+            ZoneMgmtRecv::report(self.nsid, zid)
+                .with_partial(true)
+                .read(transport, &mut buf)?;
+            let report = ZoneReport::parse(&buf).expect("complete header");
+            match report.zone(zid) {
+                Some(desc) => self.zones.insert(zid, *desc),
+                None => self.zones.remove(&zid),
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Handle a Zone Descriptor Changed notice: read the Changed Zone List of
+/// the cached namespace, which clears the event, then refresh the zones it
+/// lists in `cache`.
+#[cfg(feature = "alloc")]
+pub fn zone_changes<T: Transport>(
+    transport: &mut T,
+    cache: &mut ZoneCache,
+) -> Result<ChangedZoneList, CmdErr<T::Err>> {
+    let changes = get_changed_zone_list(transport, cache.nsid)?;
+    cache.apply(transport, &changes)?;
+    Ok(changes)
+}
+
+#[test]
+fn zone_mgmt_recv_cmd() {
+    let cmd = ZoneMgmtRecv::report(1, 0x1_0000_8000)
+        .with_filter(ZoneReportFilter::Full)
+        .with_partial(true)
+        .cmd(4096);
+    let (cdw10, cdw11, cdw12, cdw13) = (cmd.cdw10, cmd.cdw11, cmd.cdw12, cmd.cdw13);
+    assert_eq!(cmd.opcode, IoOpcode::ZoneMgmtRecv as u8);
+    assert_eq!(cdw10, 0x8000);
+    assert_eq!(cdw11, 0x1);
+    assert_eq!(cdw12, 1023);
+    assert_eq!(cdw13, 0x1_0500);
+}
+
+#[cfg(all(test, feature = "alloc"))]
+const MOCK_ZSZE: u64 = 0x1000;
+
+/// Zoned namespace 1 of eight 1000h block zones, posting its changes to the
+/// Changed Zone List.
+#[cfg(all(test, feature = "alloc"))]
+struct MockZones {
+    wps: alloc::vec::Vec<u64>,
+    changed: alloc::vec::Vec<u64>,
+    overflow: bool,
+    reports: usize,
+    /// Report from the first zone whatever the starting LBA.
+    ignore_slba: bool,
+}
+
+#[cfg(all(test, feature = "alloc"))]
+impl MockZones {
+    fn write(&mut self, zone: usize, nlb: u64) {
+        self.wps[zone] += nlb;
+        self.changed.push(zone as u64 * MOCK_ZSZE);
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+fn mock_ctrl() -> crate::cmd::mock::MockCtrl<MockZones> {
+    let zones = MockZones {
+        wps: (0..8).map(|zone| zone * MOCK_ZSZE).collect(),
+        changed: alloc::vec::Vec::new(),
+        overflow: false,
+        reports: 0,
+        ignore_slba: false,
+    };
+    crate::cmd::mock::MockCtrl::new(zones)
+        .on_log(LogPageId::ChangedZoneList, |zones, cmd, data| {
+            assert_eq!(({ cmd.nsid }, { cmd.cdw14 } >> 24), (1, 2));
+            data.fill(0);
+            let nzid = if zones.overflow {
+                0xffff
+            } else {
+                zones.changed.len() as u16
+            };
+            data[0..2].copy_from_slice(&nzid.to_le_bytes());
+            for (zid, chunk) in zones.changed.drain(..).zip(data[8..].chunks_mut(8)) {
+                chunk.copy_from_slice(&zid.to_le_bytes());
+            }
+            zones.overflow = false;
+            Ok(())
+        })
+        .on_io(IoOpcode::ZoneMgmtRecv, |zones, cmd, data| {
+            assert_eq!(({ cmd.nsid }, { cmd.cdw13 }), (1, 1 << 16));
+            assert_eq!((cmd.cdw12 as usize + 1) * 4, data.len());
+            zones.reports += 1;
+            let slba = cmd.cdw10 as u64 | (cmd.cdw11 as u64) << 32;
+            data.fill(0);
+            let first = if zones.ignore_slba {
+                0
+            } else {
+                (slba / MOCK_ZSZE) as usize
+            };
+            let mut nz = 0u64;
+            for (zone, desc) in (first..zones.wps.len()).zip(data[64..].chunks_exact_mut(64)) {
+                let zslba = zone as u64 * MOCK_ZSZE;
+                let full = zones.wps[zone] == zslba + MOCK_ZSZE;
+                desc[0] = 0x2;
+                desc[1] = if full { 0xe0 } else { 0x20 };
+                desc[8..16].copy_from_slice(&MOCK_ZSZE.to_le_bytes());
+                desc[16..24].copy_from_slice(&zslba.to_le_bytes());
+                desc[24..32].copy_from_slice(&zones.wps[zone].to_le_bytes());
+                nz += 1;
+            }
+            data[0..8].copy_from_slice(&nz.to_le_bytes());
+            Ok(())
+        })
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn zone_cache_refresh() {
+    let mut ctrl = mock_ctrl();
+    let mut cache = ZoneCache::new(1, MOCK_ZSZE);
+    cache.report_all(&mut ctrl, 64 + 3 * 64).unwrap();
+    assert_eq!(ctrl.state.reports, 3);
+    assert_eq!(cache.len(), 8);
+    assert!(cache
+        .zones()
+        .all(|zone| zone.ztype() == Some(ZoneType::SeqWriteRequired)));
+
+    let zones = &mut ctrl.state;
+    zones.write(2, 0x10);
+    zones.write(5, MOCK_ZSZE);
+    zones.changed.push(0x8000);
+    zones.reports = 0;
+    let changes = zone_changes(&mut ctrl, &mut cache).unwrap();
+    assert_eq!(
+        changes.zids().collect::<alloc::vec::Vec<_>>(),
+        [0x2000, 0x5000, 0x8000]
+    );
+    assert_eq!(ctrl.state.reports, 3);
+    assert_eq!({ cache.zone(0x2000).unwrap().wp }, 0x2010);
+    assert_eq!(cache.zone(0x5000).unwrap().state(), Some(ZoneState::Full));
+    assert!(cache.zone(0x8000).is_none());
+    assert_eq!(cache.len(), 8);
+
+    ctrl.state.wps[7] += 1;
+    ctrl.state.overflow = true;
+    let changes = zone_changes(&mut ctrl, &mut cache).unwrap();
+    assert!(changes.overflowed());
+    assert_eq!(changes.zids().count(), 0);
+    assert_eq!({ cache.zone(0x7000).unwrap().wp }, 0x7001);
+    assert_eq!(cache.len(), 8);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn zone_cache_ignored_slba() {
+    let mut ctrl = mock_ctrl();
+    ctrl.state.ignore_slba = true;
+    let mut cache = ZoneCache::new(1, MOCK_ZSZE);
+    assert!(matches!(
+        cache.report_all(&mut ctrl, 64 + 3 * 64),
+        Err(CmdErr::Invalid)
+    ));
+    assert_eq!(ctrl.state.reports, 2);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn zone_report_round_trip() {
+    use crate::util::{pattern, round_trip};
+
+    let mut bytes = pattern(64, 24);
+    bytes[4..8].fill(0);
+    bytes[32..64].fill(0);
+    round_trip::<ZoneDesc>(&bytes);
+    let mut bytes = pattern(64, 24);
+    bytes[8..64].fill(0);
+    round_trip::<ZoneReportHeader>(&bytes);
+}
@@ -17,12 +17,15 @@
  * <http://www.gnu.org/licenses/>.
  */
 
-use crate::{util::BitArray, FixedStr, Reserved, TransmuteSafe};
+use core::mem::size_of;
+
+use crate::{util::BitArray, AnaGrpDesc, AnaLogHeader, FixedStr, NulStr, Reserved, TransmuteSafe};
 
 use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
 
 #[test_structure(size = 4096)]
-#[repr(C)]
+#[repr(C, packed)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IdCtrl {
     #[loc(0:1)]
@@ -61,7 +64,7 @@ pub struct IdCtrl {
     #[cfg_attr(feature = "serde", serde(skip))]
     __rsvd102: Reserved<9>,
     #[loc(111:111)]
-    pub cntrltype: CtrlType,
+    pub cntrltype: u8,
     // TODO: Assess whether this has any value in destructured form. It appears
     // to simply be a unique identifier for the type of device.
     /// FRU Globally Unique Identifier (Big Endian)
@@ -190,9 +193,8 @@ pub struct IdCtrl {
     #[loc(544:767)]
     #[cfg_attr(feature = "serde", serde(skip))]
     __rsvd544: Reserved<224>,
-    // TODO: Properly handle null termination.
     #[loc(768:1023)]
-    pub subnqn: FixedStr<256>,
+    pub subnqn: NulStr<256>,
     #[loc(1024:1791)]
     #[cfg_attr(feature = "serde", serde(skip))]
     __rsvd1024: Reserved<768>,
@@ -203,13 +205,36 @@ pub struct IdCtrl {
 
     #[loc(2048:3071)]
     pub psds: [PowerState; 32],
+    /// Vendor Specific
     #[loc(3072:4095)]
-    #[cfg_attr(feature = "serde", serde(skip))]
-    __rsvd3072: Reserved<1024>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub vs: [u8; 1024],
 }
 
 impl TransmuteSafe for IdCtrl {}
 
+impl IdCtrl {
+    pub fn cntrltype(&self) -> Option<CtrlType> {
+        FromPrimitive::from_u8(self.cntrltype)
+    }
+
+    /// Length of the largest ANA log the controller can report. With
+    /// `groups_only` (the RGO log specific parameter), group descriptors
+    /// omit their namespace identifiers.
+    pub fn ana_log_len(&self, groups_only: bool) -> usize {
+        let nsids = if groups_only {
+            0
+        } else if self.mnan != 0 {
+            self.mnan
+        } else {
+            self.nn
+        };
+        size_of::<AnaLogHeader>()
+            + self.nanagrpid as usize * size_of::<AnaGrpDesc>()
+            + nsids as usize * size_of::<u32>()
+    }
+}
+
 #[derive(Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
@@ -285,9 +310,9 @@ pub struct CtrlAttr {
     __rsvd: B22,
 }
 
-#[non_exhaustive]
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum CtrlType {
@@ -295,9 +320,6 @@ pub enum CtrlType {
     IoCtrl = 0x01,
     DiscoveryCtrl = 0x02,
     AdminCtrl = 0x03,
-    // TODO: Make this fill the repr space so there is no undefined behavior
-    // when transmuting. Through testing, this would result in NotReported being
-    // the enum value, if the actual value lies within the reserved space.
 }
 
 #[bitfield]
@@ -455,11 +477,18 @@ pub struct Sanicap {
     pub nodmmas: Nodmmas,
 }
 
-#[derive(BitfieldSpecifier)]
+#[derive(BitfieldSpecifier, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[bits = 2]
+#[allow(clippy::manual_non_exhaustive)]
 pub enum Nodmmas {
     Undefined = 0,
     NotModified = 1,
     Modified = 2,
+
+    // The enum must exhaust the bits or else it can't reliably be transmuted.
+    #[doc(hidden)]
     _Reserved = 3,
 }
 
@@ -576,11 +605,18 @@ pub struct Sgls {
     __rsvd: B10,
 }
 
-#[derive(BitfieldSpecifier)]
+#[derive(BitfieldSpecifier, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[bits = 2]
+#[allow(clippy::manual_non_exhaustive)]
 pub enum SglsType {
     NotSupported = 0,
     Supported = 1,
     SupportedAligned = 2,
+
+    // The enum must exhaust the bits or else it can't reliably be transmuted.
+    #[doc(hidden)]
     _Reserved = 3,
 }
 
@@ -641,6 +677,7 @@ struct PowerStateUnpacked {
     pub rrt: u8,
     pub rrl: u8,
     pub rwt: u8,
+    pub rwl: u8,
     pub idlp: u16,
     pub ips: u8,
     pub actp: u16,
@@ -660,6 +697,7 @@ impl From<PowerStateUnpacked> for PowerState {
             .with_rrt(unpacked.rrt)
             .with_rrl(unpacked.rrl)
             .with_rwt(unpacked.rwt)
+            .with_rwl(unpacked.rwl)
             .with_idlp(unpacked.idlp)
             .with_ips(unpacked.ips)
             .with_actp(unpacked.actp)
@@ -680,6 +718,7 @@ impl From<PowerState> for PowerStateUnpacked {
             rrt: packed.rrt(),
             rrl: packed.rrl(),
             rwt: packed.rwt(),
+            rwl: packed.rwl(),
             idlp: packed.idlp(),
             ips: packed.ips(),
             actp: packed.actp(),
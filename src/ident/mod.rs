@@ -18,6 +18,10 @@
  */
 
 mod ctrl;
-pub use ctrl::IdCtrl;
+pub use ctrl::*;
 mod nmsp;
-pub use nmsp::IdNmsp;
+pub use nmsp::*;
+mod nmsp_indep;
+pub use nmsp_indep::*;
+mod nmsp_list;
+pub use nmsp_list::*;
@@ -17,14 +17,277 @@
  * <http://www.gnu.org/licenses/>.
  */
 
-use crate::TransmuteSafe;
+use crate::{Reserved, TransmuteSafe};
 
-pub struct IdNmsp([u8; 4096]);
+use modular_bitfield::prelude::*;
+
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdNmsp {
+    #[loc(0:7)]
+    pub nsze: u64,
+    #[loc(8:15)]
+    pub ncap: u64,
+    #[loc(16:23)]
+    pub nuse: u64,
+    #[loc(24:24)]
+    pub nsfeat: Nsfeat,
+    #[loc(25:25)]
+    pub nlbaf: u8,
+    #[loc(26:26)]
+    pub flbas: Flbas,
+    #[loc(27:27)]
+    pub mc: Mc,
+    #[loc(28:28)]
+    pub dpc: Dpc,
+    #[loc(29:29)]
+    pub dps: Dps,
+    #[loc(30:30)]
+    pub nmic: Nmic,
+    #[loc(31:31)]
+    pub rescap: Rescap,
+    #[loc(32:32)]
+    pub fpi: Fpi,
+    #[loc(33:33)]
+    pub dlfeat: Dlfeat,
+    #[loc(34:35)]
+    pub nawun: u16,
+    #[loc(36:37)]
+    pub nawupf: u16,
+    #[loc(38:39)]
+    pub nacwu: u16,
+    #[loc(40:41)]
+    pub nabsn: u16,
+    #[loc(42:43)]
+    pub nabo: u16,
+    #[loc(44:45)]
+    pub nabspf: u16,
+    #[loc(46:47)]
+    pub noiob: u16,
+    #[loc(48:63)]
+    pub nvmcap: u128,
+    #[loc(64:65)]
+    pub npwg: u16,
+    #[loc(66:67)]
+    pub npwa: u16,
+    #[loc(68:69)]
+    pub npdg: u16,
+    #[loc(70:71)]
+    pub npda: u16,
+    #[loc(72:73)]
+    pub nows: u16,
+    #[loc(74:91)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd74: Reserved<18>,
+    #[loc(92:95)]
+    pub anagrpid: u32,
+    #[loc(96:98)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd96: Reserved<3>,
+    #[loc(99:99)]
+    pub nsattr: Nsattr,
+    #[loc(100:101)]
+    pub nvmsetid: u16,
+    #[loc(102:103)]
+    pub endgid: u16,
+    /// Namespace Globally Unique Identifier (Big Endian)
+    #[loc(104:119)]
+    pub nguid: u128,
+    /// IEEE Extended Unique Identifier (Big Endian)
+    #[loc(120:127)]
+    pub eui64: u64,
+    #[loc(128:191)]
+    pub lbafs: [LbaFormat; 16],
+    #[loc(192:383)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd192: Reserved<192>,
+    #[loc(384:3071)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd384: Reserved<2688>,
+    /// Vendor Specific
+    #[loc(3072:4095)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub vs: [u8; 1024],
+}
 
 impl TransmuteSafe for IdNmsp {}
 
-#[test]
-fn structure() {
-    use std::mem;
-    assert_eq!(mem::size_of::<IdNmsp>(), 4096);
+impl IdNmsp {
+    /// The LBA format the namespace is currently formatted with.
+    pub fn current_lbaf(&self) -> LbaFormat {
+        self.lbafs[self.flbas.lbaf() as usize]
+    }
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Nsfeat {
+    pub thin_provisioning: bool,
+    pub nsabp: bool,
+    pub dae: bool,
+    pub uidreuse: bool,
+    pub optperf: bool,
+    #[skip]
+    __rsvd: B3,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Flbas {
+    pub lbaf: B4,
+    pub extended: bool,
+    #[skip]
+    __rsvd: B3,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Mc {
+    pub extended: bool,
+    pub separate: bool,
+    #[skip]
+    __rsvd: B6,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Dpc {
+    pub type1: bool,
+    pub type2: bool,
+    pub type3: bool,
+    pub first: bool,
+    pub last: bool,
+    #[skip]
+    __rsvd: B3,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Dps {
+    pub pit: B3,
+    pub first: bool,
+    #[skip]
+    __rsvd: B4,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Nmic {
+    pub shared: bool,
+    #[skip]
+    __rsvd: B7,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Rescap {
+    pub ptpl: bool,
+    pub write_exclusive: bool,
+    pub exclusive_access: bool,
+    pub write_exclusive_reg_only: bool,
+    pub exclusive_access_reg_only: bool,
+    pub write_exclusive_all_reg: bool,
+    pub exclusive_access_all_reg: bool,
+    pub ignore_existing_key: bool,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Fpi {
+    pub percent_remaining: B7,
+    pub supported: bool,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Dlfeat {
+    pub read_behavior: B3,
+    pub write_zeroes: bool,
+    pub guard_crc: bool,
+    #[skip]
+    __rsvd: B3,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Nsattr {
+    pub write_protected: bool,
+    #[skip]
+    __rsvd: B7,
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "LbaFormatUnpacked", from = "LbaFormatUnpacked")
+)]
+pub struct LbaFormat {
+    pub ms: u16,
+    pub lbads: u8,
+    pub rp: B2,
+    #[skip]
+    __rsvd: B6,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct LbaFormatUnpacked {
+    pub ms: u16,
+    pub lbads: u8,
+    pub rp: u8,
+}
+
+#[cfg(feature = "serde")]
+impl From<LbaFormatUnpacked> for LbaFormat {
+    fn from(unpacked: LbaFormatUnpacked) -> Self {
+        LbaFormat::new()
+            .with_ms(unpacked.ms)
+            .with_lbads(unpacked.lbads)
+            .with_rp(unpacked.rp)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<LbaFormat> for LbaFormatUnpacked {
+    fn from(packed: LbaFormat) -> Self {
+        LbaFormatUnpacked {
+            ms: packed.ms(),
+            lbads: packed.lbads(),
+            rp: packed.rp(),
+        }
+    }
 }
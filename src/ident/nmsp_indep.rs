@@ -0,0 +1,81 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{Fpi, Nmic, Nsattr, Rescap, Reserved, TransmuteSafe};
+
+use modular_bitfield::prelude::*;
+
+/// I/O Command Set Independent Identify Namespace data structure
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdNmspIndep {
+    #[loc(0:0)]
+    pub nsfeat: NsfeatIndep,
+    #[loc(1:1)]
+    pub nmic: Nmic,
+    #[loc(2:2)]
+    pub rescap: Rescap,
+    #[loc(3:3)]
+    pub fpi: Fpi,
+    #[loc(4:7)]
+    pub anagrpid: u32,
+    #[loc(8:8)]
+    pub nsattr: Nsattr,
+    #[loc(9:9)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd9: Reserved<1>,
+    #[loc(10:11)]
+    pub nvmsetid: u16,
+    #[loc(12:13)]
+    pub endgid: u16,
+    #[loc(14:14)]
+    pub nstat: Nstat,
+    #[loc(15:4095)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd15: Reserved<4081>,
+}
+
+impl TransmuteSafe for IdNmspIndep {}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct NsfeatIndep {
+    #[skip]
+    __rsvd: B3,
+    pub uidreuse: bool,
+    /// The namespace is stored on rotational media.
+    pub rotational: bool,
+    #[skip]
+    __rsvd: B3,
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Nstat {
+    pub nrdy: bool,
+    #[skip]
+    __rsvd: B7,
+}
@@ -0,0 +1,59 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::TransmuteSafe;
+
+/// Namespace List
+///
+/// Returned by Identify for the active and allocated namespace ID lists, and
+/// as the Changed Namespace List log page.
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NmspList {
+    #[loc(0:4095)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub nsids: [u32; 1024],
+}
+
+impl TransmuteSafe for NmspList {}
+
+impl NmspList {
+    /// Maximum number of identifiers the list can hold.
+    pub const CAPACITY: usize = 1024;
+
+    /// Identifiers in the list, which ends at the first unused entry.
+    pub fn nsids(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..Self::CAPACITY)
+            .map(move |idx| self.nsids[idx])
+            .take_while(|&nsid| nsid != 0)
+    }
+
+    /// Whether more namespaces changed than the Changed Namespace List can
+    /// hold, in which case the list holds only FFFFFFFFh and every namespace
+    /// must be rescanned.
+    pub fn overflowed(&self) -> bool {
+        self.nsids[0] == 0xffff_ffff
+    }
+
+    /// Whether the list is full, so more identifiers may follow the last.
+    pub fn is_full(&self) -> bool {
+        self.nsids[Self::CAPACITY - 1] != 0
+    }
+}
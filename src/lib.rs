@@ -17,17 +17,42 @@
  * <http://www.gnu.org/licenses/>.
  */
 
-#![allow(unused_braces)]
+//! Structures defined by the NVMe specification.
+//!
+//! The structures themselves only depend on `core`. Helpers which allocate,
+//! such as [`FixedStr::to_string_lossy`], require the `alloc` feature and
+//! serde support requires `serde`, which implies `alloc`. The default
+//! features enable `std` and `serde`.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+// `modular_bitfield` and `num_derive` generate code which raises these lints.
+// An allow on the deriving item doesn't reach the generated impls, so they
+// are allowed crate-wide.
+#![allow(
+    unused_braces,
+    unused_parens,
+    non_local_definitions,
+    clippy::identity_op,
+    clippy::new_without_default
+)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[macro_use]
 extern crate structural_assert;
 
+mod cmd;
+#[doc(inline)]
+pub use cmd::*;
 mod ident;
 #[doc(inline)]
 pub use ident::*;
 mod logpage;
 #[doc(inline)]
 pub use logpage::*;
+mod reg;
+#[doc(inline)]
+pub use reg::*;
 mod status;
 #[doc(inline)]
 pub use status::{
@@ -36,4 +61,4 @@ pub use status::{
 };
 mod util;
 #[doc(inline)]
-pub use util::{FixedStr, FromBytes, Reserved, TransmuteSafe};
+pub use util::{AsBytes, FixedStr, FromBytes, NulStr, Reserved, TransmuteSafe};
@@ -0,0 +1,351 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{convert::TryInto, mem::size_of};
+
+use num_traits::FromPrimitive;
+
+use crate::{FromBytes, Reserved, TransmuteSafe};
+
+/// Asymmetric Namespace Access (Log Identifier 0Ch) header
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnaLogHeader {
+    #[loc(0:7)]
+    pub chgcnt: u64,
+    #[loc(8:9)]
+    pub ngrps: u16,
+    #[loc(10:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd10: Reserved<6>,
+}
+
+impl TransmuteSafe for AnaLogHeader {}
+
+/// ANA Group Descriptor, which is followed by `nnsids` namespace identifiers
+#[test_structure(size = 32)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnaGrpDesc {
+    #[loc(0:3)]
+    pub grpid: u32,
+    #[loc(4:7)]
+    pub nnsids: u32,
+    #[loc(8:15)]
+    pub chgcnt: u64,
+    #[loc(16:16)]
+    pub state: u8,
+    #[loc(17:31)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd17: Reserved<15>,
+}
+
+impl TransmuteSafe for AnaGrpDesc {}
+
+impl AnaGrpDesc {
+    /// ANA state of the group, or `None` if reserved.
+    pub fn state(&self) -> Option<AnaState> {
+        FromPrimitive::from_u8(self.state & 0xf)
+    }
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AnaState {
+    Optimized = 0x1,
+    NonOptimized = 0x2,
+    Inaccessible = 0x3,
+    PersistentLoss = 0x4,
+    Change = 0xf,
+}
+
+/// Asymmetric Namespace Access log, whose group descriptors vary in length.
+#[derive(Clone, Copy)]
+pub struct AnaLog<'a> {
+    header: &'a AnaLogHeader,
+    descs: &'a [u8],
+}
+
+impl<'a> AnaLog<'a> {
+    /// Parse the header and validate that every group descriptor it counts is
+    /// present. On error, the length needed to hold the entire log, as far as
+    /// can be determined, is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<AnaLogHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| AnaLogHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let descs = &bytes[header_len..];
+        let mut len = 0usize;
+        for _ in 0..header.ngrps {
+            let desc = descs
+                .get(len..len + size_of::<AnaGrpDesc>())
+                .and_then(|desc| AnaGrpDesc::from_bytes(desc).ok())
+                .ok_or(header_len + len + size_of::<AnaGrpDesc>())?;
+            len = (desc.nnsids as usize)
+                .checked_mul(size_of::<u32>())
+                .and_then(|nsids_len| nsids_len.checked_add(size_of::<AnaGrpDesc>()))
+                .and_then(|desc_len| desc_len.checked_add(len))
+                .ok_or(usize::MAX)?;
+            if len > descs.len() {
+                return Err(len.saturating_add(header_len));
+            }
+        }
+        Ok(AnaLog {
+            header,
+            descs: &descs[..len],
+        })
+    }
+
+    pub fn header(&self) -> &'a AnaLogHeader {
+        self.header
+    }
+
+    pub fn groups(&self) -> AnaGroups<'a> {
+        AnaGroups { descs: self.descs }
+    }
+
+    /// Length of the log, from the start of the header through the last
+    /// group descriptor.
+    pub fn len(&self) -> usize {
+        size_of::<AnaLogHeader>() + self.descs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.descs.is_empty()
+    }
+}
+
+/// ANA group descriptor along with its namespace identifiers.
+#[derive(Clone, Copy)]
+pub struct AnaGroup<'a> {
+    pub desc: &'a AnaGrpDesc,
+    nsids: &'a [u8],
+}
+
+impl<'a> AnaGroup<'a> {
+    pub fn nsids(&self) -> impl Iterator<Item = u32> + 'a {
+        self.nsids
+            .chunks_exact(size_of::<u32>())
+            .map(|nsid| u32::from_le_bytes(nsid.try_into().unwrap()))
+    }
+}
+
+pub struct AnaGroups<'a> {
+    descs: &'a [u8],
+}
+
+impl<'a> Iterator for AnaGroups<'a> {
+    type Item = AnaGroup<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (desc, rest) = self
+            .descs
+            .split_at(size_of::<AnaGrpDesc>().min(self.descs.len()));
+        let desc = AnaGrpDesc::from_bytes(desc).ok()?;
+        // Lengths were validated when the log was parsed.
+        let nsids_len = (desc.nnsids as usize).checked_mul(size_of::<u32>())?;
+        let (nsids, rest) = rest.split_at(nsids_len.min(rest.len()));
+        self.descs = rest;
+        Some(AnaGroup { desc, nsids })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnaLog<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let (chgcnt, ngrps) = (self.header.chgcnt, self.header.ngrps);
+        let mut log = serializer.serialize_struct("AnaLog", 3)?;
+        log.serialize_field("chgcnt", &chgcnt)?;
+        log.serialize_field("ngrps", &ngrps)?;
+        log.serialize_field("groups", &crate::util::SerializeIter(|| self.groups()))?;
+        log.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnaGroup<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let desc = self.desc;
+        let (grpid, nnsids, chgcnt, state) = (desc.grpid, desc.nnsids, desc.chgcnt, desc.state);
+        let mut group = serializer.serialize_struct("AnaGroup", 5)?;
+        group.serialize_field("grpid", &grpid)?;
+        group.serialize_field("nnsids", &nnsids)?;
+        group.serialize_field("chgcnt", &chgcnt)?;
+        group.serialize_field("state", &state)?;
+        group.serialize_field("nsids", &crate::util::SerializeIter(|| self.nsids()))?;
+        group.end()
+    }
+}
+
+/// ANA group as last reported to an [`AnaTracker`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnaGrpState {
+    pub state: Option<AnaState>,
+    pub chgcnt: u64,
+    pub nsids: Vec<u32>,
+}
+
+/// Change in a group's ANA state between successive reads. A state of `None`
+/// means the group was not reported, or reported a reserved state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnaTransition {
+    pub grpid: u32,
+    pub from: Option<AnaState>,
+    pub to: Option<AnaState>,
+}
+
+/// ANA state of each group reported by a controller, updated from successive
+/// reads of the ANA log.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default)]
+pub struct AnaTracker {
+    chgcnt: u64,
+    groups: BTreeMap<u32, AnaGrpState>,
+}
+
+#[cfg(feature = "alloc")]
+impl AnaTracker {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Change count of the last log applied.
+    pub fn chgcnt(&self) -> u64 {
+        self.chgcnt
+    }
+
+    pub fn group(&self, grpid: u32) -> Option<&AnaGrpState> {
+        self.groups.get(&grpid)
+    }
+
+    /// Groups in ascending order of group identifier.
+    pub fn groups(&self) -> impl Iterator<Item = (u32, &AnaGrpState)> {
+        self.groups.iter().map(|(&grpid, group)| (grpid, group))
+    }
+
+    pub fn state(&self, grpid: u32) -> Option<AnaState> {
+        self.group(grpid)?.state
+    }
+
+    /// ANA state of the group containing `nsid`.
+    pub fn nsid_state(&self, nsid: u32) -> Option<AnaState> {
+        self.groups
+            .values()
+            .find(|group| group.nsids.contains(&nsid))?
+            .state
+    }
+
+    /// Apply a newly read log, returning the groups whose state changed in
+    /// ascending order of group identifier. Groups missing from the log are
+    /// removed. A `groups_only` log, read with RGO set, leaves each group's
+    /// namespaces as they were.
+    pub fn update(&mut self, log: &AnaLog<'_>, groups_only: bool) -> Vec<AnaTransition> {
+        let mut transitions = Vec::new();
+        let mut groups = BTreeMap::new();
+        for group in log.groups() {
+            let (grpid, chgcnt, state) = (group.desc.grpid, group.desc.chgcnt, group.desc.state());
+            let prev = self.groups.remove(&grpid);
+            let from = prev.as_ref().and_then(|prev| prev.state);
+            if prev.is_none() || from != state {
+                transitions.push(AnaTransition {
+                    grpid,
+                    from,
+                    to: state,
+                });
+            }
+            let nsids = match prev {
+                Some(prev) if groups_only => prev.nsids,
+                _ => group.nsids().collect(),
+            };
+            groups.insert(
+                grpid,
+                AnaGrpState {
+                    state,
+                    chgcnt,
+                    nsids,
+                },
+            );
+        }
+        for (grpid, removed) in core::mem::replace(&mut self.groups, groups) {
+            transitions.push(AnaTransition {
+                grpid,
+                from: removed.state,
+                to: None,
+            });
+        }
+        transitions.sort_by_key(|transition| transition.grpid);
+        self.chgcnt = log.header.chgcnt;
+        transitions
+    }
+}
+
+#[test]
+fn ana_log_parse() {
+    let mut bytes = [0u8; 16 + 32 + 8 + 32];
+    bytes[0] = 7;
+    bytes[8] = 2;
+    bytes[16] = 1;
+    bytes[20] = 2;
+    bytes[32] = 0x1;
+    bytes[48..52].copy_from_slice(&1u32.to_le_bytes());
+    bytes[52..56].copy_from_slice(&3u32.to_le_bytes());
+    bytes[56] = 2;
+    bytes[72] = 0x3;
+    let log = AnaLog::parse(&bytes[..]).unwrap();
+    assert_eq!(log.len(), bytes.len());
+    let groups: Vec<_> = log
+        .groups()
+        .map(|group| {
+            (
+                group.desc.grpid,
+                group.desc.state(),
+                group.nsids().collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        groups,
+        [
+            (1, Some(AnaState::Optimized), vec![1, 3]),
+            (2, Some(AnaState::Inaccessible), vec![]),
+        ]
+    );
+
+    assert_eq!(AnaLog::parse(&bytes[..8]).err(), Some(16));
+    assert_eq!(AnaLog::parse(&bytes[..60]).err(), Some(88));
+    bytes[20] = 3;
+    assert_eq!(AnaLog::parse(&bytes[..]).err(), Some(92));
+    bytes[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(AnaLog::parse(&bytes[..]).err().unwrap() > bytes.len());
+}
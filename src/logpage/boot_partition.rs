@@ -0,0 +1,48 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{Bpinfo, Reserved, TransmuteSafe};
+
+/// Boot Partition (Log Identifier 15h) header, which is followed by the
+/// contents of the boot partition selected when the log is read.
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BootPartitionLog {
+    #[loc(0:0)]
+    pub lid: u8,
+    #[loc(1:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<3>,
+    #[loc(4:7)]
+    pub bpinfo: Bpinfo,
+    #[loc(8:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd8: Reserved<8>,
+}
+
+impl TransmuteSafe for BootPartitionLog {}
+
+impl BootPartitionLog {
+    /// Length of the log, from the start of the header through the end of
+    /// the boot partition data.
+    pub fn full_len(&self) -> usize {
+        core::mem::size_of::<Self>() + self.bpinfo.bp_size()
+    }
+}
@@ -0,0 +1,606 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::{convert::TryInto, mem::size_of};
+
+use crate::{FromBytes, Reserved, TransmuteSafe};
+
+/// Media Unit Status (Log Identifier 10h) header
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaUnitStatusHeader {
+    /// Number of Media Unit Status Descriptors
+    #[loc(0:1)]
+    pub nmu: u16,
+    /// Number of Channels
+    #[loc(2:3)]
+    pub cchans: u16,
+    /// Selected Configuration
+    #[loc(4:5)]
+    pub sel_config: u16,
+    #[loc(6:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd6: Reserved<10>,
+}
+
+impl TransmuteSafe for MediaUnitStatusHeader {}
+
+/// Media Unit Status Descriptor, which is followed by the identifiers of the
+/// channels attached to the media unit
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaUnitStatusDesc {
+    #[loc(0:1)]
+    pub muid: u16,
+    #[loc(2:3)]
+    pub domainid: u16,
+    #[loc(4:5)]
+    pub endgid: u16,
+    #[loc(6:7)]
+    pub nvmsetid: u16,
+    /// Capacity Adjustment Factor
+    #[loc(8:9)]
+    pub caf: u16,
+    /// Available Spare
+    #[loc(10:10)]
+    pub avsp: u8,
+    /// Percentage Used
+    #[loc(11:11)]
+    pub pused: u8,
+    /// Number of channels attached to the media unit
+    #[loc(12:12)]
+    pub mucs: u8,
+    /// Channel Identifiers Offset, from the start of the descriptor
+    #[loc(13:13)]
+    pub cio: u8,
+    #[loc(14:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd14: Reserved<2>,
+}
+
+impl TransmuteSafe for MediaUnitStatusDesc {}
+
+impl MediaUnitStatusDesc {
+    /// Offset of the channel identifiers, which never overlap the descriptor
+    /// itself.
+    fn chanids_offset(&self) -> usize {
+        (self.cio as usize).max(size_of::<Self>())
+    }
+
+    /// Length of the descriptor through its last channel identifier.
+    pub fn desc_len(&self) -> usize {
+        self.chanids_offset() + self.mucs as usize * size_of::<u16>()
+    }
+}
+
+/// Media Unit Status log, whose descriptors vary in length.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MediaUnitStatusLog<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    header: &'a MediaUnitStatusHeader,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "units", serialize_with = "serialize_units")
+    )]
+    descs: &'a [u8],
+}
+
+impl<'a> MediaUnitStatusLog<'a> {
+    /// Parse the header and validate that every descriptor it counts is
+    /// present. On error, the length needed to hold the entire log, as far as
+    /// can be determined, is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header = prefix::<MediaUnitStatusHeader>(bytes)?;
+        let header_len = size_of::<MediaUnitStatusHeader>();
+        let len = list_len(&bytes[header_len..], header.nmu, media_unit_len)
+            .map_err(|need| header_len + need)?;
+        Ok(MediaUnitStatusLog {
+            header,
+            descs: &bytes[header_len..header_len + len],
+        })
+    }
+
+    pub fn header(&self) -> &'a MediaUnitStatusHeader {
+        self.header
+    }
+
+    pub fn units(&self) -> impl Iterator<Item = MediaUnit<'a>> + 'a {
+        units(self.descs)
+    }
+
+    pub fn unit(&self, muid: u16) -> Option<MediaUnit<'a>> {
+        self.units().find(|unit| unit.desc.muid == muid)
+    }
+}
+
+/// Media unit status along with the channels attached to the media unit.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MediaUnit<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub desc: &'a MediaUnitStatusDesc,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_ids"))]
+    chanids: &'a [u8],
+}
+
+impl<'a> MediaUnit<'a> {
+    pub fn chanids(&self) -> impl Iterator<Item = u16> + 'a {
+        ids(self.chanids)
+    }
+}
+
+fn media_unit_len(bytes: &[u8]) -> Result<usize, usize> {
+    let len = prefix::<MediaUnitStatusDesc>(bytes)?.desc_len();
+    if len > bytes.len() {
+        Err(len)
+    } else {
+        Ok(len)
+    }
+}
+
+fn units(descs: &[u8]) -> impl Iterator<Item = MediaUnit<'_>> {
+    // Lengths were validated when the log was parsed.
+    split(descs, media_unit_len).map(|unit| {
+        let desc = prefix::<MediaUnitStatusDesc>(unit).expect("validated");
+        MediaUnit {
+            desc,
+            chanids: &unit[desc.chanids_offset()..],
+        }
+    })
+}
+
+/// Supported Capacity Configuration List (Log Identifier 11h) header
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapConfigListHeader {
+    /// Number of Supported Capacity Configurations
+    #[loc(0:0)]
+    pub sccn: u8,
+    #[loc(1:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<15>,
+}
+
+impl TransmuteSafe for CapConfigListHeader {}
+
+/// Capacity Configuration Descriptor, which is followed by `egcn` endurance
+/// group configuration descriptors
+#[test_structure(size = 32)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CapConfigDesc {
+    /// Capacity Configuration Identifier
+    #[loc(0:1)]
+    pub capid: u16,
+    #[loc(2:3)]
+    pub domainid: u16,
+    /// Number of Endurance Group Configuration Descriptors
+    #[loc(4:5)]
+    pub egcn: u16,
+    #[loc(6:31)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd6: Reserved<26>,
+}
+
+impl TransmuteSafe for CapConfigDesc {}
+
+/// Endurance Group Configuration Descriptor, which is followed by `egsets`
+/// NVM set identifiers and the channel configuration of the endurance group
+#[test_structure(size = 82)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EndurGrpConfigDesc {
+    #[loc(0:1)]
+    pub endgid: u16,
+    /// Capacity Adjustment Factor
+    #[loc(2:3)]
+    pub caf: u16,
+    #[loc(4:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd4: Reserved<12>,
+    /// Total Endurance Group Capacity in bytes
+    #[loc(16:31)]
+    pub tegcap: u128,
+    /// Spare Endurance Group Capacity in bytes
+    #[loc(32:47)]
+    pub segcap: u128,
+    /// Endurance Estimate in gigabytes which may be written
+    #[loc(48:63)]
+    pub egest: u128,
+    #[loc(64:79)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd64: Reserved<16>,
+    /// Number of NVM Sets
+    #[loc(80:81)]
+    pub egsets: u16,
+}
+
+impl TransmuteSafe for EndurGrpConfigDesc {}
+
+/// Channel Configuration Descriptor, which is followed by `chmus` media unit
+/// configuration descriptors
+#[test_structure(size = 4)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChanConfigDesc {
+    #[loc(0:1)]
+    pub chanid: u16,
+    /// Number of Channel Media Units
+    #[loc(2:3)]
+    pub chmus: u16,
+}
+
+impl TransmuteSafe for ChanConfigDesc {}
+
+/// Media Unit Configuration Descriptor
+#[test_structure(size = 8)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaUnitConfigDesc {
+    #[loc(0:1)]
+    pub muid: u16,
+    #[loc(2:5)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2: Reserved<4>,
+    /// Media Unit Descriptor Length
+    #[loc(6:7)]
+    pub mudl: u16,
+}
+
+impl TransmuteSafe for MediaUnitConfigDesc {}
+
+/// Supported Capacity Configuration List log, a hierarchy of capacity
+/// configurations, their endurance groups, channels and media units.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SuppCapConfigList<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    header: &'a CapConfigListHeader,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "configs", serialize_with = "serialize_configs")
+    )]
+    descs: &'a [u8],
+}
+
+impl<'a> SuppCapConfigList<'a> {
+    /// Parse the header and validate that every descriptor at each level of
+    /// the hierarchy is present. On error, the length needed to hold the
+    /// entire log, as far as can be determined, is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header = prefix::<CapConfigListHeader>(bytes)?;
+        let header_len = size_of::<CapConfigListHeader>();
+        let len = list_len(&bytes[header_len..], header.sccn as u16, cap_config_len)
+            .map_err(|need| header_len + need)?;
+        Ok(SuppCapConfigList {
+            header,
+            descs: &bytes[header_len..header_len + len],
+        })
+    }
+
+    pub fn header(&self) -> &'a CapConfigListHeader {
+        self.header
+    }
+
+    pub fn configs(&self) -> impl Iterator<Item = CapConfig<'a>> + 'a {
+        configs(self.descs)
+    }
+
+    pub fn config(&self, capid: u16) -> Option<CapConfig<'a>> {
+        self.configs().find(|config| config.desc.capid == capid)
+    }
+}
+
+/// Capacity configuration along with the endurance groups it would create.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CapConfig<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub desc: &'a CapConfigDesc,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "endur_grps", serialize_with = "serialize_endur_grps")
+    )]
+    egs: &'a [u8],
+}
+
+impl<'a> CapConfig<'a> {
+    pub fn endur_grps(&self) -> impl Iterator<Item = EndurGrpConfig<'a>> + 'a {
+        endur_grps(self.egs)
+    }
+
+    /// Total capacity in bytes of every endurance group.
+    pub fn total_cap(&self) -> u128 {
+        self.endur_grps()
+            .map(|eg| eg.desc.tegcap)
+            .fold(0, u128::saturating_add)
+    }
+}
+
+/// Endurance group configuration along with its NVM sets and channels.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EndurGrpConfig<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub desc: &'a EndurGrpConfigDesc,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_ids"))]
+    nvmsetids: &'a [u8],
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "channels", serialize_with = "serialize_chans")
+    )]
+    chans: &'a [u8],
+}
+
+impl<'a> EndurGrpConfig<'a> {
+    pub fn nvmsetids(&self) -> impl Iterator<Item = u16> + 'a {
+        ids(self.nvmsetids)
+    }
+
+    pub fn channels(&self) -> impl Iterator<Item = ChanConfig<'a>> + 'a {
+        chans(self.chans)
+    }
+
+    /// Identifiers of the media units in every channel.
+    pub fn muids(&self) -> impl Iterator<Item = u16> + 'a {
+        self.channels()
+            .flat_map(|chan| chan.mus.iter().map(|mu| mu.muid))
+    }
+}
+
+/// Channel configuration along with its media units.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChanConfig<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub desc: &'a ChanConfigDesc,
+    pub mus: &'a [MediaUnitConfigDesc],
+}
+
+fn cap_config_len(bytes: &[u8]) -> Result<usize, usize> {
+    let desc = prefix::<CapConfigDesc>(bytes)?;
+    let egs_at = size_of::<CapConfigDesc>();
+    let len = list_len(&bytes[egs_at..], desc.egcn, endur_grp_config_len)
+        .map_err(|need| egs_at + need)?;
+    Ok(egs_at + len)
+}
+
+/// Offset of the channel count, which follows the NVM set identifiers.
+fn chcnt_offset(desc: &EndurGrpConfigDesc) -> usize {
+    size_of::<EndurGrpConfigDesc>() + desc.egsets as usize * size_of::<u16>()
+}
+
+fn endur_grp_config_len(bytes: &[u8]) -> Result<usize, usize> {
+    let desc = prefix::<EndurGrpConfigDesc>(bytes)?;
+    let chcnt_at = chcnt_offset(desc);
+    let chans_at = chcnt_at + size_of::<u16>();
+    let chcnt = bytes.get(chcnt_at..chans_at).ok_or(chans_at)?;
+    let chcnt = u16::from_le_bytes(chcnt.try_into().unwrap());
+    let len =
+        list_len(&bytes[chans_at..], chcnt, chan_config_len).map_err(|need| chans_at + need)?;
+    Ok(chans_at + len)
+}
+
+fn chan_config_len(bytes: &[u8]) -> Result<usize, usize> {
+    let desc = prefix::<ChanConfigDesc>(bytes)?;
+    let len = size_of::<ChanConfigDesc>() + desc.chmus as usize * size_of::<MediaUnitConfigDesc>();
+    if len > bytes.len() {
+        Err(len)
+    } else {
+        Ok(len)
+    }
+}
+
+// Lengths were validated when the log was parsed, so each level below is
+// split along the same boundaries.
+
+fn configs(descs: &[u8]) -> impl Iterator<Item = CapConfig<'_>> {
+    split(descs, cap_config_len).map(|config| CapConfig {
+        desc: prefix(config).expect("validated"),
+        egs: &config[size_of::<CapConfigDesc>()..],
+    })
+}
+
+fn endur_grps(descs: &[u8]) -> impl Iterator<Item = EndurGrpConfig<'_>> {
+    split(descs, endur_grp_config_len).map(|eg| {
+        let desc = prefix::<EndurGrpConfigDesc>(eg).expect("validated");
+        let chcnt_at = chcnt_offset(desc);
+        EndurGrpConfig {
+            desc,
+            nvmsetids: &eg[size_of::<EndurGrpConfigDesc>()..chcnt_at],
+            chans: &eg[chcnt_at + size_of::<u16>()..],
+        }
+    })
+}
+
+fn chans(descs: &[u8]) -> impl Iterator<Item = ChanConfig<'_>> {
+    split(descs, chan_config_len).map(|chan| ChanConfig {
+        desc: prefix(chan).expect("validated"),
+        mus: <[MediaUnitConfigDesc]>::from_bytes(&chan[size_of::<ChanConfigDesc>()..])
+            .expect("align 1"),
+    })
+}
+
+/// Leading structure of `bytes`, or the length needed to hold it.
+fn prefix<T: TransmuteSafe>(bytes: &[u8]) -> Result<&T, usize> {
+    bytes
+        .get(..size_of::<T>())
+        .and_then(|bytes| T::from_bytes(bytes).ok())
+        .ok_or(size_of::<T>())
+}
+
+/// Total length of `count` consecutive elements, or the length needed to
+/// hold them as far as can be determined.
+fn list_len(
+    bytes: &[u8],
+    count: u16,
+    elem_len: fn(&[u8]) -> Result<usize, usize>,
+) -> Result<usize, usize> {
+    let mut len = 0;
+    for _ in 0..count {
+        len += elem_len(&bytes[len..]).map_err(|need| len + need)?;
+    }
+    Ok(len)
+}
+
+/// Split a validated list into its elements.
+fn split(
+    mut bytes: &[u8],
+    elem_len: fn(&[u8]) -> Result<usize, usize>,
+) -> impl Iterator<Item = &[u8]> {
+    core::iter::from_fn(move || {
+        let (elem, rest) = bytes.split_at(elem_len(bytes).ok()?);
+        bytes = rest;
+        Some(elem)
+    })
+}
+
+fn ids(bytes: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    bytes
+        .chunks_exact(size_of::<u16>())
+        .map(|id| u16::from_le_bytes(id.try_into().unwrap()))
+}
+
+#[cfg(feature = "serde")]
+fn serialize_ids<S: serde::Serializer>(bytes: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::util::SerializeIter(|| ids(bytes)), serializer)
+}
+
+#[cfg(feature = "serde")]
+fn serialize_units<S: serde::Serializer>(descs: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::util::SerializeIter(|| units(descs)), serializer)
+}
+
+#[cfg(feature = "serde")]
+fn serialize_configs<S: serde::Serializer>(
+    descs: &&[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::util::SerializeIter(|| configs(descs)), serializer)
+}
+
+#[cfg(feature = "serde")]
+fn serialize_endur_grps<S: serde::Serializer>(
+    descs: &&[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(
+        &crate::util::SerializeIter(|| endur_grps(descs)),
+        serializer,
+    )
+}
+
+#[cfg(feature = "serde")]
+fn serialize_chans<S: serde::Serializer>(descs: &&[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::util::SerializeIter(|| chans(descs)), serializer)
+}
+
+#[test]
+fn media_unit_status_parse() {
+    let mut bytes = [0u8; 16 + 16 + 4 + 20];
+    bytes[0] = 2;
+    bytes[2] = 2;
+    bytes[16] = 1;
+    bytes[28] = 2;
+    bytes[29] = 16;
+    bytes[32..34].copy_from_slice(&7u16.to_le_bytes());
+    bytes[34..36].copy_from_slice(&9u16.to_le_bytes());
+    bytes[36] = 2;
+    bytes[48] = 1;
+    bytes[49] = 18;
+    bytes[54..56].copy_from_slice(&3u16.to_le_bytes());
+    let log = MediaUnitStatusLog::parse(&bytes[..]).unwrap();
+    let units: Vec<_> = log
+        .units()
+        .map(|unit| (unit.desc.muid, unit.chanids().collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(units, [(1, vec![7, 9]), (2, vec![3])]);
+    assert_eq!(log.unit(2).unwrap().desc.desc_len(), 20);
+
+    assert_eq!(MediaUnitStatusLog::parse(&bytes[..40]).err(), Some(52));
+    bytes[0] = 3;
+    assert_eq!(MediaUnitStatusLog::parse(&bytes[..]).err(), Some(72));
+}
+
+#[test]
+fn cap_config_list_parse() {
+    // One configuration of one endurance group with NVM set 1, and two
+    // channels of one and two media units.
+    let mut bytes = vec![0u8; 16 + 32 + 82 + 2 + 2 + 4 + 8 + 4 + 16];
+    bytes[0] = 1;
+    bytes[16] = 5;
+    bytes[20] = 1;
+    bytes[48] = 1;
+    bytes[64..80].copy_from_slice(&(1u128 << 40).to_le_bytes());
+    bytes[128] = 1;
+    bytes[130] = 1;
+    bytes[132] = 2;
+    bytes[134] = 0;
+    bytes[136] = 1;
+    bytes[138] = 0x10;
+    bytes[146] = 1;
+    bytes[148] = 2;
+    bytes[150] = 0x11;
+    bytes[158] = 0x12;
+    let list = SuppCapConfigList::parse(&bytes).unwrap();
+    let config = list.config(5).unwrap();
+    assert_eq!(config.total_cap(), 1 << 40);
+    let eg = config.endur_grps().next().unwrap();
+    assert_eq!(eg.nvmsetids().collect::<Vec<_>>(), [1]);
+    let chans: Vec<_> = eg
+        .channels()
+        .map(|chan| (chan.desc.chanid, chan.mus.len()))
+        .collect();
+    assert_eq!(chans, [(0, 1), (1, 2)]);
+    assert_eq!(eg.muids().collect::<Vec<_>>(), [0x10, 0x11, 0x12]);
+
+    assert_eq!(SuppCapConfigList::parse(&bytes[..100]).err(), Some(130));
+    assert_eq!(SuppCapConfigList::parse(&bytes[..150]).err(), Some(166));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn capacity_round_trip() {
+    use crate::util::{pattern, round_trip};
+
+    let mut bytes = pattern(16, 22);
+    bytes[6..16].fill(0);
+    round_trip::<MediaUnitStatusHeader>(&bytes);
+    let mut bytes = pattern(16, 22);
+    bytes[14..16].fill(0);
+    round_trip::<MediaUnitStatusDesc>(&bytes);
+    let mut bytes = pattern(16, 22);
+    bytes[1..16].fill(0);
+    round_trip::<CapConfigListHeader>(&bytes);
+    let mut bytes = pattern(32, 22);
+    bytes[6..32].fill(0);
+    round_trip::<CapConfigDesc>(&bytes);
+    let mut bytes = pattern(82, 22);
+    bytes[4..16].fill(0);
+    bytes[64..80].fill(0);
+    round_trip::<EndurGrpConfigDesc>(&bytes);
+    round_trip::<ChanConfigDesc>(&pattern(4, 22));
+    let mut bytes = pattern(8, 22);
+    bytes[2..6].fill(0);
+    round_trip::<MediaUnitConfigDesc>(&bytes);
+}
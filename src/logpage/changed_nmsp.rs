@@ -0,0 +1,157 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeSet, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::iter::FromIterator;
+
+use crate::NmspList;
+
+/// Changed Namespace List (Log Identifier 04h)
+pub type ChangedNmspList = NmspList;
+
+/// Cached set of attached namespaces, reconciled against namespace attribute
+/// notices.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NmspInventory {
+    nsids: BTreeSet<u32>,
+}
+
+#[cfg(feature = "alloc")]
+impl NmspInventory {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn contains(&self, nsid: u32) -> bool {
+        self.nsids.contains(&nsid)
+    }
+
+    /// Cached namespaces in ascending order.
+    pub fn nsids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.nsids.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nsids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nsids.is_empty()
+    }
+
+    /// Classify each namespace in `changes` by comparing the cache with the
+    /// namespaces now `active` on the controller. If the list overflowed,
+    /// every cached or active namespace is considered changed.
+    ///
+    /// Namespaces which are neither cached nor active, e.g. ones created and
+    /// deleted without being attached, are left out.
+    pub fn diff(
+        &self,
+        changes: &ChangedNmspList,
+        active: impl IntoIterator<Item = u32>,
+    ) -> NmspDiff {
+        let active: BTreeSet<u32> = active.into_iter().collect();
+        let candidates: BTreeSet<u32> = if changes.overflowed() {
+            self.nsids.union(&active).copied().collect()
+        } else {
+            changes.nsids().collect()
+        };
+        let mut diff = NmspDiff::default();
+        for nsid in candidates {
+            match (self.contains(nsid), active.contains(&nsid)) {
+                (false, true) => diff.attached.push(nsid),
+                (true, false) => diff.detached.push(nsid),
+                (true, true) => diff.changed.push(nsid),
+                (false, false) => {}
+            }
+        }
+        diff
+    }
+
+    /// Bring the cache up to date with `diff`.
+    pub fn apply(&mut self, diff: &NmspDiff) {
+        self.nsids.extend(diff.attached.iter().copied());
+        diff.detached.iter().for_each(|nsid| {
+            self.nsids.remove(nsid);
+        });
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<u32> for NmspInventory {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        NmspInventory {
+            nsids: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Namespaces reported by a Changed Namespace List, each in ascending order.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NmspDiff {
+    pub attached: Vec<u32>,
+    pub detached: Vec<u32>,
+    pub changed: Vec<u32>,
+}
+
+#[cfg(feature = "alloc")]
+impl NmspDiff {
+    /// Namespaces which must be rescanned: those newly attached or changed.
+    pub fn rescan(&self) -> impl Iterator<Item = u32> + '_ {
+        self.attached.iter().chain(self.changed.iter()).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attached.is_empty() && self.detached.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn nmsp_inventory_diff() {
+    use crate::FromBytes;
+
+    let mut bytes = [0u8; 4096];
+    for (idx, nsid) in [2u32, 3, 5, 9].iter().enumerate() {
+        bytes[idx * 4..idx * 4 + 4].copy_from_slice(&nsid.to_le_bytes());
+    }
+    let list = ChangedNmspList::from_bytes(&bytes[..]).unwrap();
+    let mut inventory: NmspInventory = [1, 2, 3].iter().copied().collect();
+    let diff = inventory.diff(list, [1, 3, 5].iter().copied());
+    assert_eq!(diff.attached, [5]);
+    assert_eq!(diff.detached, [2]);
+    assert_eq!(diff.changed, [3]);
+    assert_eq!(diff.rescan().collect::<Vec<_>>(), [5, 3]);
+    inventory.apply(&diff);
+    assert_eq!(inventory.nsids().collect::<Vec<_>>(), [1, 3, 5]);
+
+    let mut bytes = [0u8; 4096];
+    bytes[..4].copy_from_slice(&[0xff; 4]);
+    let list = ChangedNmspList::from_bytes(&bytes[..]).unwrap();
+    assert!(list.overflowed());
+    let diff = inventory.diff(list, [3, 5, 7].iter().copied());
+    assert_eq!(diff.attached, [7]);
+    assert_eq!(diff.detached, [1]);
+    assert_eq!(diff.changed, [3, 5]);
+}
@@ -0,0 +1,62 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{Reserved, TransmuteSafe};
+
+/// Changed Zone List (Log Identifier BFh), specific to a zoned namespace
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangedZoneList {
+    /// Number of Zone Identifiers
+    #[loc(0:1)]
+    pub nzid: u16,
+    #[loc(2:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2: Reserved<6>,
+    /// Zone identifiers, each the start LBA of a zone
+    #[loc(8:4095)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub zids: [u64; 511],
+}
+
+impl TransmuteSafe for ChangedZoneList {}
+
+impl ChangedZoneList {
+    pub const CAPACITY: usize = 511;
+
+    /// Value of `nzid` when more zones changed than the list can hold.
+    pub const OVERFLOW: u16 = 0xffff;
+
+    /// Zones whose descriptors changed, none if the list overflowed.
+    pub fn zids(&self) -> impl Iterator<Item = u64> + '_ {
+        let nzid = if self.overflowed() {
+            0
+        } else {
+            (self.nzid as usize).min(Self::CAPACITY)
+        };
+        (0..nzid).map(move |idx| self.zids[idx])
+    }
+
+    /// Whether more zones changed than the list can hold, in which case
+    /// every zone must be reported again.
+    pub fn overflowed(&self) -> bool {
+        self.nzid == Self::OVERFLOW
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{Reserved, TransmuteSafe};
+
+use modular_bitfield::prelude::*;
+
+/// Commands Supported and Effects (Log Identifier 05h)
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CmdEffectsLog {
+    #[loc(0:1023)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub acs: [CmdEffects; 256],
+    #[loc(1024:2047)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub iocs: [CmdEffects; 256],
+    #[loc(2048:4095)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2048: Reserved<2048>,
+}
+
+impl TransmuteSafe for CmdEffectsLog {}
+
+impl CmdEffectsLog {
+    pub fn admin(&self, opcode: u8) -> CmdEffects {
+        self.acs[opcode as usize]
+    }
+
+    pub fn io(&self, opcode: u8) -> CmdEffects {
+        self.iocs[opcode as usize]
+    }
+
+    /// Opcodes and effects of each supported admin command.
+    pub fn supported_admin(&self) -> impl Iterator<Item = (u8, CmdEffects)> + '_ {
+        supported(&self.acs)
+    }
+
+    /// Opcodes and effects of each supported I/O command.
+    pub fn supported_io(&self) -> impl Iterator<Item = (u8, CmdEffects)> + '_ {
+        supported(&self.iocs)
+    }
+}
+
+fn supported(effects: &[CmdEffects; 256]) -> impl Iterator<Item = (u8, CmdEffects)> + '_ {
+    effects
+        .iter()
+        .enumerate()
+        .filter(|(_, effects)| effects.csupp())
+        .map(|(opcode, effects)| (opcode as u8, *effects))
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct CmdEffects {
+    pub csupp: bool,
+    pub lbcc: bool,
+    pub ncc: bool,
+    pub nic: bool,
+    pub ccc: bool,
+    #[skip]
+    __rsvd: B11,
+    pub cse: Cse,
+    pub uss: bool,
+    #[skip]
+    __rsvd: B12,
+}
+
+impl CmdEffects {
+    /// Whether namespaces should be rescanned after the command completes,
+    /// due to a change in namespace capabilities or inventory.
+    pub fn nmsp_rescan_required(&self) -> bool {
+        self.ncc() || self.nic()
+    }
+}
+
+/// NVMe-MI Commands Supported and Effects (Log Identifier 13h)
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MiCmdEffectsLog {
+    #[loc(0:1023)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub mics: [MiCmdEffects; 256],
+    #[loc(1024:4095)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1024: Reserved<3072>,
+}
+
+impl TransmuteSafe for MiCmdEffectsLog {}
+
+impl MiCmdEffectsLog {
+    pub fn mi(&self, opcode: u8) -> MiCmdEffects {
+        self.mics[opcode as usize]
+    }
+
+    /// Opcodes and effects of each supported NVMe-MI command.
+    pub fn supported(&self) -> impl Iterator<Item = (u8, MiCmdEffects)> + '_ {
+        self.mics
+            .iter()
+            .enumerate()
+            .filter(|(_, effects)| effects.csupp())
+            .map(|(opcode, effects)| (opcode as u8, *effects))
+    }
+}
+
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct MiCmdEffects {
+    pub csupp: bool,
+    pub udcc: bool,
+    pub ncc: bool,
+    pub nic: bool,
+    pub ccc: bool,
+    #[skip]
+    __rsvd: B15,
+    pub nmsp_scope: bool,
+    pub ctrl_scope: bool,
+    pub nvm_set_scope: bool,
+    pub endur_grp_scope: bool,
+    pub domain_scope: bool,
+    pub nvm_subsys_scope: bool,
+    #[skip]
+    __rsvd: B6,
+}
+
+impl MiCmdEffects {
+    /// Whether the command may change user data, namespaces or controller
+    /// capabilities, as opposed to only reporting state.
+    pub fn has_effects(&self) -> bool {
+        self.udcc() || self.ncc() || self.nic() || self.ccc()
+    }
+
+    /// Whether namespaces should be rescanned after the command completes,
+    /// due to a change in namespace capabilities or inventory.
+    pub fn nmsp_rescan_required(&self) -> bool {
+        self.ncc() || self.nic()
+    }
+}
+
+/// NVMe Management Interface Command Set opcodes
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MiOpcode {
+    ReadMiDataStructure = 0x00,
+    NvmSubsysHealthStatusPoll = 0x01,
+    CtrlHealthStatusPoll = 0x02,
+    ConfigSet = 0x03,
+    ConfigGet = 0x04,
+    VpdRead = 0x05,
+    VpdWrite = 0x06,
+    Reset = 0x07,
+    SesReceive = 0x08,
+    SesSend = 0x09,
+    MgmtEndpointBufRead = 0x0a,
+    MgmtEndpointBufWrite = 0x0b,
+    Shutdown = 0x0c,
+}
+
+/// Command Submission and Execution
+#[derive(BitfieldSpecifier, Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[bits = 3]
+pub enum Cse {
+    NoRestriction = 0,
+    SingleNmsp = 1,
+    SingleCmd = 2,
+
+    // The enum must exhaust the bits or else it can't reliably be transmuted.
+    #[doc(hidden)]
+    _Reserved3 = 3,
+    #[doc(hidden)]
+    _Reserved4 = 4,
+    #[doc(hidden)]
+    _Reserved5 = 5,
+    #[doc(hidden)]
+    _Reserved6 = 6,
+    #[doc(hidden)]
+    _Reserved7 = 7,
+}
+
+#[test]
+fn cmd_effects_accessors() {
+    use crate::FromBytes;
+
+    let mut bytes = [0u8; 4096];
+    bytes[0x80 * 4..0x80 * 4 + 4].copy_from_slice(&0x0001_000fu32.to_le_bytes());
+    bytes[1024 + 4..1024 + 8].copy_from_slice(&0x0000_0003u32.to_le_bytes());
+    let log = CmdEffectsLog::from_bytes(&bytes[..]).unwrap();
+    let format = log.admin(0x80);
+    assert!(format.csupp() && format.lbcc() && format.nmsp_rescan_required());
+    assert_eq!(format.cse(), Cse::SingleNmsp);
+    assert!(!log.admin(0x81).csupp());
+    assert_eq!(
+        log.supported_admin().map(|(op, _)| op).collect::<Vec<_>>(),
+        [0x80]
+    );
+    assert_eq!(
+        log.supported_io().map(|(op, _)| op).collect::<Vec<_>>(),
+        [0x01]
+    );
+    assert!(!log.io(0x01).nmsp_rescan_required());
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{EndurGrpCritWarning, EventAggregate, Reserved, TransmuteSafe};
+
+/// Endurance Group Information (Log Identifier 09h)
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EndurGrpLog {
+    #[loc(0:0)]
+    pub crit_warning: u8,
+    #[loc(1:1)]
+    pub egfeat: u8,
+    #[loc(2:2)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2: Reserved<1>,
+    #[loc(3:3)]
+    pub avail_spare: u8,
+    #[loc(4:4)]
+    pub avail_spare_thresh: u8,
+    #[loc(5:5)]
+    pub percent_used: u8,
+    /// Domain Identifier
+    #[loc(6:7)]
+    pub domainid: u16,
+    #[loc(8:31)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd8: Reserved<24>,
+    #[loc(32:47)]
+    pub endur_estimate: u128,
+    #[loc(48:63)]
+    pub data_units_read: u128,
+    #[loc(64:79)]
+    pub data_units_written: u128,
+    #[loc(80:95)]
+    pub media_units_written: u128,
+    #[loc(96:111)]
+    pub host_read_cmds: u128,
+    #[loc(112:127)]
+    pub host_write_cmds: u128,
+    #[loc(128:143)]
+    pub mad_integrity_errs: u128,
+    #[loc(144:159)]
+    pub num_err_log_entries: u128,
+    #[loc(160:175)]
+    pub tegcap: u128,
+    #[loc(176:191)]
+    pub uegcap: u128,
+    #[loc(192:511)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd192: Reserved<320>,
+}
+
+impl TransmuteSafe for EndurGrpLog {}
+
+impl EndurGrpLog {
+    pub fn has_crit_warning(&self) -> bool {
+        self.crit_warning > 0
+    }
+    pub fn crit_warning(&self) -> EndurGrpCritWarning {
+        EndurGrpCritWarning::from(self.crit_warning)
+    }
+}
+
+/// Endurance Group Event Aggregate (Log Identifier 0Fh), listing the
+/// endurance groups with critical warnings or other events outstanding.
+pub type EndurGrpEventAggregate<'a> = EventAggregate<'a>;
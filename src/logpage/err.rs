@@ -22,7 +22,7 @@ use crate::{Reserved, StatusField, TransmuteSafe};
 use modular_bitfield::prelude::*;
 
 #[test_structure(size = 64)]
-#[repr(C)]
+#[repr(C, packed)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ErrLogEntry {
     #[loc(0:7)]
@@ -32,10 +32,8 @@ pub struct ErrLogEntry {
     #[loc(10:11)]
     pub cmd_id: u16,
     #[loc(12:13)]
-    #[cfg_attr(feature = "serde", serde(with = "crate::status::StatusFieldUnpacked"))]
     pub status_field: StatusField,
     #[loc(14:15)]
-    #[cfg_attr(feature = "serde", serde(with = "ParamErrLocUnpacked"))]
     pub param_err_loc: ParamErrLoc,
     #[loc(16:23)]
     pub lba: u64,
@@ -61,6 +59,11 @@ impl TransmuteSafe for ErrLogEntry {}
 
 #[bitfield]
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "ParamErrLocUnpacked", from = "ParamErrLocUnpacked")
+)]
 pub struct ParamErrLoc {
     pub byte: u8,
     pub bit: B3,
@@ -70,11 +73,8 @@ pub struct ParamErrLoc {
 
 #[cfg(feature = "serde")]
 #[derive(serde::Serialize, serde::Deserialize)]
-#[serde(remote = "ParamErrLoc")]
 struct ParamErrLocUnpacked {
-    #[serde(getter = "ParamErrLoc::byte")]
     pub byte: u8,
-    #[serde(getter = "ParamErrLoc::bit")]
     pub bit: u8,
 }
 
@@ -86,3 +86,13 @@ impl From<ParamErrLocUnpacked> for ParamErrLoc {
             .with_bit(unpacked.bit)
     }
 }
+
+#[cfg(feature = "serde")]
+impl From<ParamErrLoc> for ParamErrLocUnpacked {
+    fn from(packed: ParamErrLoc) -> Self {
+        ParamErrLocUnpacked {
+            byte: packed.byte(),
+            bit: packed.bit(),
+        }
+    }
+}
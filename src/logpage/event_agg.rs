@@ -0,0 +1,124 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::{
+    convert::{TryFrom, TryInto},
+    mem::size_of,
+};
+
+/// Event aggregate log, a count of entries followed by that many 16-bit
+/// identifiers: NVM Sets for Predictable Latency Event Aggregate, and
+/// Endurance Groups for Endurance Group Event Aggregate.
+#[derive(Clone, Copy)]
+pub struct EventAggregate<'a> {
+    num_entries: u64,
+    ids: &'a [u8],
+}
+
+impl<'a> EventAggregate<'a> {
+    /// Identifiers are 16 bits, so no more entries than this can be distinct.
+    pub const MAX_ENTRIES: usize = 0x1_0000;
+
+    /// Parse the entry count and whichever entries fit in `bytes`, as the log
+    /// may be read only in part. On error, the length of the entry count is
+    /// returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<u64>();
+        let num_entries = bytes
+            .get(..header_len)
+            .map(|num| u64::from_le_bytes(num.try_into().unwrap()))
+            .ok_or(header_len)?;
+        let ids = &bytes[header_len..];
+        let len = usize::try_from(num_entries)
+            .unwrap_or(usize::MAX)
+            .min(ids.len() / size_of::<u16>());
+        Ok(EventAggregate {
+            num_entries,
+            ids: &ids[..len * size_of::<u16>()],
+        })
+    }
+
+    /// Length needed to hold the entire log, with the number of entries
+    /// limited to `MAX_ENTRIES`.
+    pub fn full_len(&self) -> usize {
+        let num = usize::try_from(self.num_entries).unwrap_or(usize::MAX);
+        size_of::<u64>() + num.min(Self::MAX_ENTRIES) * size_of::<u16>()
+    }
+
+    /// Whether every entry counted was read.
+    pub fn is_complete(&self) -> bool {
+        (self.ids.len() / size_of::<u16>()) as u64 == self.num_entries
+    }
+
+    pub fn num_entries(&self) -> u64 {
+        self.num_entries
+    }
+
+    /// Identifiers with events outstanding.
+    pub fn ids(&self) -> impl Iterator<Item = u16> + 'a {
+        self.ids
+            .chunks_exact(size_of::<u16>())
+            .map(|id| u16::from_le_bytes(id.try_into().unwrap()))
+    }
+
+    /// Length of the log, from the entry count through the last entry read.
+    pub fn len(&self) -> usize {
+        size_of::<u64>() + self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for EventAggregate<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut log = serializer.serialize_struct("EventAggregate", 2)?;
+        log.serialize_field("num_entries", &self.num_entries)?;
+        log.serialize_field("ids", &crate::util::SerializeIter(|| self.ids()))?;
+        log.end()
+    }
+}
+
+#[test]
+fn event_aggregate_parse() {
+    let mut bytes = [0u8; 16];
+    bytes[0] = 3;
+    bytes[8..10].copy_from_slice(&1u16.to_le_bytes());
+    bytes[10..12].copy_from_slice(&4u16.to_le_bytes());
+    bytes[12..14].copy_from_slice(&0x100u16.to_le_bytes());
+    let log = EventAggregate::parse(&bytes[..]).unwrap();
+    assert_eq!(log.num_entries(), 3);
+    assert_eq!((log.len(), log.full_len()), (14, 14));
+    assert!(log.is_complete());
+    assert_eq!(log.ids().collect::<Vec<_>>(), [1, 4, 0x100]);
+
+    assert_eq!(EventAggregate::parse(&bytes[..4]).err(), Some(8));
+    let log = EventAggregate::parse(&bytes[..11]).unwrap();
+    assert_eq!((log.len(), log.full_len()), (10, 14));
+    assert!(!log.is_complete());
+    assert_eq!(log.ids().collect::<Vec<_>>(), [1]);
+
+    bytes[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+    let log = EventAggregate::parse(&bytes[..]).unwrap();
+    assert_eq!((log.len(), log.full_len()), (16, 8 + 0x2_0000));
+}
@@ -0,0 +1,588 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::mem::size_of;
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+use crate::{FromBytes, Reserved, TransmuteSafe};
+
+/// FDP Configurations (Log Identifier 20h) header
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FdpConfigLogHeader {
+    /// Number of FDP Configurations, 0's based
+    #[loc(0:1)]
+    pub ncfg: u16,
+    #[loc(2:2)]
+    pub version: u8,
+    #[loc(3:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd3: Reserved<1>,
+    /// Size of the log in bytes, including this header
+    #[loc(4:7)]
+    pub sze: u32,
+    #[loc(8:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd8: Reserved<8>,
+}
+
+impl TransmuteSafe for FdpConfigLogHeader {}
+
+/// FDP Configuration Descriptor, which is followed by `nruh` reclaim unit
+/// handle descriptors and vendor specific data
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FdpConfigDesc {
+    /// Descriptor Size in bytes
+    #[loc(0:1)]
+    pub dsze: u16,
+    #[loc(2:2)]
+    pub fdpa: Fdpa,
+    /// Vendor Specific Size in dwords
+    #[loc(3:3)]
+    pub vss: u8,
+    /// Number of Reclaim Groups
+    #[loc(4:7)]
+    pub nrg: u32,
+    /// Number of Reclaim Unit Handles
+    #[loc(8:9)]
+    pub nruh: u16,
+    /// Max Placement Identifiers, 0's based
+    #[loc(10:11)]
+    pub maxpids: u16,
+    /// Number of Namespaces Supported
+    #[loc(12:15)]
+    pub nns: u32,
+    /// Reclaim Unit Nominal Size in bytes
+    #[loc(16:23)]
+    pub runs: u64,
+    /// Estimated Reclaim Unit Time Limit in seconds
+    #[loc(24:27)]
+    pub erutl: u32,
+    #[loc(28:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd28: Reserved<36>,
+}
+
+impl TransmuteSafe for FdpConfigDesc {}
+
+impl FdpConfigDesc {
+    /// Length of the descriptor, which always covers its reclaim unit handle
+    /// descriptors.
+    pub fn desc_len(&self) -> usize {
+        (self.dsze as usize).max(size_of::<Self>() + self.nruh as usize * size_of::<RuhDesc>())
+    }
+
+    /// Placement identifier of placement handle `phndl` within reclaim
+    /// group `rgid`. The reclaim group occupies the upper `rgif` bits.
+    pub fn pid(&self, rgid: u16, phndl: u16) -> u16 {
+        match self.fdpa.rgif() {
+            0 => phndl,
+            rgif => (rgid << (16 - rgif as u32)) | (phndl & (u16::MAX >> rgif as u32)),
+        }
+    }
+
+    /// Reclaim group and placement handle of placement identifier `pid`.
+    pub fn split_pid(&self, pid: u16) -> (u16, u16) {
+        match self.fdpa.rgif() {
+            0 => (0, pid),
+            rgif => (pid >> (16 - rgif as u32), pid & (u16::MAX >> rgif as u32)),
+        }
+    }
+}
+
+/// FDP Attributes
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct Fdpa {
+    /// Reclaim Group Identifier Format, the number of placement identifier
+    /// bits which select the reclaim group
+    pub rgif: B4,
+    /// FDP Volatile Write Cache
+    pub fdpvwc: bool,
+    #[skip]
+    __rsvd: B2,
+    /// FDP Configuration Valid
+    pub fdpcv: bool,
+}
+
+/// Reclaim Unit Handle Descriptor
+#[test_structure(size = 4)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuhDesc {
+    #[loc(0:0)]
+    pub ruht: u8,
+    #[loc(1:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<3>,
+}
+
+impl TransmuteSafe for RuhDesc {}
+
+impl RuhDesc {
+    pub fn ruht(&self) -> Option<RuhType> {
+        FromPrimitive::from_u8(self.ruht)
+    }
+}
+
+/// Reclaim Unit Handle Type
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RuhType {
+    InitiallyIsolated = 0x1,
+    PersistentlyIsolated = 0x2,
+}
+
+/// FDP Configurations log, whose descriptors vary in length.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FdpConfigLog<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    header: &'a FdpConfigLogHeader,
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "configs", serialize_with = "serialize_configs")
+    )]
+    descs: &'a [u8],
+}
+
+impl<'a> FdpConfigLog<'a> {
+    /// Parse the header and validate that every configuration descriptor it
+    /// counts is present. On error, the length needed to hold the entire
+    /// log, as far as can be determined, is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<FdpConfigLogHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| FdpConfigLogHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let descs = &bytes[header_len..];
+        let mut len = 0usize;
+        for _ in 0..=header.ncfg {
+            len += config_len(&descs[len..]).map_err(|need| header_len + len + need)?;
+        }
+        Ok(FdpConfigLog {
+            header,
+            descs: &descs[..len],
+        })
+    }
+
+    pub fn header(&self) -> &'a FdpConfigLogHeader {
+        self.header
+    }
+
+    /// Configurations in order of their index, which is how the FDP feature
+    /// selects them.
+    pub fn configs(&self) -> impl Iterator<Item = FdpConfig<'a>> + 'a {
+        configs(self.descs)
+    }
+
+    pub fn config(&self, idx: usize) -> Option<FdpConfig<'a>> {
+        self.configs().nth(idx)
+    }
+}
+
+/// FDP configuration along with its reclaim unit handles.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FdpConfig<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub desc: &'a FdpConfigDesc,
+    pub ruhs: &'a [RuhDesc],
+}
+
+fn config_len(bytes: &[u8]) -> Result<usize, usize> {
+    let len = bytes
+        .get(..size_of::<FdpConfigDesc>())
+        .and_then(|desc| FdpConfigDesc::from_bytes(desc).ok())
+        .ok_or(size_of::<FdpConfigDesc>())?
+        .desc_len();
+    if len > bytes.len() {
+        Err(len)
+    } else {
+        Ok(len)
+    }
+}
+
+fn configs(mut descs: &[u8]) -> impl Iterator<Item = FdpConfig<'_>> {
+    // Lengths were validated when the log was parsed.
+    core::iter::from_fn(move || {
+        let (config, rest) = descs.split_at(config_len(descs).ok()?);
+        descs = rest;
+        let (desc, ruhs) = config.split_at(size_of::<FdpConfigDesc>());
+        let desc = FdpConfigDesc::from_bytes(desc).expect("align 1");
+        let ruhs = &ruhs[..desc.nruh as usize * size_of::<RuhDesc>()];
+        Some(FdpConfig {
+            desc,
+            ruhs: <[RuhDesc]>::from_bytes(ruhs).expect("align 1"),
+        })
+    })
+}
+
+#[cfg(feature = "serde")]
+fn serialize_configs<S: serde::Serializer>(
+    descs: &&[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&crate::util::SerializeIter(|| configs(descs)), serializer)
+}
+
+/// Reclaim Unit Handle Usage (Log Identifier 21h) header
+#[test_structure(size = 8)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuhUsageLogHeader {
+    /// Number of Reclaim Unit Handles
+    #[loc(0:1)]
+    pub nruh: u16,
+    #[loc(2:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2: Reserved<6>,
+}
+
+impl TransmuteSafe for RuhUsageLogHeader {}
+
+/// Reclaim Unit Handle Usage Descriptor
+#[test_structure(size = 8)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuhUsageDesc {
+    /// Reclaim Unit Handle Attributes
+    #[loc(0:0)]
+    pub ruha: u8,
+    #[loc(1:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<7>,
+}
+
+impl TransmuteSafe for RuhUsageDesc {}
+
+impl RuhUsageDesc {
+    pub fn ruha(&self) -> Option<RuhUsage> {
+        FromPrimitive::from_u8(self.ruha)
+    }
+}
+
+/// How a reclaim unit handle is referenced by namespaces
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RuhUsage {
+    Unused = 0x0,
+    /// Referenced by a placement handle chosen by the host.
+    HostSpecified = 0x1,
+    /// Referenced by a placement handle chosen by the controller.
+    CtrlSpecified = 0x2,
+}
+
+/// Reclaim Unit Handle Usage log, indexed by reclaim unit handle identifier.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RuhUsageLog<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub header: &'a RuhUsageLogHeader,
+    pub ruhs: &'a [RuhUsageDesc],
+}
+
+impl<'a> RuhUsageLog<'a> {
+    /// Parse the header and the descriptors it counts. On error, the length
+    /// needed to hold the entire log, as far as can be determined, is
+    /// returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<RuhUsageLogHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| RuhUsageLogHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let len = header_len + header.nruh as usize * size_of::<RuhUsageDesc>();
+        let ruhs = bytes.get(header_len..len).ok_or(len)?;
+        Ok(RuhUsageLog {
+            header,
+            ruhs: <[RuhUsageDesc]>::from_bytes(ruhs).expect("align 1"),
+        })
+    }
+}
+
+/// FDP Statistics (Log Identifier 22h)
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FdpStatsLog {
+    /// Host Bytes with Metadata Written
+    #[loc(0:15)]
+    pub hbmw: u128,
+    /// Media Bytes with Metadata Written
+    #[loc(16:31)]
+    pub mbmw: u128,
+    /// Media Bytes Erased
+    #[loc(32:47)]
+    pub mbe: u128,
+    #[loc(48:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd48: Reserved<16>,
+}
+
+impl TransmuteSafe for FdpStatsLog {}
+
+impl FdpStatsLog {
+    /// Media bytes written per host byte written, or `None` before the host
+    /// has written anything.
+    pub fn write_amp(&self) -> Option<f64> {
+        let (hbmw, mbmw) = (self.hbmw, self.mbmw);
+        if hbmw == 0 {
+            None
+        } else {
+            Some(mbmw as f64 / hbmw as f64)
+        }
+    }
+}
+
+/// FDP Events (Log Identifier 23h), holding either host or controller events
+/// as selected when the log is read
+#[test_structure(size = 4096)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FdpEventsLog {
+    /// Number of FDP Events
+    #[loc(0:3)]
+    pub nevents: u32,
+    #[loc(4:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd4: Reserved<60>,
+    #[loc(64:4095)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub events: [FdpEvent; 63],
+}
+
+impl TransmuteSafe for FdpEventsLog {}
+
+impl FdpEventsLog {
+    /// Events which were reported, oldest first.
+    pub fn events(&self) -> &[FdpEvent] {
+        let nevents = self.nevents as usize;
+        &self.events[..nevents.min(self.events.len())]
+    }
+}
+
+/// FDP Event
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FdpEvent {
+    #[loc(0:0)]
+    pub etype: u8,
+    #[loc(1:1)]
+    pub fdpef: FdpEventFlags,
+    /// Placement Identifier, if `fdpef.piv` is set
+    #[loc(2:3)]
+    pub pid: u16,
+    #[loc(4:11)]
+    pub ets: u64,
+    /// Namespace identifier, if `fdpef.nsidv` is set
+    #[loc(12:15)]
+    pub nsid: u32,
+    /// Event Type Specific data
+    #[loc(16:31)]
+    pub tsd: [u8; 16],
+    /// Reclaim Group Identifier, if `fdpef.lv` is set
+    #[loc(32:33)]
+    pub rgid: u16,
+    /// Reclaim Unit Handle Identifier, if `fdpef.lv` is set
+    #[loc(34:34)]
+    pub ruhid: u8,
+    #[loc(35:39)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd35: Reserved<5>,
+    #[loc(40:63)]
+    pub vs: [u8; 24],
+}
+
+impl TransmuteSafe for FdpEvent {}
+
+impl FdpEvent {
+    pub fn etype(&self) -> Option<FdpEventType> {
+        FromPrimitive::from_u8(self.etype)
+    }
+
+    /// Milliseconds since the Unix epoch, or since the last controller reset
+    /// if the timestamp was never set.
+    pub fn timestamp(&self) -> u64 {
+        self.ets & 0xffff_ffff_ffff
+    }
+
+    /// Type specific data of a Media Reallocated event.
+    pub fn media_realloc(&self) -> Option<&MediaReallocEvent> {
+        match self.etype() {
+            Some(FdpEventType::MediaRealloc) => MediaReallocEvent::from_bytes(&self.tsd[..]).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct FdpEventFlags {
+    /// Placement Identifier Valid
+    pub piv: bool,
+    /// Namespace Identifier Valid
+    pub nsidv: bool,
+    /// Location Valid
+    pub lv: bool,
+    #[skip]
+    __rsvd: B5,
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FdpEventType {
+    // Host events
+    RuNotFullyWritten = 0x00,
+    RuTimeLimitExceeded = 0x01,
+    CtrlResetModifiedRuhs = 0x02,
+    InvalidPid = 0x03,
+    // Controller events
+    MediaRealloc = 0x80,
+    ImplicitlyModifiedRuh = 0x81,
+}
+
+/// Media Reallocated event type specific data
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaReallocEvent {
+    /// Specific Event Flags; bit 0 is set if `lba` is valid
+    #[loc(0:0)]
+    pub sef: u8,
+    #[loc(1:1)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<1>,
+    /// Number of LBAs Moved
+    #[loc(2:3)]
+    pub nlbam: u16,
+    #[loc(4:11)]
+    pub lba: u64,
+    #[loc(12:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd12: Reserved<4>,
+}
+
+impl TransmuteSafe for MediaReallocEvent {}
+
+impl MediaReallocEvent {
+    /// First LBA moved, if reported.
+    pub fn lba(&self) -> Option<u64> {
+        if self.sef & 0x1 != 0 {
+            Some(self.lba)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn fdpa_bits() {
+    let fdpa = Fdpa::from(0x90);
+    assert!(fdpa.fdpcv() && fdpa.fdpvwc());
+    assert_eq!(fdpa.rgif(), 0);
+    let fdpa = Fdpa::from(0x10);
+    assert!(!fdpa.fdpcv() && fdpa.fdpvwc());
+}
+
+#[test]
+fn fdp_config_log_parse() {
+    let mut bytes = [0u8; 16 + 72 + 64];
+    bytes[0] = 1;
+    bytes[16] = 72;
+    bytes[18] = 0x82;
+    bytes[24] = 2;
+    bytes[80] = 0x1;
+    bytes[84] = 0x2;
+    bytes[88 + 2] = 0x80;
+    let log = FdpConfigLog::parse(&bytes[..]).unwrap();
+    let configs: Vec<_> = log
+        .configs()
+        .map(|config| {
+            let ruhts: Vec<_> = config.ruhs.iter().map(RuhDesc::ruht).collect();
+            (config.desc.fdpa.rgif(), ruhts)
+        })
+        .collect();
+    assert_eq!(
+        configs,
+        [
+            (
+                2,
+                vec![
+                    Some(RuhType::InitiallyIsolated),
+                    Some(RuhType::PersistentlyIsolated)
+                ]
+            ),
+            (0, vec![]),
+        ]
+    );
+
+    let desc = log.config(0).unwrap().desc;
+    assert_eq!(desc.pid(1, 3), 0x4003);
+    assert_eq!(desc.split_pid(0xc005), (3, 5));
+    assert_eq!(log.config(1).unwrap().desc.pid(1, 3), 3);
+
+    assert_eq!(FdpConfigLog::parse(&bytes[..100]).err(), Some(152));
+    bytes[0] = 2;
+    assert_eq!(FdpConfigLog::parse(&bytes[..]).err(), Some(216));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn fdp_round_trip() {
+    use crate::util::{pattern, round_trip};
+
+    let mut bytes = pattern(16, 23);
+    bytes[3..4].fill(0);
+    bytes[8..16].fill(0);
+    round_trip::<FdpConfigLogHeader>(&bytes);
+    let mut bytes = pattern(64, 23);
+    bytes[28..64].fill(0);
+    round_trip::<FdpConfigDesc>(&bytes);
+    let mut bytes = pattern(8, 23);
+    bytes[2..8].fill(0);
+    round_trip::<RuhUsageLogHeader>(&bytes);
+    let mut bytes = pattern(16, 23);
+    bytes[1..2].fill(0);
+    bytes[12..16].fill(0);
+    round_trip::<MediaReallocEvent>(&bytes);
+}
@@ -17,24 +17,24 @@
  * <http://www.gnu.org/licenses/>.
  */
 
-use std::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
 
-use crate::{FixedStr, Reserved, TransmuteSafe};
+use crate::{NulStr, Reserved, TransmuteSafe};
 
 use modular_bitfield::prelude::*;
 
 #[test_structure(size = 512)]
-#[repr(C)]
+#[repr(C, packed)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FwSlotLog {
     #[loc(0:0)]
-    #[cfg_attr(feature = "serde", serde(with = "ActiveFwInfoUnpacked"))]
     pub afi: ActiveFwInfo,
     #[loc(1:7)]
     #[cfg_attr(feature = "serde", serde(skip))]
     __rsvd1: Reserved<7>,
     #[loc(8:63)]
-    pub frs: [FixedStr<8>; 7],
+    pub frs: [NulStr<8>; 7],
     #[loc(64:511)]
     #[cfg_attr(feature = "serde", serde(skip))]
     __rsvd64: Reserved<448>,
@@ -43,14 +43,19 @@ pub struct FwSlotLog {
 impl TransmuteSafe for FwSlotLog {}
 
 impl FwSlotLog {
+    #[cfg(feature = "alloc")]
     pub fn get_slot<'a>(&'a self, index: usize) -> Cow<'a, str> {
         self.frs[index].to_string_lossy()
     }
 }
 
 #[bitfield]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "ActiveFwInfoUnpacked", from = "ActiveFwInfoUnpacked")
+)]
 pub struct ActiveFwInfo {
     pub active_slot: B3,
     #[skip]
@@ -62,11 +67,8 @@ pub struct ActiveFwInfo {
 
 #[cfg(feature = "serde")]
 #[derive(serde::Serialize, serde::Deserialize)]
-#[serde(remote = "ActiveFwInfo")]
 struct ActiveFwInfoUnpacked {
-    #[serde(getter = "ActiveFwInfo::active_slot")]
     pub active_slot: u8,
-    #[serde(getter = "ActiveFwInfo::next_active")]
     pub next_active: u8,
 }
 
@@ -78,3 +80,13 @@ impl From<ActiveFwInfoUnpacked> for ActiveFwInfo {
             .with_next_active(unpacked.next_active)
     }
 }
+
+#[cfg(feature = "serde")]
+impl From<ActiveFwInfo> for ActiveFwInfoUnpacked {
+    fn from(packed: ActiveFwInfo) -> Self {
+        ActiveFwInfoUnpacked {
+            active_slot: packed.active_slot(),
+            next_active: packed.next_active(),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::mem::size_of;
+
+use num_traits::FromPrimitive;
+
+use crate::{FromBytes, Reserved, TransmuteSafe};
+
+/// LBA Status Information (Log Identifier 0Eh) header
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LbaStatusLogHeader {
+    #[loc(0:3)]
+    pub lslplen: u32,
+    #[loc(4:7)]
+    pub nlslne: u32,
+    #[loc(8:11)]
+    pub estulb: u32,
+    #[loc(12:13)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd12: Reserved<2>,
+    #[loc(14:15)]
+    pub lsgc: u16,
+}
+
+impl TransmuteSafe for LbaStatusLogHeader {}
+
+/// LBA Status Log Namespace Element, which is followed by `nlrd` LBA Range
+/// Descriptors
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LbaStatusNmspElem {
+    #[loc(0:3)]
+    pub neid: u32,
+    #[loc(4:7)]
+    pub nlrd: u32,
+    #[loc(8:8)]
+    pub ratype: u8,
+    #[loc(9:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd9: Reserved<7>,
+}
+
+impl TransmuteSafe for LbaStatusNmspElem {}
+
+impl LbaStatusNmspElem {
+    /// Action type to pass to Get LBA Status for the element's ranges.
+    pub fn ratype(&self) -> Option<LbaStatusAtype> {
+        FromPrimitive::from_u8(self.ratype)
+    }
+}
+
+/// LBA Range Descriptor
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LbaRangeDesc {
+    #[loc(0:7)]
+    pub rslba: u64,
+    #[loc(8:11)]
+    pub rnlb: u32,
+    #[loc(12:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd12: Reserved<4>,
+}
+
+impl TransmuteSafe for LbaRangeDesc {}
+
+/// Action Type of Get LBA Status, also recommended per namespace by the LBA
+/// Status Information log.
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LbaStatusAtype {
+    /// Scan the range for potentially unrecoverable LBAs which the
+    /// controller isn't yet tracking, then report them.
+    Untracked = 0x10,
+    /// Report the potentially unrecoverable LBAs already tracked.
+    Tracked = 0x11,
+}
+
+/// LBA Status Information log, whose namespace elements vary in length.
+#[derive(Clone, Copy)]
+pub struct LbaStatusLog<'a> {
+    header: &'a LbaStatusLogHeader,
+    elems: &'a [u8],
+}
+
+impl<'a> LbaStatusLog<'a> {
+    /// Parse the header and validate that every namespace element it counts
+    /// is present. On error, the length needed to hold the entire log, as far
+    /// as can be determined, is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<LbaStatusLogHeader>();
+        let header = bytes
+            .get(..header_len)
+            .and_then(|header| LbaStatusLogHeader::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let elems = &bytes[header_len..];
+        let mut len = 0usize;
+        for _ in 0..header.nlslne {
+            let elem_end = len + size_of::<LbaStatusNmspElem>();
+            let elem = elems
+                .get(len..elem_end)
+                .and_then(|elem| LbaStatusNmspElem::from_bytes(elem).ok())
+                .ok_or(header_len + elem_end)?;
+            len = (elem.nlrd as usize)
+                .saturating_mul(size_of::<LbaRangeDesc>())
+                .saturating_add(elem_end);
+            if len > elems.len() {
+                return Err(len.saturating_add(header_len));
+            }
+        }
+        Ok(LbaStatusLog {
+            header,
+            elems: &elems[..len],
+        })
+    }
+
+    pub fn header(&self) -> &'a LbaStatusLogHeader {
+        self.header
+    }
+
+    pub fn nmsps(&self) -> LbaStatusNmsps<'a> {
+        LbaStatusNmsps { elems: self.elems }
+    }
+
+    /// Length of the log, from the start of the header through the last
+    /// namespace element.
+    pub fn len(&self) -> usize {
+        size_of::<LbaStatusLogHeader>() + self.elems.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+}
+
+/// LBA status log namespace element along with its LBA ranges.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LbaStatusNmsp<'a> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub elem: &'a LbaStatusNmspElem,
+    pub ranges: &'a [LbaRangeDesc],
+}
+
+pub struct LbaStatusNmsps<'a> {
+    elems: &'a [u8],
+}
+
+impl<'a> Iterator for LbaStatusNmsps<'a> {
+    type Item = LbaStatusNmsp<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (elem, rest) = self
+            .elems
+            .split_at(size_of::<LbaStatusNmspElem>().min(self.elems.len()));
+        let elem = LbaStatusNmspElem::from_bytes(elem).ok()?;
+        // Lengths were validated when the log was parsed.
+        let (ranges, rest) = rest.split_at(elem.nlrd as usize * size_of::<LbaRangeDesc>());
+        self.elems = rest;
+        Some(LbaStatusNmsp {
+            elem,
+            ranges: <[LbaRangeDesc]>::from_bytes(ranges).expect("align 1"),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LbaStatusLog<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let header = self.header;
+        let (lslplen, nlslne, estulb, lsgc) =
+            (header.lslplen, header.nlslne, header.estulb, header.lsgc);
+        let mut log = serializer.serialize_struct("LbaStatusLog", 5)?;
+        log.serialize_field("lslplen", &lslplen)?;
+        log.serialize_field("nlslne", &nlslne)?;
+        log.serialize_field("estulb", &estulb)?;
+        log.serialize_field("lsgc", &lsgc)?;
+        log.serialize_field("nmsps", &crate::util::SerializeIter(|| self.nmsps()))?;
+        log.end()
+    }
+}
+
+#[test]
+fn lba_status_log_parse() {
+    let mut bytes = [0u8; 16 + 16 + 32 + 16];
+    bytes[0] = 80;
+    bytes[4] = 2;
+    bytes[8] = 24;
+    bytes[14] = 5;
+    bytes[16] = 1;
+    bytes[20] = 2;
+    bytes[24] = 0x11;
+    bytes[32..40].copy_from_slice(&0x1000u64.to_le_bytes());
+    bytes[40] = 8;
+    bytes[48..56].copy_from_slice(&0x8000u64.to_le_bytes());
+    bytes[56] = 16;
+    bytes[64] = 3;
+    bytes[72] = 0x10;
+    let log = LbaStatusLog::parse(&bytes[..]).unwrap();
+    assert_eq!(log.len(), bytes.len());
+    let nmsps: Vec<_> = log
+        .nmsps()
+        .map(|nmsp| {
+            (
+                nmsp.elem.neid,
+                nmsp.elem.ratype(),
+                nmsp.ranges
+                    .iter()
+                    .map(|range| (range.rslba, range.rnlb))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        nmsps,
+        [
+            (
+                1,
+                Some(LbaStatusAtype::Tracked),
+                vec![(0x1000, 8), (0x8000, 16)]
+            ),
+            (3, Some(LbaStatusAtype::Untracked), vec![]),
+        ]
+    );
+
+    assert_eq!(LbaStatusLog::parse(&bytes[..8]).err(), Some(16));
+    assert_eq!(LbaStatusLog::parse(&bytes[..40]).err(), Some(64));
+    bytes[20] = 3;
+    assert_eq!(LbaStatusLog::parse(&bytes[..]).err(), Some(96));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn lba_range_desc_round_trip() {
+    let mut bytes = crate::util::pattern(16, 14);
+    bytes[12..16].fill(0);
+    crate::util::round_trip::<LbaRangeDesc>(&bytes);
+}
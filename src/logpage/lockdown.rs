@@ -0,0 +1,110 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use num_traits::FromPrimitive;
+
+use crate::{Reserved, TransmuteSafe};
+
+/// Command and Feature Lockdown (Log Identifier 14h)
+///
+/// Lists the opcodes or feature identifiers within one scope which are
+/// prohibited, or which support being prohibited, as selected when the log
+/// is read.
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockdownLog {
+    /// Contents of Command and Feature Scope
+    #[loc(0:0)]
+    pub cfs: u8,
+    #[loc(1:2)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<2>,
+    /// Length of the Prohibited or Supported List
+    #[loc(3:3)]
+    pub lsl: u8,
+    /// Prohibited or Supported List, of which the first `lsl` entries are
+    /// used.
+    #[loc(4:511)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub psl: [u8; 508],
+}
+
+impl TransmuteSafe for LockdownLog {}
+
+impl LockdownLog {
+    pub fn scp(&self) -> Option<LockdownScope> {
+        FromPrimitive::from_u8(self.cfs & 0xf)
+    }
+
+    pub fn cntype(&self) -> Option<LockdownContent> {
+        FromPrimitive::from_u8(self.cfs >> 4 & 0x3)
+    }
+
+    /// Opcodes or feature identifiers in the list. Opcode 00h is a valid
+    /// entry, so the list is bounded by `lsl` rather than a terminator.
+    pub fn list(&self) -> impl Iterator<Item = u8> + '_ {
+        self.psl[..(self.lsl as usize).min(self.psl.len())]
+            .iter()
+            .copied()
+    }
+
+    pub fn contains(&self, id: u8) -> bool {
+        self.list().any(|listed| listed == id)
+    }
+}
+
+/// Scope of a command or feature lockdown
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LockdownScope {
+    AdminCmd = 0x0,
+    SetFeatures = 0x2,
+    MiCmd = 0x3,
+    PcieCmd = 0x4,
+}
+
+/// Content Type of the Command and Feature Lockdown log
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LockdownContent {
+    /// Commands or features which may be prohibited.
+    Supported = 0x1,
+    /// Commands or features which are currently prohibited.
+    Prohibited = 0x2,
+}
+
+#[test]
+fn lockdown_list_len() {
+    use crate::FromBytes;
+
+    let mut bytes = [0u8; 512];
+    bytes[3] = 3;
+    bytes[4..8].copy_from_slice(&[0x00, 0x80, 0x00, 0x10]);
+    let log = LockdownLog::from_bytes(&bytes[..]).unwrap();
+    assert_eq!(log.list().collect::<Vec<_>>(), [0x00, 0x80, 0x00]);
+    assert!(log.contains(0x00));
+    assert!(!log.contains(0x10));
+}
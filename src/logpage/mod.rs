@@ -17,9 +17,157 @@
  * <http://www.gnu.org/licenses/>.
  */
 
+mod ana;
+pub use ana::*;
+mod boot_partition;
+pub use boot_partition::*;
+mod capacity;
+pub use capacity::*;
+mod changed_nmsp;
+pub use changed_nmsp::*;
+mod changed_zone;
+pub use changed_zone::*;
+mod cmd_effects;
+pub use cmd_effects::*;
+mod endur_grp;
+pub use endur_grp::*;
 mod err;
 pub use err::*;
+mod event_agg;
+pub use event_agg::*;
+mod fdp;
+pub use fdp::*;
 mod fw_slot;
 pub use fw_slot::*;
+mod lba_status;
+pub use lba_status::*;
+mod lockdown;
+pub use lockdown::*;
+mod persist_event;
+pub use persist_event::*;
+mod pred_lat;
+pub use pred_lat::*;
+mod reservation;
+pub use reservation::*;
+mod rotational;
+pub use rotational::*;
+mod sanitize;
+pub use sanitize::*;
+mod self_test;
+pub use self_test::*;
 mod smart;
 pub use smart::*;
+mod supported;
+pub use supported::*;
+mod telemetry;
+pub use telemetry::*;
+
+use crate::{NmspList, TransmuteSafe};
+
+/// Log Page Identifier
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum LogPageId {
+    SupportedLogPages = 0x00,
+    ErrInfo = 0x01,
+    Smart = 0x02,
+    FwSlot = 0x03,
+    ChangedNmspList = 0x04,
+    CmdEffects = 0x05,
+    DevSelfTest = 0x06,
+    TelemetryHostInit = 0x07,
+    TelemetryCtrlInit = 0x08,
+    EndurGrpInfo = 0x09,
+    PredLat = 0x0a,
+    PredLatEventAggregate = 0x0b,
+    Ana = 0x0c,
+    PersistEvent = 0x0d,
+    LbaStatusInfo = 0x0e,
+    EndurGrpEventAggregate = 0x0f,
+    MediaUnitStatus = 0x10,
+    SuppCapConfigList = 0x11,
+    FidEffects = 0x12,
+    MiCmdEffects = 0x13,
+    Lockdown = 0x14,
+    BootPartition = 0x15,
+    RotationalMedia = 0x16,
+    FdpConfigs = 0x20,
+    RuhUsage = 0x21,
+    FdpStats = 0x22,
+    FdpEvents = 0x23,
+    ReservationNotification = 0x80,
+    SanitizeStatus = 0x81,
+    ChangedZoneList = 0xbf,
+}
+
+/// Fixed size log page which may be read in a single transfer.
+pub trait LogPage: TransmuteSafe {
+    const LID: LogPageId;
+}
+
+impl LogPage for LidEffectsLog {
+    const LID: LogPageId = LogPageId::SupportedLogPages;
+}
+
+impl LogPage for SmartLog {
+    const LID: LogPageId = LogPageId::Smart;
+}
+
+impl LogPage for FwSlotLog {
+    const LID: LogPageId = LogPageId::FwSlot;
+}
+
+impl LogPage for NmspList {
+    const LID: LogPageId = LogPageId::ChangedNmspList;
+}
+
+impl LogPage for CmdEffectsLog {
+    const LID: LogPageId = LogPageId::CmdEffects;
+}
+
+impl LogPage for SelfTestLog {
+    const LID: LogPageId = LogPageId::DevSelfTest;
+}
+
+impl LogPage for EndurGrpLog {
+    const LID: LogPageId = LogPageId::EndurGrpInfo;
+}
+
+impl LogPage for PredLatLog {
+    const LID: LogPageId = LogPageId::PredLat;
+}
+
+impl LogPage for FidEffectsLog {
+    const LID: LogPageId = LogPageId::FidEffects;
+}
+
+impl LogPage for MiCmdEffectsLog {
+    const LID: LogPageId = LogPageId::MiCmdEffects;
+}
+
+impl LogPage for LockdownLog {
+    const LID: LogPageId = LogPageId::Lockdown;
+}
+
+impl LogPage for RotationalMediaLog {
+    const LID: LogPageId = LogPageId::RotationalMedia;
+}
+
+impl LogPage for FdpStatsLog {
+    const LID: LogPageId = LogPageId::FdpStats;
+}
+
+impl LogPage for FdpEventsLog {
+    const LID: LogPageId = LogPageId::FdpEvents;
+}
+
+impl LogPage for ReservationNotificationLog {
+    const LID: LogPageId = LogPageId::ReservationNotification;
+}
+
+impl LogPage for SanitizeLog {
+    const LID: LogPageId = LogPageId::SanitizeStatus;
+}
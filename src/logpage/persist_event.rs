@@ -0,0 +1,672 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::{
+    convert::{TryFrom, TryInto},
+    mem::size_of,
+};
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+use crate::{
+    util::BitArray, Dps, FixedStr, Flbas, FromBytes, Nmic, NulStr, Reserved, SmartLog,
+    TelemetryLog, TransmuteSafe,
+};
+
+/// Persistent Event Log (Log Identifier 0Dh) header
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistEventLogHeader {
+    #[loc(0:0)]
+    pub lid: u8,
+    #[loc(1:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<3>,
+    #[loc(4:7)]
+    pub tnev: u32,
+    #[loc(8:15)]
+    pub tll: u64,
+    #[loc(16:16)]
+    pub rv: u8,
+    #[loc(17:17)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd17: Reserved<1>,
+    #[loc(18:19)]
+    pub lhl: u16,
+    #[loc(20:27)]
+    pub ts: u64,
+    #[loc(28:43)]
+    pub poh: u128,
+    #[loc(44:51)]
+    pub pcc: u64,
+    #[loc(52:53)]
+    pub vid: u16,
+    #[loc(54:55)]
+    pub ssvid: u16,
+    #[loc(56:75)]
+    pub sn: FixedStr<20>,
+    #[loc(76:115)]
+    pub mn: FixedStr<40>,
+    #[loc(116:371)]
+    pub subnqn: NulStr<256>,
+    #[loc(372:373)]
+    pub gen_num: u16,
+    #[loc(374:377)]
+    pub rci: u32,
+    #[loc(378:479)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd378: Reserved<102>,
+    #[loc(480:511)]
+    pub seb: BitArray<32>,
+}
+
+impl TransmuteSafe for PersistEventLogHeader {}
+
+impl PersistEventLogHeader {
+    /// Whether the controller records events of type `etype`.
+    pub fn supports(&self, etype: PersistEventType) -> bool {
+        let seb = self.seb;
+        seb.get(etype as usize).unwrap_or(false)
+    }
+}
+
+/// Persistent Event Log Event Header
+#[test_structure(size = 24)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PersistEventHeader {
+    #[loc(0:0)]
+    pub etype: u8,
+    #[loc(1:1)]
+    pub etype_rev: u8,
+    /// Length of the event header following this field.
+    #[loc(2:2)]
+    pub ehl: u8,
+    #[loc(3:3)]
+    pub ehai: u8,
+    #[loc(4:5)]
+    pub cntlid: u16,
+    #[loc(6:13)]
+    pub ets: u64,
+    #[loc(14:19)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd14: Reserved<6>,
+    #[loc(20:21)]
+    pub vsil: u16,
+    /// Length of the vendor specific information and event data.
+    #[loc(22:23)]
+    pub el: u16,
+}
+
+impl TransmuteSafe for PersistEventHeader {}
+
+impl PersistEventHeader {
+    pub fn etype(&self) -> Option<PersistEventType> {
+        FromPrimitive::from_u8(self.etype)
+    }
+
+    /// Milliseconds since the Unix epoch, or since the last controller reset
+    /// if the timestamp was never set.
+    pub fn timestamp(&self) -> u64 {
+        self.ets & 0xffff_ffff_ffff
+    }
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PersistEventType {
+    Smart = 0x01,
+    FwCommit = 0x02,
+    TimestampChange = 0x03,
+    PwrOnReset = 0x04,
+    NssHwErr = 0x05,
+    ChangeNmsp = 0x06,
+    FormatNvmStart = 0x07,
+    FormatNvmComplete = 0x08,
+    SanitizeStart = 0x09,
+    SanitizeComplete = 0x0a,
+    SetFeature = 0x0b,
+    TelemetryCreated = 0x0c,
+    ThermalExcursion = 0x0d,
+    VndrSpecific = 0xde,
+    TcgDefined = 0xdf,
+}
+
+/// Firmware Commit Event Data
+#[test_structure(size = 22)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FwCommitEvent {
+    #[loc(0:7)]
+    pub old_fr: FixedStr<8>,
+    #[loc(8:15)]
+    pub new_fr: FixedStr<8>,
+    #[loc(16:16)]
+    pub ca: u8,
+    #[loc(17:17)]
+    pub fs: u8,
+    #[loc(18:18)]
+    pub sct: u8,
+    #[loc(19:19)]
+    pub sc: u8,
+    #[loc(20:21)]
+    pub vndr_sc: u16,
+}
+
+impl TransmuteSafe for FwCommitEvent {}
+
+/// Timestamp Change Event Data
+#[test_structure(size = 16)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimestampChangeEvent {
+    #[loc(0:7)]
+    pub prev_ts: u64,
+    #[loc(8:15)]
+    pub ms_since_reset: u64,
+}
+
+impl TransmuteSafe for TimestampChangeEvent {}
+
+/// Power on or Reset Information, one per controller in the subsystem
+#[test_structure(size = 36)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PwrOnResetInfo {
+    #[loc(0:1)]
+    pub cntlid: u16,
+    #[loc(2:2)]
+    pub fw_act: u8,
+    #[loc(3:3)]
+    pub op_in_prog: u8,
+    #[loc(4:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd4: Reserved<12>,
+    #[loc(16:19)]
+    pub ctrl_pwr_cycle: u32,
+    #[loc(20:27)]
+    pub pwr_on_ms: u64,
+    #[loc(28:35)]
+    pub ctrl_ts: u64,
+}
+
+impl TransmuteSafe for PwrOnResetInfo {}
+
+/// NVM Subsystem Hardware Error Event Data, followed by additional hardware
+/// error information
+#[test_structure(size = 4)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NssHwErrEvent {
+    #[loc(0:1)]
+    pub code: u16,
+    #[loc(2:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2: Reserved<2>,
+}
+
+impl TransmuteSafe for NssHwErrEvent {}
+
+/// Change Namespace Event Data
+#[test_structure(size = 48)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangeNmspEvent {
+    #[loc(0:3)]
+    pub nmsp_mgmt_cdw10: u32,
+    #[loc(4:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd4: Reserved<4>,
+    #[loc(8:15)]
+    pub nsze: u64,
+    #[loc(16:23)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd16: Reserved<8>,
+    #[loc(24:31)]
+    pub ncap: u64,
+    #[loc(32:32)]
+    pub flbas: Flbas,
+    #[loc(33:33)]
+    pub dps: Dps,
+    #[loc(34:34)]
+    pub nmic: Nmic,
+    #[loc(35:35)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd35: Reserved<1>,
+    #[loc(36:39)]
+    pub anagrpid: u32,
+    #[loc(40:41)]
+    pub nvmsetid: u16,
+    #[loc(42:43)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd42: Reserved<2>,
+    #[loc(44:47)]
+    pub nsid: u32,
+}
+
+impl TransmuteSafe for ChangeNmspEvent {}
+
+/// Format NVM Start Event Data
+#[test_structure(size = 12)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormatNvmStartEvent {
+    #[loc(0:3)]
+    pub nsid: u32,
+    #[loc(4:4)]
+    pub fna: u8,
+    #[loc(5:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd5: Reserved<3>,
+    #[loc(8:11)]
+    pub cdw10: u32,
+}
+
+impl TransmuteSafe for FormatNvmStartEvent {}
+
+/// Format NVM Completion Event Data
+#[test_structure(size = 12)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FormatNvmCompleteEvent {
+    #[loc(0:3)]
+    pub nsid: u32,
+    #[loc(4:4)]
+    pub smallest_fpi: u8,
+    #[loc(5:5)]
+    pub status: u8,
+    #[loc(6:7)]
+    pub compln_info: u16,
+    #[loc(8:11)]
+    pub status_field: u32,
+}
+
+impl TransmuteSafe for FormatNvmCompleteEvent {}
+
+/// Sanitize Start Event Data
+#[test_structure(size = 12)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SanitizeStartEvent {
+    #[loc(0:3)]
+    pub sanicap: u32,
+    #[loc(4:7)]
+    pub cdw10: u32,
+    #[loc(8:11)]
+    pub cdw11: u32,
+}
+
+impl TransmuteSafe for SanitizeStartEvent {}
+
+/// Sanitize Completion Event Data
+#[test_structure(size = 8)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SanitizeCompleteEvent {
+    #[loc(0:1)]
+    pub sprog: u16,
+    #[loc(2:3)]
+    pub sstat: u16,
+    #[loc(4:5)]
+    pub compln_info: u16,
+    #[loc(6:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd6: Reserved<2>,
+}
+
+impl TransmuteSafe for SanitizeCompleteEvent {}
+
+/// Set Feature Event Layout
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct SetFeatureLayout {
+    pub dw_cnt: B3,
+    pub lccd0: bool,
+    #[skip]
+    __rsvd: B12,
+    pub mb_cnt: u16,
+}
+
+/// Set Feature Event Data: the logged command dwords starting at CDW10, the
+/// completion's Dword 0 if logged, then the command's memory buffer.
+#[derive(Clone, Copy)]
+pub struct SetFeatureEvent<'a> {
+    pub layout: SetFeatureLayout,
+    data: &'a [u8],
+}
+
+impl<'a> SetFeatureEvent<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        let layout = SetFeatureLayout::from_bytes(data.get(..4)?.try_into().unwrap());
+        let len =
+            (layout.dw_cnt() as usize + layout.lccd0() as usize) * 4 + layout.mb_cnt() as usize;
+        let data = data.get(4..4 + len)?;
+        Some(SetFeatureEvent { layout, data })
+    }
+
+    fn dword(&self, idx: usize) -> u32 {
+        u32::from_le_bytes(self.data[idx * 4..idx * 4 + 4].try_into().unwrap())
+    }
+
+    /// Command dwords as logged, starting at CDW10.
+    pub fn cdws(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.layout.dw_cnt() as usize).map(move |idx| self.dword(idx))
+    }
+
+    /// Feature identifier from CDW10, if logged.
+    pub fn fid(&self) -> Option<u8> {
+        self.cdws().next().map(|cdw10| cdw10 as u8)
+    }
+
+    pub fn cqe_dw0(&self) -> Option<u32> {
+        Some(self.dword(self.layout.dw_cnt() as usize)).filter(|_| self.layout.lccd0())
+    }
+
+    pub fn mem_buf(&self) -> &'a [u8] {
+        let start = (self.layout.dw_cnt() as usize + self.layout.lccd0() as usize) * 4;
+        &self.data[start..]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SetFeatureEvent<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut event = serializer.serialize_struct("SetFeatureEvent", 3)?;
+        event.serialize_field("cdws", &crate::util::SerializeIter(|| self.cdws()))?;
+        event.serialize_field("cqe_dw0", &self.cqe_dw0())?;
+        event.serialize_field("mem_buf", self.mem_buf())?;
+        event.end()
+    }
+}
+
+/// Thermal Excursion Event Data
+#[test_structure(size = 2)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThermalExcursionEvent {
+    /// Degrees Celsius over the threshold, or 0 if back under it.
+    #[loc(0:0)]
+    pub over_temp: u8,
+    #[loc(1:1)]
+    pub thresh: u8,
+}
+
+impl TransmuteSafe for ThermalExcursionEvent {}
+
+/// Event data decoded according to its event type. Data which is too short
+/// for its type, or whose type isn't known, is left raw as `Other`.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PersistEventData<'a> {
+    Smart(&'a SmartLog),
+    FwCommit(&'a FwCommitEvent),
+    TimestampChange(&'a TimestampChangeEvent),
+    PwrOnReset {
+        fr: FixedStr<8>,
+        infos: &'a [PwrOnResetInfo],
+    },
+    NssHwErr {
+        event: &'a NssHwErrEvent,
+        add_info: &'a [u8],
+    },
+    ChangeNmsp(&'a ChangeNmspEvent),
+    FormatNvmStart(&'a FormatNvmStartEvent),
+    FormatNvmComplete(&'a FormatNvmCompleteEvent),
+    SanitizeStart(&'a SanitizeStartEvent),
+    SanitizeComplete(&'a SanitizeCompleteEvent),
+    SetFeature(SetFeatureEvent<'a>),
+    /// Header of the Telemetry Host-Initiated log which was created.
+    TelemetryCreated(&'a TelemetryLog),
+    ThermalExcursion(&'a ThermalExcursionEvent),
+    VndrSpecific(&'a [u8]),
+    Other(&'a [u8]),
+}
+
+/// Decode the start of `data` as `T`.
+fn prefix<T: TransmuteSafe>(data: &[u8]) -> Option<&T> {
+    T::from_bytes(data.get(..size_of::<T>())?).ok()
+}
+
+impl<'a> PersistEventData<'a> {
+    fn decode(etype: Option<PersistEventType>, data: &'a [u8]) -> Self {
+        use PersistEventData::*;
+
+        let decoded = match etype {
+            Some(PersistEventType::Smart) => prefix(data).map(Smart),
+            Some(PersistEventType::FwCommit) => prefix(data).map(FwCommit),
+            Some(PersistEventType::TimestampChange) => prefix(data).map(TimestampChange),
+            Some(PersistEventType::PwrOnReset) => data.get(..8).map(|fr| {
+                let infos = &data[8..];
+                let len = infos.len() - infos.len() % size_of::<PwrOnResetInfo>();
+                PwrOnReset {
+                    fr: FixedStr::try_from(fr).unwrap(),
+                    infos: <[PwrOnResetInfo]>::from_bytes(&infos[..len]).unwrap(),
+                }
+            }),
+            Some(PersistEventType::NssHwErr) => prefix(data).map(|event| NssHwErr {
+                event,
+                add_info: &data[size_of::<NssHwErrEvent>()..],
+            }),
+            Some(PersistEventType::ChangeNmsp) => prefix(data).map(ChangeNmsp),
+            Some(PersistEventType::FormatNvmStart) => prefix(data).map(FormatNvmStart),
+            Some(PersistEventType::FormatNvmComplete) => prefix(data).map(FormatNvmComplete),
+            Some(PersistEventType::SanitizeStart) => prefix(data).map(SanitizeStart),
+            Some(PersistEventType::SanitizeComplete) => prefix(data).map(SanitizeComplete),
+            Some(PersistEventType::SetFeature) => SetFeatureEvent::parse(data).map(SetFeature),
+            Some(PersistEventType::TelemetryCreated) => prefix(data).map(TelemetryCreated),
+            Some(PersistEventType::ThermalExcursion) => prefix(data).map(ThermalExcursion),
+            Some(PersistEventType::VndrSpecific) => Some(VndrSpecific(data)),
+            Some(PersistEventType::TcgDefined) | None => None,
+        };
+        decoded.unwrap_or(Other(data))
+    }
+}
+
+/// Event within the Persistent Event Log.
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PersistEvent<'a> {
+    pub header: &'a PersistEventHeader,
+    pub vndr_info: &'a [u8],
+    pub data: PersistEventData<'a>,
+}
+
+/// Persistent Event Log, parsed from the bytes read within a reporting
+/// context.
+#[derive(Clone, Copy)]
+pub struct PersistEventLog<'a> {
+    header: &'a PersistEventLogHeader,
+    events: &'a [u8],
+}
+
+impl<'a> PersistEventLog<'a> {
+    /// Parse the header and find the events it describes. On error, the
+    /// length needed to hold the entire log is returned.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, usize> {
+        let header_len = size_of::<PersistEventLogHeader>();
+        let header: &PersistEventLogHeader = bytes
+            .get(..header_len)
+            .and_then(|header| FromBytes::from_bytes(header).ok())
+            .ok_or(header_len)?;
+        let tll = usize::try_from(header.tll)
+            .unwrap_or(usize::MAX)
+            .max(header_len);
+        let events = bytes.get(header_len..tll).ok_or(tll)?;
+        Ok(PersistEventLog { header, events })
+    }
+
+    pub fn header(&self) -> &'a PersistEventLogHeader {
+        self.header
+    }
+
+    /// Events in the order they were recorded, oldest first. Iteration stops
+    /// early at an event whose lengths run past the end of the log.
+    pub fn events(&self) -> PersistEvents<'a> {
+        PersistEvents {
+            events: self.events,
+            remaining: self.header.tnev,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PersistEventLog<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut log = serializer.serialize_struct("PersistEventLog", 2)?;
+        log.serialize_field("header", self.header)?;
+        log.serialize_field("events", &crate::util::SerializeIter(|| self.events()))?;
+        log.end()
+    }
+}
+
+pub struct PersistEvents<'a> {
+    events: &'a [u8],
+    remaining: u32,
+}
+
+impl<'a> PersistEvents<'a> {
+    fn parse_next(&mut self) -> Option<PersistEvent<'a>> {
+        let header: &PersistEventHeader = prefix(self.events)?;
+        let header_len = (header.ehl as usize + 3).max(size_of::<PersistEventHeader>());
+        let end = header_len + header.el as usize;
+        let body = self.events.get(header_len..end)?;
+        let (vndr_info, data) = body.split_at((header.vsil as usize).min(body.len()));
+        self.events = &self.events[end..];
+        Some(PersistEvent {
+            header,
+            vndr_info,
+            data: PersistEventData::decode(header.etype(), data),
+        })
+    }
+}
+
+impl<'a> Iterator for PersistEvents<'a> {
+    type Item = PersistEvent<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let event = self.parse_next();
+        self.remaining = if event.is_some() {
+            self.remaining - 1
+        } else {
+            0
+        };
+        event
+    }
+}
+
+#[test]
+fn persist_events() {
+    fn event(etype: u8, ets: u64, vndr_info: &[u8], data: &[u8]) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[0] = etype;
+        bytes[2] = 21;
+        bytes[6..14].copy_from_slice(&ets.to_le_bytes());
+        bytes[20..22].copy_from_slice(&(vndr_info.len() as u16).to_le_bytes());
+        bytes[22..24].copy_from_slice(&((vndr_info.len() + data.len()) as u16).to_le_bytes());
+        bytes[24..24 + vndr_info.len()].copy_from_slice(vndr_info);
+        bytes[24 + vndr_info.len()..24 + vndr_info.len() + data.len()].copy_from_slice(data);
+        bytes
+    }
+
+    let mut log = [0u8; 512 + 64 * 3];
+    log[0] = 0x0d;
+    log[4] = 3;
+    let tll = 512 + (24 + 2) + (24 + 2 + 8) + (24 + 13);
+    log[8..16].copy_from_slice(&(tll as u64).to_le_bytes());
+    let mut offset = 512;
+    for (etype, vndr_info, data) in [
+        (0x0du8, &[][..], &[3u8, 70][..]),
+        (0x0b, &[0xaa, 0xbb][..], &[0x08, 0, 0, 0, 0x06, 0, 0, 0][..]),
+        (
+            0x0b,
+            &[][..],
+            &[0x02, 0, 0x01, 0, 0x02, 0, 0, 0, 0, 0, 0, 0, 0xff][..],
+        ),
+    ]
+    .iter()
+    {
+        let len = 24 + vndr_info.len() + data.len();
+        log[offset..offset + len].copy_from_slice(&event(*etype, 1, vndr_info, data)[..len]);
+        offset += len;
+    }
+    assert_eq!(offset, tll);
+
+    let parsed = PersistEventLog::parse(&log[..]).unwrap();
+    assert_eq!(PersistEventLog::parse(&log[..tll - 1]).err(), Some(tll));
+    let events: Vec<_> = parsed.events().collect();
+    assert_eq!(events.len(), 3);
+    match events[0].data {
+        PersistEventData::ThermalExcursion(event) => {
+            assert_eq!((event.over_temp, event.thresh), (3, 70))
+        }
+        _ => panic!("expected thermal excursion"),
+    }
+    assert_eq!(events[1].vndr_info, [0xaa, 0xbb]);
+    match events[1].data {
+        PersistEventData::SetFeature(event) => {
+            assert_eq!(event.fid(), None);
+            assert_eq!(event.cqe_dw0(), Some(6));
+        }
+        _ => panic!("expected set feature"),
+    }
+    match events[2].data {
+        PersistEventData::SetFeature(event) => {
+            assert_eq!(event.layout.dw_cnt(), 2);
+            assert_eq!(event.layout.mb_cnt(), 1);
+            assert_eq!(event.cdws().collect::<Vec<_>>(), [2, 0]);
+            assert_eq!(event.fid(), Some(2));
+            assert_eq!(event.mem_buf(), [0xff]);
+        }
+        _ => panic!("expected set feature"),
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn persist_event_round_trip() {
+    use crate::util::{pattern, round_trip};
+
+    let mut bytes = pattern(24, 13);
+    bytes[14..20].fill(0);
+    round_trip::<PersistEventHeader>(&bytes);
+    let mut bytes = pattern(22, 13);
+    bytes[0..16].iter_mut().for_each(|b| *b = b'!' + *b % 94);
+    round_trip::<FwCommitEvent>(&bytes);
+    let mut bytes = pattern(48, 13);
+    for range in [4..8, 16..24, 35..36, 42..44] {
+        bytes[range].fill(0);
+    }
+    round_trip::<ChangeNmspEvent>(&bytes);
+}
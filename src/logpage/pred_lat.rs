@@ -0,0 +1,120 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+use crate::{EventAggregate, Reserved, TransmuteSafe};
+
+/// Predictable Latency Per NVM Set (Log Identifier 0Ah)
+///
+/// Read for a single NVM Set, whose identifier is passed as the log specific
+/// identifier.
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PredLatLog {
+    #[loc(0:0)]
+    pub status: u8,
+    #[loc(1:1)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<1>,
+    #[loc(2:3)]
+    pub event_type: u16,
+    #[loc(4:31)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd4: Reserved<28>,
+    /// DTWIN Reads Typical, in 4 KiB units
+    #[loc(32:39)]
+    pub dtwin_rt: u64,
+    /// DTWIN Writes Typical, in units of the optimal write size
+    #[loc(40:47)]
+    pub dtwin_wt: u64,
+    /// DTWIN Time Maximum, in milliseconds
+    #[loc(48:55)]
+    pub dtwin_tmax: u64,
+    /// NDWIN Time Minimum High, in milliseconds
+    #[loc(56:63)]
+    pub ndwin_tminh: u64,
+    /// NDWIN Time Minimum Low, in milliseconds
+    #[loc(64:71)]
+    pub ndwin_tminl: u64,
+    #[loc(72:127)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd72: Reserved<56>,
+    /// DTWIN Reads Estimate, in 4 KiB units
+    #[loc(128:135)]
+    pub dtwin_re: u64,
+    /// DTWIN Writes Estimate, in units of the optimal write size
+    #[loc(136:143)]
+    pub dtwin_we: u64,
+    /// DTWIN Time Estimate, in milliseconds
+    #[loc(144:151)]
+    pub dtwin_te: u64,
+    #[loc(152:511)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd152: Reserved<360>,
+}
+
+impl TransmuteSafe for PredLatLog {}
+
+impl PredLatLog {
+    /// Window the NVM Set is currently in, or `None` if predictable latency
+    /// mode is not enabled.
+    pub fn status(&self) -> Option<PredLatWindow> {
+        FromPrimitive::from_u8(self.status & 0x7)
+    }
+
+    pub fn event_type(&self) -> PredLatEventType {
+        PredLatEventType::from(self.event_type)
+    }
+}
+
+/// Deterministic Window (DTWIN) or Non-Deterministic Window (NDWIN)
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PredLatWindow {
+    Dtwin = 0x1,
+    Ndwin = 0x2,
+}
+
+#[bitfield]
+#[repr(u16)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u16", from = "u16"))]
+pub struct PredLatEventType {
+    pub dtwin_reads_warning: bool,
+    pub dtwin_writes_warning: bool,
+    pub dtwin_time_warning: bool,
+    #[skip]
+    __rsvd: B11,
+    /// Autonomous transition to NDWIN because a typical or maximum value was
+    /// exceeded.
+    pub dtwin_exceeded: bool,
+    /// Autonomous transition to NDWIN because of a deterministic excursion.
+    pub deterministic_excursion: bool,
+}
+
+/// Predictable Latency Event Aggregate (Log Identifier 0Bh), listing the NVM
+/// Sets with predictable latency events outstanding.
+pub type PredLatEventAggregate<'a> = EventAggregate<'a>;
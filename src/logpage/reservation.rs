@@ -0,0 +1,72 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use num_traits::FromPrimitive;
+
+use crate::{Reserved, TransmuteSafe};
+
+/// Reservation Notification (Log Identifier 80h)
+#[test_structure(size = 64)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReservationNotificationLog {
+    /// Count of notifications logged since the controller was reset, which
+    /// skips over any that were lost.
+    #[loc(0:7)]
+    pub log_page_count: u64,
+    #[loc(8:8)]
+    pub log_page_type: u8,
+    /// Number of notifications still queued after this one.
+    #[loc(9:9)]
+    pub num_avail_log_pages: u8,
+    #[loc(10:11)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd10: Reserved<2>,
+    #[loc(12:15)]
+    pub nsid: u32,
+    #[loc(16:63)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd16: Reserved<48>,
+}
+
+impl TransmuteSafe for ReservationNotificationLog {}
+
+impl ReservationNotificationLog {
+    /// Kind of notification, or `None` if reserved.
+    pub fn log_page_type(&self) -> Option<ReservationNotificationType> {
+        FromPrimitive::from_u8(self.log_page_type)
+    }
+
+    /// Whether no notification was queued when the log was read.
+    pub fn is_empty(&self) -> bool {
+        self.log_page_type == ReservationNotificationType::Empty as u8
+    }
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReservationNotificationType {
+    Empty = 0x0,
+    RegistrationPreempted = 0x1,
+    ReservationReleased = 0x2,
+    ReservationPreempted = 0x3,
+}
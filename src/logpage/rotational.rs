@@ -0,0 +1,56 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{Reserved, TransmuteSafe};
+
+/// Rotational Media Information (Log Identifier 16h), reported per
+/// endurance group for rotational media.
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RotationalMediaLog {
+    #[loc(0:1)]
+    pub endgid: u16,
+    /// Number of Actuators
+    #[loc(2:3)]
+    pub numa: u16,
+    /// Nominal Rotational Speed in revolutions per minute
+    #[loc(4:5)]
+    pub nrs: u16,
+    #[loc(6:7)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd6: Reserved<2>,
+    /// Spinup Count
+    #[loc(8:11)]
+    pub spinc: u32,
+    /// Failed Spinup Count
+    #[loc(12:15)]
+    pub fspinc: u32,
+    /// Load Count, the number of times the heads were loaded onto the media
+    #[loc(16:19)]
+    pub ldc: u32,
+    /// Failed Load Count
+    #[loc(20:23)]
+    pub fldc: u32,
+    #[loc(24:511)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd24: Reserved<488>,
+}
+
+impl TransmuteSafe for RotationalMediaLog {}
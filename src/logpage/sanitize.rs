@@ -0,0 +1,176 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::time::Duration;
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+use crate::{Reserved, TransmuteSafe};
+
+/// Sanitize Status (Log Identifier 81h)
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SanitizeLog {
+    #[loc(0:1)]
+    pub sprog: u16,
+    #[loc(2:3)]
+    pub sstat: u16,
+    #[loc(4:7)]
+    pub scdw10: u32,
+    /// Estimated seconds for Overwrite, or FFFFFFFFh if not reported. The
+    /// `nd` variants apply when media is modified despite No-Deallocate After
+    /// Sanitize.
+    #[loc(8:11)]
+    pub eto: u32,
+    #[loc(12:15)]
+    pub etbe: u32,
+    #[loc(16:19)]
+    pub etce: u32,
+    #[loc(20:23)]
+    pub etond: u32,
+    #[loc(24:27)]
+    pub etbend: u32,
+    #[loc(28:31)]
+    pub etcend: u32,
+    #[loc(32:511)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd32: Reserved<480>,
+}
+
+impl TransmuteSafe for SanitizeLog {}
+
+impl SanitizeLog {
+    pub fn sstat(&self) -> SanitizeStatus {
+        SanitizeStatus::from(self.sstat)
+    }
+
+    pub fn state(&self) -> Option<SanitizeState> {
+        FromPrimitive::from_u8(self.sstat().status())
+    }
+
+    /// Percent complete of the sanitize operation in progress.
+    pub fn percent(&self) -> u8 {
+        (self.sprog as u32 * 100 / 0x1_0000) as u8
+    }
+
+    /// Sanitize command of the operation in progress or last completed.
+    pub fn sanact(&self) -> Option<SanitizeAction> {
+        FromPrimitive::from_u32(self.scdw10 & 0x7)
+    }
+
+    /// Whether the operation in progress or last completed was started with
+    /// No-Deallocate After Sanitize.
+    pub fn ndas(&self) -> bool {
+        self.scdw10 >> 9 & 1 == 1
+    }
+
+    /// Estimated time for `sanact`, with `nd` selecting the estimate for
+    /// operations started with No-Deallocate After Sanitize.
+    pub fn estimate(&self, sanact: SanitizeAction, nd: bool) -> Option<Duration> {
+        let secs = match (sanact, nd) {
+            (SanitizeAction::Overwrite, false) => self.eto,
+            (SanitizeAction::Overwrite, true) => self.etond,
+            (SanitizeAction::BlockErase, false) => self.etbe,
+            (SanitizeAction::BlockErase, true) => self.etbend,
+            (SanitizeAction::CryptoErase, false) => self.etce,
+            (SanitizeAction::CryptoErase, true) => self.etcend,
+            (SanitizeAction::ExitFailureMode, _) => return None,
+        };
+        Some(Duration::from_secs(secs as u64)).filter(|_| secs != u32::MAX)
+    }
+
+    /// Estimated time until the operation in progress completes, scaling its
+    /// estimate by the fraction remaining.
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.state() != Some(SanitizeState::InProgress) {
+            return None;
+        }
+        let total = self.estimate(self.sanact()?, self.ndas())?;
+        let remaining_ms = total.as_millis() as u64 * (0x1_0000 - self.sprog as u64) / 0x1_0000;
+        Some(Duration::from_millis(remaining_ms))
+    }
+}
+
+#[bitfield]
+#[repr(u16)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u16", from = "u16"))]
+pub struct SanitizeStatus {
+    pub status: B3,
+    /// Overwrite Passes Completed
+    pub opc: B5,
+    /// Global Data Erased
+    pub gde: bool,
+    #[skip]
+    __rsvd: B7,
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SanitizeState {
+    NeverSanitized = 0x0,
+    Completed = 0x1,
+    InProgress = 0x2,
+    Failed = 0x3,
+    /// Completed, with every logical block deallocated despite No-Deallocate
+    /// After Sanitize.
+    CompletedWithDealloc = 0x4,
+}
+
+/// Sanitize Action
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SanitizeAction {
+    ExitFailureMode = 0x1,
+    BlockErase = 0x2,
+    Overwrite = 0x3,
+    CryptoErase = 0x4,
+}
+
+#[test]
+fn sanitize_remaining() {
+    let mut bytes = [0u8; 512];
+    bytes[0..2].copy_from_slice(&0x4000u16.to_le_bytes());
+    bytes[2..4].copy_from_slice(&0x0012u16.to_le_bytes());
+    bytes[4..8].copy_from_slice(&(0x3u32 | 1 << 9).to_le_bytes());
+    bytes[8..12].copy_from_slice(&400u32.to_le_bytes());
+    bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+    bytes[20..24].copy_from_slice(&800u32.to_le_bytes());
+    let log = <SanitizeLog as crate::FromBytes>::from_bytes(&bytes[..]).unwrap();
+    assert_eq!(log.state(), Some(SanitizeState::InProgress));
+    assert_eq!(log.sstat().opc(), 2);
+    assert_eq!(log.percent(), 25);
+    assert_eq!(log.sanact(), Some(SanitizeAction::Overwrite));
+    assert!(log.ndas());
+    assert_eq!(
+        log.estimate(SanitizeAction::Overwrite, false),
+        Some(Duration::from_secs(400))
+    );
+    assert_eq!(log.estimate(SanitizeAction::BlockErase, false), None);
+    assert_eq!(log.remaining(), Some(Duration::from_secs(600)));
+}
@@ -0,0 +1,197 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{Reserved, StatusCode, StatusField, TransmuteSafe};
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+/// Device Self-test (Log Identifier 06h)
+#[test_structure(size = 564)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestLog {
+    #[loc(0:0)]
+    pub curr_op: u8,
+    #[loc(1:1)]
+    pub curr_completion: u8,
+    #[loc(2:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd2: Reserved<2>,
+    #[loc(4:563)]
+    pub results: [SelfTestResult; 20],
+}
+
+impl TransmuteSafe for SelfTestLog {}
+
+impl SelfTestLog {
+    /// The self-test in progress, if any.
+    pub fn curr_op(&self) -> Option<SelfTestCode> {
+        FromPrimitive::from_u8(self.curr_op & 0xf)
+    }
+
+    /// Percent complete of the self-test in progress.
+    pub fn curr_completion(&self) -> u8 {
+        self.curr_completion & 0x7f
+    }
+
+    /// Results which are in use, from most to least recent.
+    pub fn results(&self) -> impl Iterator<Item = &SelfTestResult> {
+        self.results.iter().filter(|result| result.is_used())
+    }
+}
+
+/// Self-test Result Data Structure
+#[test_structure(size = 28)]
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestResult {
+    #[loc(0:0)]
+    pub status: u8,
+    #[loc(1:1)]
+    pub seg_num: u8,
+    #[loc(2:2)]
+    pub valid_diag_info: ValidDiagInfo,
+    #[loc(3:3)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd3: Reserved<1>,
+    #[loc(4:11)]
+    pub pwr_on_hrs: u64,
+    #[loc(12:15)]
+    pub nsid: u32,
+    #[loc(16:23)]
+    pub failing_lba: u64,
+    #[loc(24:24)]
+    pub sct: u8,
+    #[loc(25:25)]
+    pub sc: u8,
+    #[loc(26:27)]
+    pub vndr_specific: u16,
+}
+
+impl TransmuteSafe for SelfTestResult {}
+
+impl SelfTestResult {
+    /// Result of the self-test, or `None` if reserved.
+    pub fn result(&self) -> Option<SelfTestResultCode> {
+        FromPrimitive::from_u8(self.status & 0xf)
+    }
+
+    /// Which self-test the result is for.
+    pub fn code(&self) -> Option<SelfTestCode> {
+        FromPrimitive::from_u8(self.status >> 4)
+    }
+
+    pub fn is_used(&self) -> bool {
+        self.result() != Some(SelfTestResultCode::NotUsed)
+    }
+
+    pub fn nsid(&self) -> Option<u32> {
+        let valid = self.valid_diag_info;
+        Some(self.nsid).filter(|_| valid.nsidv())
+    }
+
+    pub fn failing_lba(&self) -> Option<u64> {
+        let valid = self.valid_diag_info;
+        Some(self.failing_lba).filter(|_| valid.flbav())
+    }
+
+    /// Status of the command which failed the self-test, if reported.
+    pub fn status_code(&self) -> Option<StatusCode> {
+        let valid = self.valid_diag_info;
+        if valid.sctv() && valid.scv() {
+            let raw = (self.sc as u16) << 1 | (self.sct as u16 & 0x7) << 9;
+            Some(StatusField::from_bytes(raw.to_le_bytes()).status_code())
+        } else {
+            None
+        }
+    }
+}
+
+#[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
+pub struct ValidDiagInfo {
+    pub nsidv: bool,
+    pub flbav: bool,
+    pub sctv: bool,
+    pub scv: bool,
+    #[skip]
+    __rsvd: B4,
+}
+
+/// Self-test Code
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SelfTestCode {
+    Short = 0x1,
+    Extended = 0x2,
+    VndrSpecific = 0xe,
+    Abort = 0xf,
+}
+
+#[repr(u8)]
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SelfTestResultCode {
+    Success = 0x0,
+    AbortedBySelfTestCmd = 0x1,
+    AbortedByCtrlReset = 0x2,
+    AbortedByNmspRemoval = 0x3,
+    AbortedByFormatNvm = 0x4,
+    FatalErr = 0x5,
+    UnknownSegmentFailed = 0x6,
+    SegmentsFailed = 0x7,
+    AbortedUnknown = 0x8,
+    AbortedBySanitize = 0x9,
+    NotUsed = 0xf,
+}
+
+#[test]
+fn result_status_code() {
+    use crate::MadIntegrityStatus;
+
+    let mut bytes = [0u8; 28];
+    bytes[0] = 0x27;
+    bytes[1] = 3;
+    bytes[2] = 0x0f;
+    bytes[12..16].copy_from_slice(&1u32.to_le_bytes());
+    bytes[16..24].copy_from_slice(&0x1d4c0u64.to_le_bytes());
+    bytes[24] = 0x2;
+    bytes[25] = 0x81;
+    let result = <SelfTestResult as crate::FromBytes>::from_bytes(&bytes[..]).unwrap();
+    assert_eq!(result.result(), Some(SelfTestResultCode::SegmentsFailed));
+    assert_eq!(result.code(), Some(SelfTestCode::Extended));
+    assert_eq!(result.nsid(), Some(1));
+    assert_eq!(result.failing_lba(), Some(0x1d4c0));
+    assert_eq!(
+        result.status_code(),
+        Some(StatusCode::MadIntegrity(
+            MadIntegrityStatus::UnrecoveredReadErr
+        ))
+    );
+}
@@ -82,17 +82,20 @@ impl SmartLog {
         self.crit_warning > 0
     }
     pub fn crit_warning(&self) -> CriticalWarning {
-        CriticalWarning::from_bytes([self.crit_warning])
+        CriticalWarning::from(self.crit_warning)
     }
     pub fn has_endur_grp_crit_warning(&self) -> bool {
         self.endur_grp_crit_warning > 0
     }
     pub fn endur_grp_crit_warning(&self) -> EndurGrpCritWarning {
-        EndurGrpCritWarning::from_bytes([self.endur_grp_crit_warning])
+        EndurGrpCritWarning::from(self.endur_grp_crit_warning)
     }
 }
 
 #[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+// Serialized as its `bytes` field, the shape it has always had.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CriticalWarning {
     pub spare_cap_below_thresh: bool,
@@ -106,6 +109,10 @@ pub struct CriticalWarning {
 }
 
 #[bitfield]
+#[repr(u8)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u8", from = "u8"))]
 pub struct EndurGrpCritWarning {
     pub spare_cap_below_thresh: bool,
     #[skip]
@@ -115,3 +122,10 @@ pub struct EndurGrpCritWarning {
     #[skip]
     __: B4,
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn crit_warning_serde_shape() {
+    let warning = CriticalWarning::from(0x09);
+    assert_eq!(serde_json::to_string(&warning).unwrap(), r#"{"bytes":[9]}"#);
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use crate::{LogPageId, TransmuteSafe};
+
+use modular_bitfield::prelude::*;
+
+/// Supported Log Pages (Log Identifier 00h)
+#[test_structure(size = 1024)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LidEffectsLog {
+    #[loc(0:1023)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub lids: [LidEffects; 256],
+}
+
+impl TransmuteSafe for LidEffectsLog {}
+
+impl LidEffectsLog {
+    pub fn lid(&self, lid: u8) -> LidEffects {
+        self.lids[lid as usize]
+    }
+
+    pub fn supports(&self, lid: LogPageId) -> bool {
+        self.lid(lid as u8).lsupp()
+    }
+
+    /// Identifiers and effects of each supported log page.
+    pub fn supported(&self) -> impl Iterator<Item = (u8, LidEffects)> + '_ {
+        self.lids
+            .iter()
+            .enumerate()
+            .filter(|(_, effects)| effects.lsupp())
+            .map(|(lid, effects)| (lid as u8, *effects))
+    }
+}
+
+/// LID Supported and Effects
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct LidEffects {
+    pub lsupp: bool,
+    /// Index Offset Supported, where the log page offset counts entries
+    /// rather than bytes.
+    pub ios: bool,
+    #[skip]
+    __rsvd: B14,
+    /// LID Specific Parameter, e.g. the log specific fields supported.
+    pub lidsp: u16,
+}
+
+/// Feature Identifiers Supported and Effects (Log Identifier 12h)
+#[test_structure(size = 1024)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FidEffectsLog {
+    #[loc(0:1023)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub fids: [FidEffects; 256],
+}
+
+impl TransmuteSafe for FidEffectsLog {}
+
+impl FidEffectsLog {
+    pub fn fid(&self, fid: u8) -> FidEffects {
+        self.fids[fid as usize]
+    }
+
+    /// Identifiers and effects of each supported feature.
+    pub fn supported(&self) -> impl Iterator<Item = (u8, FidEffects)> + '_ {
+        self.fids
+            .iter()
+            .enumerate()
+            .filter(|(_, effects)| effects.fsupp())
+            .map(|(fid, effects)| (fid as u8, *effects))
+    }
+}
+
+/// FID Supported and Effects
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct FidEffects {
+    pub fsupp: bool,
+    pub udcc: bool,
+    pub ncc: bool,
+    pub nic: bool,
+    pub ccc: bool,
+    #[skip]
+    __rsvd: B14,
+    pub uss: bool,
+    pub nmsp_scope: bool,
+    pub ctrl_scope: bool,
+    pub nvm_set_scope: bool,
+    pub endur_grp_scope: bool,
+    pub domain_scope: bool,
+    pub nvm_subsys_scope: bool,
+    #[skip]
+    __rsvd: B6,
+}
+
+impl FidEffects {
+    /// Whether namespaces should be rescanned after setting the feature, due
+    /// to a change in namespace capabilities or inventory.
+    pub fn nmsp_rescan_required(&self) -> bool {
+        self.ncc() || self.nic()
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::FromBytes;
+use crate::{Ieee, Reserved, TransmuteSafe};
+
+/// Size of a telemetry block. The header occupies block 0.
+pub const TELEMETRY_BLOCK_SIZE: usize = 512;
+
+/// Telemetry Host-Initiated (Log Identifier 07h) and Telemetry
+/// Controller-Initiated (Log Identifier 08h) header
+#[test_structure(size = 512)]
+#[repr(C, packed)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TelemetryLog {
+    #[loc(0:0)]
+    pub lid: u8,
+    #[loc(1:4)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd1: Reserved<4>,
+    #[loc(5:7)]
+    pub ieee: Ieee,
+    #[loc(8:9)]
+    pub da1_last_block: u16,
+    #[loc(10:11)]
+    pub da2_last_block: u16,
+    #[loc(12:13)]
+    pub da3_last_block: u16,
+    #[loc(14:15)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd14: Reserved<2>,
+    #[loc(16:19)]
+    pub da4_last_block: u32,
+    #[loc(20:380)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    __rsvd20: Reserved<361>,
+    /// Telemetry Host-Initiated Data Generation Number. Reserved in the
+    /// controller-initiated log (08h), whose data is tracked by
+    /// `ctrl_data_gen_num`.
+    #[loc(381:381)]
+    pub gen_num: u8,
+    #[loc(382:382)]
+    pub ctrl_data_avail: u8,
+    #[loc(383:383)]
+    pub ctrl_data_gen_num: u8,
+    #[loc(384:511)]
+    #[cfg_attr(feature = "serde", serde(with = "crate::util::big_array"))]
+    pub reason_id: [u8; 128],
+}
+
+impl TransmuteSafe for TelemetryLog {}
+
+impl TelemetryLog {
+    /// Last block of `area`. Data Area 4 is only reported when enabled
+    /// through the Host Behavior Support feature.
+    pub fn last_block(&self, area: TelemetryDataArea) -> u32 {
+        match area {
+            TelemetryDataArea::One => self.da1_last_block as u32,
+            TelemetryDataArea::Two => self.da2_last_block as u32,
+            TelemetryDataArea::Three => self.da3_last_block as u32,
+            TelemetryDataArea::Four => self.da4_last_block,
+        }
+    }
+
+    /// Byte range of `area` within the log, relative to the start of the
+    /// header. Each area begins where the previous one ended; areas which
+    /// contain no data are empty.
+    pub fn data_area(&self, area: TelemetryDataArea) -> Range<usize> {
+        let start = match area.prev() {
+            Some(prev) => self.data_area(prev).end,
+            None => TELEMETRY_BLOCK_SIZE,
+        };
+        let end = (self.last_block(area) as usize)
+            .saturating_add(1)
+            .saturating_mul(TELEMETRY_BLOCK_SIZE);
+        start..end.max(start)
+    }
+
+    /// Whether controller-initiated telemetry data is available.
+    pub fn ctrl_data_avail(&self) -> bool {
+        self.ctrl_data_avail == 1
+    }
+}
+
+/// Telemetry data areas, in the order they follow the header.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TelemetryDataArea {
+    One = 1,
+    Two = 2,
+    Three = 3,
+    Four = 4,
+}
+
+impl TelemetryDataArea {
+    pub const ALL: [TelemetryDataArea; 4] = [
+        TelemetryDataArea::One,
+        TelemetryDataArea::Two,
+        TelemetryDataArea::Three,
+        TelemetryDataArea::Four,
+    ];
+
+    fn prev(self) -> Option<Self> {
+        match self {
+            TelemetryDataArea::One => None,
+            TelemetryDataArea::Two => Some(TelemetryDataArea::One),
+            TelemetryDataArea::Three => Some(TelemetryDataArea::Two),
+            TelemetryDataArea::Four => Some(TelemetryDataArea::Three),
+        }
+    }
+}
+
+/// Telemetry log retrieved up to some data area, beginning with its header.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct Telemetry {
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "alloc")]
+impl Telemetry {
+    /// Wrap a raw telemetry log, e.g. one saved by `nvme telemetry-log`. On
+    /// error, the length is returned if it does not hold a complete header.
+    pub fn new(bytes: Vec<u8>) -> Result<Self, usize> {
+        if bytes.len() < TELEMETRY_BLOCK_SIZE {
+            Err(bytes.len())
+        } else {
+            Ok(Telemetry { bytes })
+        }
+    }
+
+    pub fn header(&self) -> &TelemetryLog {
+        TelemetryLog::from_bytes(&self.bytes[..TELEMETRY_BLOCK_SIZE])
+            .expect("header is complete and align 1")
+    }
+
+    /// Contents of `area`, or `None` if the log was not retrieved that far.
+    pub fn data_area(&self, area: TelemetryDataArea) -> Option<&[u8]> {
+        self.bytes.get(self.header().data_area(area))
+    }
+
+    /// Data areas which were retrieved, including empty ones.
+    pub fn data_areas(&self) -> impl Iterator<Item = (TelemetryDataArea, &[u8])> {
+        TelemetryDataArea::ALL
+            .iter()
+            .filter_map(move |&area| Some((area, self.data_area(area)?)))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Write the header and each retrieved data area to separate files in
+    /// `dir`: `header.bin` and `data-area-1.bin` through `data-area-4.bin`.
+    #[cfg(feature = "std")]
+    pub fn export(&self, dir: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let dir = dir.as_ref();
+        std::fs::write(dir.join("header.bin"), &self.bytes[..TELEMETRY_BLOCK_SIZE])?;
+        for (area, data) in self.data_areas() {
+            std::fs::write(dir.join(format!("data-area-{}.bin", area as u8)), data)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn telemetry_data_areas() {
+    let mut bytes = [0u8; 512];
+    bytes[8..10].copy_from_slice(&3u16.to_le_bytes());
+    bytes[10..12].copy_from_slice(&3u16.to_le_bytes());
+    bytes[12..14].copy_from_slice(&8u16.to_le_bytes());
+    let log = <TelemetryLog as crate::FromBytes>::from_bytes(&bytes[..]).unwrap();
+    assert_eq!(log.data_area(TelemetryDataArea::One), 512..2048);
+    assert_eq!(log.data_area(TelemetryDataArea::Two), 2048..2048);
+    assert_eq!(log.data_area(TelemetryDataArea::Three), 2048..4608);
+    assert_eq!(log.data_area(TelemetryDataArea::Four), 4608..4608);
+}
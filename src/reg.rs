@@ -0,0 +1,125 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+//! Controller registers, as mapped through the controller's memory BAR.
+
+use modular_bitfield::prelude::*;
+use num_traits::FromPrimitive;
+
+/// Offset of the Boot Partition Information register.
+pub const BPINFO_OFFSET: usize = 0x40;
+/// Offset of the Boot Partition Read Select register.
+pub const BPRSEL_OFFSET: usize = 0x44;
+/// Offset of the Boot Partition Memory Buffer Location register.
+pub const BPMBL_OFFSET: usize = 0x48;
+
+/// Unit of the boot partition size.
+pub const BOOT_PARTITION_UNIT: usize = 128 * 1024;
+/// Unit of the boot partition read size and offset.
+pub const BOOT_PARTITION_READ_UNIT: usize = 4096;
+
+/// Boot Partition Information. Also reported in the Boot Partition log,
+/// where the read status is reserved.
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct Bpinfo {
+    pub bpsz: B15,
+    #[skip]
+    __rsvd: B9,
+    pub brs: B2,
+    #[skip]
+    __rsvd: B5,
+    pub abpid: bool,
+}
+
+impl Bpinfo {
+    /// Size of each boot partition in bytes.
+    pub fn bp_size(&self) -> usize {
+        self.bpsz() as usize * BOOT_PARTITION_UNIT
+    }
+
+    pub fn read_status(&self) -> BootReadStatus {
+        BootReadStatus::from_u8(self.brs()).expect("all 2 bit values are valid")
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, num_derive::FromPrimitive, num_derive::ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BootReadStatus {
+    NoRead = 0x0,
+    InProgress = 0x1,
+    Completed = 0x2,
+    Error = 0x3,
+}
+
+/// Boot Partition Read Select. Writing it starts a read of `bprsz` 4 KiB
+/// units at offset `bprof` of boot partition `bpid` into the buffer at
+/// [`Bpmbl`].
+#[bitfield]
+#[repr(u32)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u32", from = "u32"))]
+pub struct Bprsel {
+    pub bprsz: B10,
+    pub bprof: B20,
+    #[skip]
+    __rsvd: B1,
+    pub bpid: bool,
+}
+
+/// Boot Partition Memory Buffer Location, a 4 KiB aligned host address.
+#[bitfield]
+#[repr(u64)]
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "u64", from = "u64"))]
+pub struct Bpmbl {
+    #[skip]
+    __rsvd: B12,
+    pub bmbba: B52,
+}
+
+impl Bpmbl {
+    pub fn addr(&self) -> u64 {
+        self.bmbba() << 12
+    }
+
+    /// Buffer at `addr`, with the low 12 bits ignored.
+    pub fn from_addr(addr: u64) -> Self {
+        Bpmbl::new().with_bmbba(addr >> 12)
+    }
+}
+
+#[test]
+fn boot_partition_regs() {
+    let bpinfo = Bpinfo::from(0x8200_0004);
+    assert_eq!(bpinfo.bp_size(), 512 * 1024);
+    assert_eq!(bpinfo.read_status(), BootReadStatus::Completed);
+    assert!(bpinfo.abpid());
+
+    let bprsel = Bprsel::new().with_bprsz(2).with_bprof(1).with_bpid(true);
+    assert_eq!(u32::from(bprsel), 0x8000_0402);
+    assert_eq!(Bpmbl::from_addr(0x1234_5fff).addr(), 0x1234_5000);
+}
@@ -25,6 +25,10 @@ use num_traits::{FromPrimitive, ToPrimitive};
 #[bitfield(bits = 16)]
 #[derive(BitfieldSpecifier, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "StatusFieldUnpacked", from = "StatusFieldUnpacked")
+)]
 pub struct StatusField {
     pub phase_tag: bool,
     pub sc: u8,
@@ -36,17 +40,11 @@ pub struct StatusField {
 
 #[cfg(feature = "serde")]
 #[derive(serde::Serialize, serde::Deserialize)]
-#[serde(remote = "StatusField")]
-pub struct StatusFieldUnpacked {
-    #[serde(getter = "StatusField::phase_tag")]
+struct StatusFieldUnpacked {
     pub phase_tag: bool,
-    #[serde(getter = "StatusField::status_code")]
     pub status_code: StatusCode,
-    #[serde(getter = "StatusField::crd")]
     pub crd: u8,
-    #[serde(getter = "StatusField::more")]
     pub more: bool,
-    #[serde(getter = "StatusField::dnr")]
     pub dnr: bool,
 }
 
@@ -64,6 +62,19 @@ impl From<StatusFieldUnpacked> for StatusField {
     }
 }
 
+#[cfg(feature = "serde")]
+impl From<StatusField> for StatusFieldUnpacked {
+    fn from(packed: StatusField) -> Self {
+        StatusFieldUnpacked {
+            phase_tag: packed.phase_tag(),
+            status_code: packed.status_code(),
+            crd: packed.crd(),
+            more: packed.more(),
+            dnr: packed.dnr(),
+        }
+    }
+}
+
 impl StatusField {
     pub fn status_code(&self) -> StatusCode {
         let sc = self.sc();
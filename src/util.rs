@@ -17,27 +17,44 @@
  * <http://www.gnu.org/licenses/>.
  */
 
-use std::{
-    borrow::Cow,
+use core::{
     convert::{TryFrom, TryInto},
-    mem::MaybeUninit,
+    str::Utf8Error,
 };
 
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String, vec::Vec};
+
 pub trait FromBytes {
-    fn from_bytes<'a>(bytes: &'a [u8]) -> Result<&'a Self, usize>;
+    fn from_bytes(bytes: &[u8]) -> Result<&Self, usize>;
+}
+
+// `usize::is_multiple_of` requires Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+fn is_aligned<T>(bytes: &[u8]) -> bool {
+    bytes.as_ptr() as usize % core::mem::align_of::<T>() == 0
+}
+
+/// Inverse of [`FromBytes`], viewing a structure as its raw byte layout.
+pub trait AsBytes {
+    fn as_bytes(&self) -> &[u8];
+    fn as_bytes_mut(&mut self) -> &mut [u8];
 }
 
 /// Marker trait to indicate that struct can safely be transmuted or cast from
 /// bytes without any undefined behavior. This means any contained enums fill
 /// their entire repr space. Also, this most likely means the struct is packed.
+///
+/// Casts from a misaligned buffer are rejected, so implementors should be
+/// packed to be usable with arbitrary buffers.
 pub trait TransmuteSafe {}
 
 impl<T> FromBytes for T
 where
     T: TransmuteSafe + Sized,
 {
-    fn from_bytes<'a>(bytes: &'a [u8]) -> Result<&'a Self, usize> {
-        if bytes.len() == std::mem::size_of::<Self>() {
+    fn from_bytes(bytes: &[u8]) -> Result<&Self, usize> {
+        if bytes.len() == core::mem::size_of::<Self>() && is_aligned::<Self>(bytes) {
             Ok(unsafe { &*(bytes.as_ptr() as *const Self) })
         } else {
             Err(bytes.len())
@@ -49,12 +66,13 @@ impl<T> FromBytes for [T]
 where
     T: TransmuteSafe + Sized,
 {
-    fn from_bytes<'a>(bytes: &'a [u8]) -> Result<&'a Self, usize> {
-        if bytes.len() % std::mem::size_of::<T>() == 0 {
+    #[allow(clippy::manual_is_multiple_of)]
+    fn from_bytes(bytes: &[u8]) -> Result<&Self, usize> {
+        if bytes.len() % core::mem::size_of::<T>() == 0 && is_aligned::<T>(bytes) {
             Ok(unsafe {
-                std::slice::from_raw_parts(
+                core::slice::from_raw_parts(
                     bytes.as_ptr() as *const T,
-                    bytes.len() / std::mem::size_of::<T>(),
+                    bytes.len() / core::mem::size_of::<T>(),
                 )
             })
         } else {
@@ -63,6 +81,49 @@ where
     }
 }
 
+impl<T> AsBytes for T
+where
+    T: TransmuteSafe + Sized,
+{
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self as *mut Self as *mut u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
+
+impl<T> AsBytes for [T]
+where
+    T: TransmuteSafe + Sized,
+{
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(self.as_ptr() as *const u8, core::mem::size_of_val(self))
+        }
+    }
+
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.as_mut_ptr() as *mut u8,
+                core::mem::size_of_val(self),
+            )
+        }
+    }
+}
+
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 pub struct Reserved<const SIZE: usize>([u8; SIZE]);
@@ -73,49 +134,152 @@ impl<const SIZE: usize> Default for Reserved<SIZE> {
     }
 }
 
+/// Fixed width string field as found in identify structures and log pages.
+///
+/// Unused trailing bytes are filled with `PAD` when constructing from a
+/// shorter string. ASCII fields such as the serial number are space padded,
+/// while UTF-8 fields such as the NQN are NUL padded.
+///
+/// Serde preserves every byte: the field is serialized as a string, less
+/// any trailing NUL padding, or as an array of bytes if it is not UTF-8.
 #[repr(transparent)]
 #[derive(Clone, Copy)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-// TODO: Implement serde without copying.
-#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
-pub struct FixedStr<const SIZE: usize>([u8; SIZE]);
+pub struct FixedStr<const SIZE: usize, const PAD: u8 = b' '>([u8; SIZE]);
+
+/// Fixed width string field which is NUL padded.
+pub type NulStr<const SIZE: usize> = FixedStr<SIZE, 0>;
+
+impl<const SIZE: usize, const PAD: u8> FixedStr<SIZE, PAD> {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0[..]
+    }
+
+    /// The string up to the first NUL byte, if any.
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(self.terminated())
+    }
+
+    /// Decode the string up to the first NUL byte, if any.
+    #[cfg(feature = "alloc")]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.terminated())
+    }
 
-impl<const SIZE: usize> FixedStr<SIZE> {
-    pub fn to_string_lossy(&self) -> Cow<str> {
-        String::from_utf8_lossy(&self.0[..])
+    fn terminated(&self) -> &[u8] {
+        let end = self.0.iter().position(|&b| b == 0).unwrap_or(SIZE);
+        &self.0[..end]
+    }
+
+    /// Bytes up to any trailing NUL padding, which construction restores.
+    #[cfg(feature = "serde")]
+    fn unpadded(&self) -> &[u8] {
+        if PAD != 0 {
+            return &self.0[..];
+        }
+        let end = self
+            .0
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |end| end + 1);
+        &self.0[..end]
     }
 }
 
-impl<const SIZE: usize> From<FixedStr<SIZE>> for String {
-    fn from(val: FixedStr<SIZE>) -> Self {
-        val.to_string_lossy().to_string()
+#[cfg(feature = "serde")]
+impl<const SIZE: usize, const PAD: u8> serde::Serialize for FixedStr<SIZE, PAD> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.unpadded();
+        match core::str::from_utf8(bytes) {
+            Ok(s) => serializer.serialize_str(s),
+            Err(_) => serializer.collect_seq(bytes),
+        }
     }
 }
 
-impl<const SIZE: usize> TryFrom<&[u8]> for FixedStr<SIZE> {
+#[cfg(feature = "serde")]
+impl<'de, const SIZE: usize, const PAD: u8> serde::Deserialize<'de> for FixedStr<SIZE, PAD> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, SeqAccess, Visitor};
+
+        struct FixedStrVisitor<const SIZE: usize, const PAD: u8>;
+
+        impl<'de, const SIZE: usize, const PAD: u8> Visitor<'de> for FixedStrVisitor<SIZE, PAD> {
+            type Value = FixedStr<SIZE, PAD>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, "a string or byte array of at most {} bytes", SIZE)
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+                self.visit_bytes(v.as_bytes())
+            }
+
+            fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                FixedStr::try_from(v).map_err(|len| E::invalid_length(len, &self))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut res = FixedStr::default();
+                let mut len = 0;
+                while let Some(b) = seq.next_element()? {
+                    if len == SIZE {
+                        return Err(A::Error::invalid_length(len + 1, &self));
+                    }
+                    res.0[len] = b;
+                    len += 1;
+                }
+                Ok(res)
+            }
+        }
+
+        deserializer.deserialize_any(FixedStrVisitor)
+    }
+}
+
+impl<const SIZE: usize, const PAD: u8> Default for FixedStr<SIZE, PAD> {
+    fn default() -> Self {
+        FixedStr([PAD; SIZE])
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const SIZE: usize, const PAD: u8> From<FixedStr<SIZE, PAD>> for String {
+    fn from(val: FixedStr<SIZE, PAD>) -> Self {
+        val.to_string_lossy().into_owned()
+    }
+}
+
+impl<const SIZE: usize, const PAD: u8> TryFrom<&[u8]> for FixedStr<SIZE, PAD> {
     type Error = usize;
 
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
         if bytes.len() > SIZE {
             Err(bytes.len())
         } else {
-            Ok(FixedStr(unsafe {
-                let mut cpy: [u8; SIZE] = MaybeUninit::uninit().assume_init();
-                cpy[..].clone_from_slice(bytes);
-                cpy
-            }))
+            let mut res = Self::default();
+            res.0[..bytes.len()].copy_from_slice(bytes);
+            Ok(res)
         }
     }
 }
 
-impl<const SIZE: usize> TryFrom<String> for FixedStr<SIZE> {
+impl<const SIZE: usize, const PAD: u8> TryFrom<&str> for FixedStr<SIZE, PAD> {
     type Error = usize;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         value.as_bytes().try_into()
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<const SIZE: usize, const PAD: u8> TryFrom<String> for FixedStr<SIZE, PAD> {
+    type Error = usize;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.as_str().try_into()
+    }
+}
+
 #[repr(transparent)]
 #[derive(Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -142,6 +306,7 @@ impl<const BYTES: usize> BitArray<BYTES> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<const BYTES: usize> TryFrom<Vec<bool>> for BitArray<BYTES> {
     type Error = usize;
 
@@ -158,8 +323,121 @@ impl<const BYTES: usize> TryFrom<Vec<bool>> for BitArray<BYTES> {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<const BYTES: usize> From<BitArray<BYTES>> for Vec<bool> {
     fn from(value: BitArray<BYTES>) -> Self {
         (0..BYTES * 8).filter_map(|idx| value.get(idx)).collect()
     }
 }
+
+/// Serialize the items produced by an iterator as a sequence, for borrowed
+/// views over variable length structures.
+#[cfg(feature = "serde")]
+pub(crate) struct SerializeIter<F>(pub F);
+
+#[cfg(feature = "serde")]
+impl<F, I> serde::Serialize for SerializeIter<F>
+where
+    F: Fn() -> I,
+    I: Iterator,
+    I::Item: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((self.0)())
+    }
+}
+
+/// Serde support for arrays longer than the 32 elements serde handles
+/// natively, used as `#[serde(with = "crate::util::big_array")]`.
+#[cfg(feature = "serde")]
+pub(crate) mod big_array {
+    use alloc::{format, vec::Vec};
+    use core::convert::TryInto;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, T, const N: usize>(array: &[T; N], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        serializer.collect_seq(array.iter())
+    }
+
+    pub fn deserialize<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        let vec = Vec::<T>::deserialize(deserializer)?;
+        let len = vec.len();
+        vec.try_into()
+            .map_err(|_| D::Error::invalid_length(len, &format!("{} elements", N).as_str()))
+    }
+}
+
+/// Deterministic pseudo-random bytes for round trip tests.
+#[cfg(all(test, feature = "serde"))]
+pub(crate) fn pattern(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u8
+        })
+        .collect()
+}
+
+/// Check that `bytes`, with reserved fields zeroed, survive decoding as `T`,
+/// serializing to JSON and deserializing again.
+#[cfg(all(test, feature = "serde"))]
+pub(crate) fn round_trip<T>(bytes: &[u8])
+where
+    T: FromBytes + AsBytes + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let decoded = T::from_bytes(bytes).expect("decode");
+    let json = serde_json::to_string(decoded).expect("serialize");
+    let deserialized: T = serde_json::from_str(json.as_str()).expect("deserialize");
+    assert_eq!(deserialized.as_bytes(), bytes, "{}", json);
+}
+
+#[test]
+fn fixed_str_padding() {
+    let sn = FixedStr::<20>::try_from("S4EWNX0R123456").unwrap();
+    assert_eq!(sn.as_bytes(), b"S4EWNX0R123456      ");
+    assert_eq!(sn.to_str().unwrap(), "S4EWNX0R123456      ");
+
+    let nqn = NulStr::<16>::try_from("nqn.2014-08").unwrap();
+    assert_eq!(nqn.as_bytes(), b"nqn.2014-08\0\0\0\0\0");
+    assert_eq!(nqn.to_str().unwrap(), "nqn.2014-08");
+
+    assert_eq!(NulStr::<8>::default().to_str().unwrap(), "");
+    assert_eq!(FixedStr::<8>::try_from("").unwrap().as_bytes(), b"        ");
+    assert_eq!(
+        FixedStr::<8>::try_from("12345678").unwrap().as_bytes(),
+        b"12345678"
+    );
+    assert_eq!(FixedStr::<8>::try_from("123456789").err(), Some(9));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn fixed_str_serde_lossless() {
+    let check = |bytes: &[u8], json: &str| {
+        let sn = FixedStr::<8>::try_from(bytes).unwrap();
+        assert_eq!(serde_json::to_string(&sn).unwrap(), json);
+        let back: FixedStr<8> = serde_json::from_str(json).unwrap();
+        assert_eq!(back.as_bytes(), bytes);
+    };
+    check(b"SN 1    ", r#""SN 1    ""#);
+    check(
+        &[0; 8],
+        r#""\u0000\u0000\u0000\u0000\u0000\u0000\u0000\u0000""#,
+    );
+    check(b"AB\0CD   ", r#""AB\u0000CD   ""#);
+    check(b"\xffSN     ", "[255,83,78,32,32,32,32,32]");
+
+    let nqn = NulStr::<8>::try_from("nqn").unwrap();
+    assert_eq!(serde_json::to_string(&nqn).unwrap(), r#""nqn""#);
+    assert!(serde_json::from_str::<FixedStr<2>>(r#""abc""#).is_err());
+}
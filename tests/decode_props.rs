@@ -0,0 +1,833 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+//! Properties which must hold for any input, since dumps may come from
+//! untrusted sources: decoding never panics and serializing a decoded
+//! structure then deserializing it reproduces every non-reserved bit.
+
+#![allow(clippy::single_range_in_vec_init)]
+
+use std::ops::Range;
+
+use nvme::*;
+use proptest::prelude::*;
+
+/// Layout of a structure's fields which do not survive serialization.
+struct Layout {
+    size: usize,
+    reserved: &'static [Range<usize>],
+    /// Per-byte masks of the non-reserved bits within unpacked bitfields,
+    /// repeated across the range for arrays of bitfields.
+    bitfields: &'static [(Range<usize>, &'static [u8])],
+}
+
+const PSD_MASK: &[u8] = &[
+    0xff, 0xff, 0x00, 0x03, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x1f, 0x1f, 0x1f, 0x1f,
+    0xff, 0xff, 0xc0, 0x00, 0xff, 0xff, 0xc7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+const ID_CTRL: Layout = Layout {
+    size: 4096,
+    reserved: &[102..111, 134..253, 356..512, 534..536, 544..768, 1024..2048],
+    bitfields: &[(2048..3072, PSD_MASK)],
+};
+
+const ID_NMSP: Layout = Layout {
+    size: 4096,
+    reserved: &[74..92, 96..99, 192..3072],
+    bitfields: &[(128..192, &[0xff, 0xff, 0xff, 0x03])],
+};
+
+const ID_NMSP_INDEP: Layout = Layout {
+    size: 4096,
+    reserved: &[9..10, 15..4096],
+    bitfields: &[],
+};
+
+const SMART_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[7..32, 232..512],
+    bitfields: &[],
+};
+
+const ERR_LOG_ENTRY: Layout = Layout {
+    size: 64,
+    reserved: &[30..32, 42..64],
+    bitfields: &[(14..16, &[0xff, 0x07])],
+};
+
+const FW_SLOT_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[1..8, 64..512],
+    bitfields: &[(0..1, &[0x77])],
+};
+
+const CMD_EFFECTS_LOG: Layout = Layout {
+    size: 4096,
+    reserved: &[2048..4096],
+    bitfields: &[],
+};
+
+const SELF_TEST_LOG: Layout = Layout {
+    size: 564,
+    reserved: &[2..4],
+    bitfields: &[(
+        4..564,
+        &[
+            0xff, 0xff, 0x0f, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        ],
+    )],
+};
+
+impl Layout {
+    /// Bitmask of every bit which must be preserved.
+    fn mask(&self) -> Vec<u8> {
+        let mut mask = vec![0xff; self.size];
+        for range in self.reserved {
+            mask[range.clone()].iter_mut().for_each(|b| *b = 0);
+        }
+        for (range, bits) in self.bitfields {
+            for chunk in mask[range.clone()].chunks_mut(bits.len()) {
+                chunk.iter_mut().zip(bits.iter()).for_each(|(b, m)| *b &= m);
+            }
+        }
+        mask
+    }
+}
+
+fn round_trip<T>(layout: &Layout, bytes: Vec<u8>) -> Result<(), TestCaseError>
+where
+    T: FromBytes + AsBytes + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let decoded = T::from_bytes(bytes.as_slice()).expect("decode");
+    let json = serde_json::to_string(decoded).expect("serialize");
+    let deserialized: T = serde_json::from_str(json.as_str()).expect("deserialize");
+    let encoded = deserialized.as_bytes();
+    for (idx, mask) in layout.mask().into_iter().enumerate() {
+        prop_assert_eq!(
+            encoded[idx] & mask,
+            bytes[idx] & mask,
+            "byte {} of {}",
+            idx,
+            json
+        );
+    }
+    Ok(())
+}
+
+/// Decode arbitrary bytes, including misaligned buffers, and exercise every
+/// accessor which interprets the raw contents.
+fn decode<T>(bytes: &[u8], inspect: impl Fn(&T)) -> Result<(), TestCaseError>
+where
+    T: FromBytes + serde::Serialize,
+{
+    let size = std::mem::size_of::<T>();
+    let mut buf = vec![0u8; bytes.len() + 1];
+    buf[1..].copy_from_slice(bytes);
+    let decoded = T::from_bytes(&buf[1..]);
+    prop_assert_eq!(decoded.is_ok(), bytes.len() == size);
+    if let Ok(decoded) = decoded {
+        inspect(decoded);
+        serde_json::to_string(decoded).expect("serialize");
+    }
+    Ok(())
+}
+
+fn sized(size: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), size)
+}
+
+fn around(size: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![sized(size), prop::collection::vec(any::<u8>(), 0..size * 2)]
+}
+
+const NMSP_LIST: Layout = Layout {
+    size: 4096,
+    reserved: &[],
+    bitfields: &[],
+};
+
+const ANA_LOG_HEADER: Layout = Layout {
+    size: 16,
+    reserved: &[10..16],
+    bitfields: &[],
+};
+
+const ANA_GRP_DESC: Layout = Layout {
+    size: 32,
+    reserved: &[17..32],
+    bitfields: &[],
+};
+
+const PERSIST_EVENT_LOG_HEADER: Layout = Layout {
+    size: 512,
+    reserved: &[1..4, 17..18, 378..480],
+    bitfields: &[],
+};
+
+const ENDUR_GRP_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[2..3, 8..32, 192..512],
+    bitfields: &[],
+};
+
+const PRED_LAT_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[1..2, 4..32, 72..128, 152..512],
+    bitfields: &[],
+};
+
+const LBA_STATUS_LOG_HEADER: Layout = Layout {
+    size: 16,
+    reserved: &[12..14],
+    bitfields: &[],
+};
+
+const LBA_STATUS_NMSP_ELEM: Layout = Layout {
+    size: 16,
+    reserved: &[9..16],
+    bitfields: &[],
+};
+
+const LBA_STATUS_DESC: Layout = Layout {
+    size: 16,
+    reserved: &[12..13, 14..16],
+    bitfields: &[],
+};
+
+const SANITIZE_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[32..512],
+    bitfields: &[],
+};
+
+const RESERVATION_NOTIFICATION_LOG: Layout = Layout {
+    size: 64,
+    reserved: &[10..12, 16..64],
+    bitfields: &[],
+};
+
+const LID_EFFECTS_LOG: Layout = Layout {
+    size: 1024,
+    reserved: &[],
+    bitfields: &[],
+};
+
+const FID_EFFECTS_LOG: Layout = Layout {
+    size: 1024,
+    reserved: &[],
+    bitfields: &[],
+};
+
+const MI_CMD_EFFECTS_LOG: Layout = Layout {
+    size: 4096,
+    reserved: &[1024..4096],
+    bitfields: &[],
+};
+
+const BOOT_PARTITION_LOG: Layout = Layout {
+    size: 16,
+    reserved: &[1..4, 8..16],
+    bitfields: &[(4..8, &[0xff, 0x7f, 0x00, 0x83])],
+};
+
+const FDP_STATS_LOG: Layout = Layout {
+    size: 64,
+    reserved: &[48..64],
+    bitfields: &[],
+};
+
+/// Every byte of an FDP event except its reserved bytes 39:35.
+const FDP_EVENT_MASK: &[u8] = &[
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+const FDP_EVENTS_LOG: Layout = Layout {
+    size: 4096,
+    reserved: &[4..64],
+    bitfields: &[(64..4096, FDP_EVENT_MASK)],
+};
+
+const CHANGED_ZONE_LIST: Layout = Layout {
+    size: 4096,
+    reserved: &[2..8],
+    bitfields: &[],
+};
+
+const LOCKDOWN_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[1..3],
+    bitfields: &[],
+};
+
+const ROTATIONAL_MEDIA_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[6..8, 24..512],
+    bitfields: &[],
+};
+
+const TELEMETRY_LOG: Layout = Layout {
+    size: 512,
+    reserved: &[1..5, 14..16, 20..381],
+    bitfields: &[],
+};
+
+proptest! {
+    #[test]
+    fn decode_id_ctrl(bytes in around(ID_CTRL.size)) {
+        decode::<IdCtrl>(bytes.as_slice(), |id_ctrl| {
+            id_ctrl.cntrltype();
+            let sanicap = id_ctrl.sanicap;
+            sanicap.nodmmas();
+            let sgls = id_ctrl.sgls;
+            sgls.support();
+        })?;
+    }
+
+    #[test]
+    fn decode_id_nmsp(bytes in around(ID_NMSP.size)) {
+        decode::<IdNmsp>(bytes.as_slice(), |id_nmsp| {
+            id_nmsp.current_lbaf();
+        })?;
+    }
+
+    #[test]
+    fn decode_id_nmsp_indep(bytes in around(ID_NMSP_INDEP.size)) {
+        decode::<IdNmspIndep>(bytes.as_slice(), |id| {
+            id.nsfeat.rotational();
+            id.nstat.nrdy();
+        })?;
+    }
+
+    #[test]
+    fn decode_smart_log(bytes in around(SMART_LOG.size)) {
+        decode::<SmartLog>(bytes.as_slice(), |smart| {
+            smart.crit_warning();
+            smart.endur_grp_crit_warning();
+        })?;
+    }
+
+    #[test]
+    fn decode_err_log_entry(bytes in around(ERR_LOG_ENTRY.size)) {
+        decode::<ErrLogEntry>(bytes.as_slice(), |entry| {
+            let status_field = entry.status_field;
+            status_field.status_code();
+        })?;
+    }
+
+    #[test]
+    fn decode_err_log(bytes in prop::collection::vec(any::<u8>(), 0..64 * 8)) {
+        let errs = <[ErrLogEntry]>::from_bytes(bytes.as_slice());
+        prop_assert_eq!(errs.is_ok(), bytes.len() % 64 == 0);
+        if let Ok(errs) = errs {
+            prop_assert_eq!(errs.len(), bytes.len() / 64);
+            serde_json::to_string(errs).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_fw_slot_log(bytes in around(FW_SLOT_LOG.size)) {
+        decode::<FwSlotLog>(bytes.as_slice(), |fw_log| {
+            (0..7).for_each(|slot| {
+                fw_log.get_slot(slot);
+            });
+        })?;
+    }
+
+    #[test]
+    fn decode_cmd_effects_log(bytes in around(CMD_EFFECTS_LOG.size)) {
+        decode::<CmdEffectsLog>(bytes.as_slice(), |log| {
+            log.supported_admin().for_each(|(_, effects)| {
+                effects.cse();
+            });
+            log.supported_io().for_each(|(_, effects)| {
+                effects.cse();
+            });
+        })?;
+    }
+
+    #[test]
+    fn decode_self_test_log(bytes in around(SELF_TEST_LOG.size)) {
+        decode::<SelfTestLog>(bytes.as_slice(), |log| {
+            log.curr_op();
+            log.curr_completion();
+            log.results().for_each(|result| {
+                result.result();
+                result.code();
+                result.nsid();
+                result.failing_lba();
+                result.status_code();
+            });
+        })?;
+    }
+
+    #[test]
+    fn decode_nmsp_list(bytes in around(NMSP_LIST.size)) {
+        decode::<NmspList>(bytes.as_slice(), |list| {
+            list.nsids().count();
+            list.overflowed();
+            list.is_full();
+        })?;
+    }
+
+    #[test]
+    fn decode_ana_log(bytes in prop::collection::vec(any::<u8>(), 0..1024)) {
+        decode::<AnaLogHeader>(bytes.as_slice(), |_| ())?;
+        decode::<AnaGrpDesc>(bytes.as_slice(), |desc| {
+            desc.state();
+        })?;
+        if let Ok(log) = AnaLog::parse(bytes.as_slice()) {
+            prop_assert!(log.len() <= bytes.len());
+            for group in log.groups() {
+                group.desc.state();
+                group.nsids().count();
+            }
+            serde_json::to_string(&log).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_persist_event_log(
+        header in sized(PERSIST_EVENT_LOG_HEADER.size),
+        events in prop::collection::vec(any::<u8>(), 0..2048),
+    ) {
+        decode::<PersistEventLogHeader>(header.as_slice(), |header| {
+            header.supports(PersistEventType::Smart);
+        })?;
+        let mut bytes = header;
+        bytes[8..16].copy_from_slice(&(512 + events.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&events);
+        let log = PersistEventLog::parse(bytes.as_slice()).expect("complete log");
+        for event in log.events() {
+            event.header.etype();
+            event.header.timestamp();
+            if let PersistEventData::SetFeature(event) = event.data {
+                event.cdws().count();
+                event.fid();
+                event.cqe_dw0();
+                event.mem_buf();
+            }
+        }
+        serde_json::to_string(&log).expect("serialize");
+    }
+
+    #[test]
+    fn decode_endur_grp_log(bytes in around(ENDUR_GRP_LOG.size)) {
+        decode::<EndurGrpLog>(bytes.as_slice(), |log| {
+            log.has_crit_warning();
+            log.crit_warning();
+        })?;
+    }
+
+    #[test]
+    fn decode_pred_lat_log(bytes in around(PRED_LAT_LOG.size)) {
+        decode::<PredLatLog>(bytes.as_slice(), |log| {
+            log.status();
+            log.event_type();
+        })?;
+    }
+
+    #[test]
+    fn decode_event_aggregate(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+        if let Ok(log) = EventAggregate::parse(bytes.as_slice()) {
+            prop_assert!(log.len() <= bytes.len());
+            prop_assert_eq!(log.ids().count() * 2 + 8, log.len());
+            prop_assert!(log.len() <= log.full_len());
+            serde_json::to_string(&log).expect("serialize");
+        } else {
+            prop_assert!(bytes.len() < 8);
+        }
+    }
+
+    #[test]
+    fn decode_lba_status_log(bytes in prop::collection::vec(any::<u8>(), 0..1024)) {
+        decode::<LbaStatusLogHeader>(bytes.as_slice(), |_| ())?;
+        decode::<LbaStatusNmspElem>(bytes.as_slice(), |elem| {
+            elem.ratype();
+        })?;
+        if let Ok(log) = LbaStatusLog::parse(bytes.as_slice()) {
+            prop_assert!(log.len() <= bytes.len());
+            for nmsp in log.nmsps() {
+                nmsp.elem.ratype();
+                prop_assert_eq!(nmsp.ranges.len(), nmsp.elem.nlrd as usize);
+            }
+            serde_json::to_string(&log).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_lba_status_list(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        if let Ok(list) = LbaStatusList::parse(bytes.as_slice()) {
+            prop_assert!(8 + list.descs.len() * 16 <= bytes.len());
+            prop_assert!(list.descs.len() <= list.header.nlsd as usize);
+            list.header.cmpc();
+            list.next_slba();
+            serde_json::to_string(&list).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_fdp_config_log(bytes in prop::collection::vec(any::<u8>(), 0..1024)) {
+        decode::<FdpConfigLogHeader>(bytes.as_slice(), |_| ())?;
+        if let Ok(log) = FdpConfigLog::parse(bytes.as_slice()) {
+            for config in log.configs() {
+                prop_assert_eq!(config.ruhs.len(), config.desc.nruh as usize);
+                config.ruhs.iter().for_each(|ruh| {
+                    ruh.ruht();
+                });
+                let (rgid, phndl) = config.desc.split_pid(0xffff);
+                config.desc.pid(rgid, phndl);
+            }
+            serde_json::to_string(&log).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_ruh_usage_log(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        if let Ok(log) = RuhUsageLog::parse(bytes.as_slice()) {
+            prop_assert_eq!(log.ruhs.len(), log.header.nruh as usize);
+            log.ruhs.iter().for_each(|ruh| {
+                ruh.ruha();
+            });
+            serde_json::to_string(&log).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_ruh_status(bytes in prop::collection::vec(any::<u8>(), 0..256)) {
+        if let Ok(status) = RuhStatus::parse(bytes.as_slice()) {
+            prop_assert!(16 + status.descs.len() * 32 <= bytes.len());
+            prop_assert!(status.descs.len() <= status.header.nruhsd as usize);
+            status.is_complete();
+            serde_json::to_string(&status).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_zone_report(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        if let Ok(report) = ZoneReport::parse(bytes.as_slice()) {
+            prop_assert!(64 + report.descs.len() * 64 <= bytes.len());
+            prop_assert!(report.descs.len() as u64 <= report.header.nz);
+            for desc in report.descs {
+                desc.ztype();
+                desc.state();
+                prop_assert!(report.zone(desc.zslba).is_some());
+            }
+            report.is_complete();
+            serde_json::to_string(&report).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_media_unit_status(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        decode::<MediaUnitStatusHeader>(bytes.as_slice(), |_| ())?;
+        if let Ok(log) = MediaUnitStatusLog::parse(bytes.as_slice()) {
+            for unit in log.units() {
+                prop_assert!(unit.desc.desc_len() <= bytes.len());
+                prop_assert_eq!(unit.chanids().count(), unit.desc.mucs as usize);
+            }
+            serde_json::to_string(&log).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_supp_cap_config_list(bytes in prop::collection::vec(any::<u8>(), 0..1024)) {
+        decode::<CapConfigListHeader>(bytes.as_slice(), |_| ())?;
+        if let Ok(list) = SuppCapConfigList::parse(bytes.as_slice()) {
+            for config in list.configs() {
+                config.total_cap();
+                for eg in config.endur_grps() {
+                    prop_assert_eq!(eg.nvmsetids().count(), eg.desc.egsets as usize);
+                    for chan in eg.channels() {
+                        prop_assert_eq!(chan.mus.len(), chan.desc.chmus as usize);
+                    }
+                }
+            }
+            serde_json::to_string(&list).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_sanitize_log(bytes in around(SANITIZE_LOG.size)) {
+        decode::<SanitizeLog>(bytes.as_slice(), |log| {
+            log.sstat();
+            log.state();
+            assert!(log.percent() <= 100);
+            log.sanact();
+            log.remaining();
+        })?;
+    }
+
+    #[test]
+    fn decode_reservation_notification_log(bytes in around(RESERVATION_NOTIFICATION_LOG.size)) {
+        decode::<ReservationNotificationLog>(bytes.as_slice(), |log| {
+            log.log_page_type();
+            log.is_empty();
+        })?;
+    }
+
+    #[test]
+    fn decode_reservation_status(bytes in prop::collection::vec(any::<u8>(), 0..512), eds in any::<bool>()) {
+        if let Ok(status) = ReservationStatus::parse(bytes.as_slice(), eds) {
+            status.header().rtype();
+            let regctl = status.header().regctl as usize;
+            prop_assert_eq!(status.registrants().count(), regctl);
+            status.holder();
+            serde_json::to_string(&status).expect("serialize");
+        }
+    }
+
+    #[test]
+    fn decode_lid_effects_log(bytes in around(LID_EFFECTS_LOG.size)) {
+        decode::<LidEffectsLog>(bytes.as_slice(), |log| {
+            log.supported().for_each(|(_, effects)| {
+                effects.lidsp();
+            });
+        })?;
+    }
+
+    #[test]
+    fn decode_fid_effects_log(bytes in around(FID_EFFECTS_LOG.size)) {
+        decode::<FidEffectsLog>(bytes.as_slice(), |log| {
+            log.supported().for_each(|(_, effects)| {
+                effects.nmsp_rescan_required();
+            });
+        })?;
+    }
+
+    #[test]
+    fn decode_mi_cmd_effects_log(bytes in around(MI_CMD_EFFECTS_LOG.size)) {
+        decode::<MiCmdEffectsLog>(bytes.as_slice(), |log| {
+            log.supported().for_each(|(_, effects)| {
+                effects.has_effects();
+            });
+        })?;
+    }
+
+    #[test]
+    fn decode_boot_partition_log(bytes in around(BOOT_PARTITION_LOG.size)) {
+        decode::<BootPartitionLog>(bytes.as_slice(), |log| {
+            assert!(log.full_len() >= 16);
+            log.bpinfo.read_status();
+        })?;
+    }
+
+    #[test]
+    fn decode_fdp_stats_log(bytes in around(FDP_STATS_LOG.size)) {
+        decode::<FdpStatsLog>(bytes.as_slice(), |log| {
+            log.write_amp();
+        })?;
+    }
+
+    #[test]
+    fn decode_fdp_events_log(bytes in around(FDP_EVENTS_LOG.size)) {
+        decode::<FdpEventsLog>(bytes.as_slice(), |log| {
+            assert!(log.events().len() <= 63);
+            for event in log.events() {
+                event.etype();
+                event.timestamp();
+                if let Some(realloc) = event.media_realloc() {
+                    realloc.lba();
+                }
+            }
+        })?;
+    }
+
+    #[test]
+    fn decode_changed_zone_list(bytes in around(CHANGED_ZONE_LIST.size)) {
+        decode::<ChangedZoneList>(bytes.as_slice(), |list| {
+            let count = list.zids().count();
+            assert!(count <= ChangedZoneList::CAPACITY);
+            assert!(!list.overflowed() || count == 0);
+        })?;
+    }
+
+    #[test]
+    fn decode_lockdown_log(bytes in around(LOCKDOWN_LOG.size)) {
+        decode::<LockdownLog>(bytes.as_slice(), |log| {
+            log.scp();
+            log.cntype();
+            assert!(log.list().all(|id| log.contains(id)));
+        })?;
+    }
+
+    #[test]
+    fn decode_rotational_media_log(bytes in around(ROTATIONAL_MEDIA_LOG.size)) {
+        decode::<RotationalMediaLog>(bytes.as_slice(), |_| {})?;
+    }
+
+    #[test]
+    fn decode_telemetry_log(bytes in around(TELEMETRY_LOG.size)) {
+        decode::<TelemetryLog>(bytes.as_slice(), |log| {
+            log.ctrl_data_avail();
+            for area in TelemetryDataArea::ALL.iter() {
+                log.data_area(*area);
+            }
+        })?;
+    }
+
+    #[test]
+    fn round_trip_id_ctrl(bytes in sized(ID_CTRL.size)) {
+        round_trip::<IdCtrl>(&ID_CTRL, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_id_nmsp(bytes in sized(ID_NMSP.size)) {
+        round_trip::<IdNmsp>(&ID_NMSP, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_id_nmsp_indep(bytes in sized(ID_NMSP_INDEP.size)) {
+        round_trip::<IdNmspIndep>(&ID_NMSP_INDEP, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_smart_log(bytes in sized(SMART_LOG.size)) {
+        round_trip::<SmartLog>(&SMART_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_err_log_entry(bytes in sized(ERR_LOG_ENTRY.size)) {
+        round_trip::<ErrLogEntry>(&ERR_LOG_ENTRY, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_fw_slot_log(bytes in sized(FW_SLOT_LOG.size)) {
+        round_trip::<FwSlotLog>(&FW_SLOT_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_cmd_effects_log(bytes in sized(CMD_EFFECTS_LOG.size)) {
+        round_trip::<CmdEffectsLog>(&CMD_EFFECTS_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_self_test_log(bytes in sized(SELF_TEST_LOG.size)) {
+        round_trip::<SelfTestLog>(&SELF_TEST_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_ana_log_header(bytes in sized(ANA_LOG_HEADER.size)) {
+        round_trip::<AnaLogHeader>(&ANA_LOG_HEADER, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_ana_grp_desc(bytes in sized(ANA_GRP_DESC.size)) {
+        round_trip::<AnaGrpDesc>(&ANA_GRP_DESC, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_persist_event_log_header(bytes in sized(PERSIST_EVENT_LOG_HEADER.size)) {
+        round_trip::<PersistEventLogHeader>(&PERSIST_EVENT_LOG_HEADER, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_endur_grp_log(bytes in sized(ENDUR_GRP_LOG.size)) {
+        round_trip::<EndurGrpLog>(&ENDUR_GRP_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_pred_lat_log(bytes in sized(PRED_LAT_LOG.size)) {
+        round_trip::<PredLatLog>(&PRED_LAT_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_lba_status_log_header(bytes in sized(LBA_STATUS_LOG_HEADER.size)) {
+        round_trip::<LbaStatusLogHeader>(&LBA_STATUS_LOG_HEADER, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_lba_status_nmsp_elem(bytes in sized(LBA_STATUS_NMSP_ELEM.size)) {
+        round_trip::<LbaStatusNmspElem>(&LBA_STATUS_NMSP_ELEM, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_lba_status_desc(bytes in sized(LBA_STATUS_DESC.size)) {
+        round_trip::<LbaStatusDesc>(&LBA_STATUS_DESC, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_sanitize_log(bytes in sized(SANITIZE_LOG.size)) {
+        round_trip::<SanitizeLog>(&SANITIZE_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_reservation_notification_log(bytes in sized(RESERVATION_NOTIFICATION_LOG.size)) {
+        round_trip::<ReservationNotificationLog>(&RESERVATION_NOTIFICATION_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_lid_effects_log(bytes in sized(LID_EFFECTS_LOG.size)) {
+        round_trip::<LidEffectsLog>(&LID_EFFECTS_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_fid_effects_log(bytes in sized(FID_EFFECTS_LOG.size)) {
+        round_trip::<FidEffectsLog>(&FID_EFFECTS_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_mi_cmd_effects_log(bytes in sized(MI_CMD_EFFECTS_LOG.size)) {
+        round_trip::<MiCmdEffectsLog>(&MI_CMD_EFFECTS_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_boot_partition_log(bytes in sized(BOOT_PARTITION_LOG.size)) {
+        round_trip::<BootPartitionLog>(&BOOT_PARTITION_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_fdp_stats_log(bytes in sized(FDP_STATS_LOG.size)) {
+        round_trip::<FdpStatsLog>(&FDP_STATS_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_fdp_events_log(bytes in sized(FDP_EVENTS_LOG.size)) {
+        round_trip::<FdpEventsLog>(&FDP_EVENTS_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_changed_zone_list(bytes in sized(CHANGED_ZONE_LIST.size)) {
+        round_trip::<ChangedZoneList>(&CHANGED_ZONE_LIST, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_lockdown_log(bytes in sized(LOCKDOWN_LOG.size)) {
+        round_trip::<LockdownLog>(&LOCKDOWN_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_rotational_media_log(bytes in sized(ROTATIONAL_MEDIA_LOG.size)) {
+        round_trip::<RotationalMediaLog>(&ROTATIONAL_MEDIA_LOG, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_nmsp_list(bytes in sized(NMSP_LIST.size)) {
+        round_trip::<NmspList>(&NMSP_LIST, bytes)?;
+    }
+
+    #[test]
+    fn round_trip_telemetry_log(bytes in sized(TELEMETRY_LOG.size)) {
+        round_trip::<TelemetryLog>(&TELEMETRY_LOG, bytes)?;
+    }
+}
@@ -0,0 +1,194 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+//! Decodes the fixture corpus and compares it against expected JSON.
+//!
+//! The corpus under `tests/fixtures/synthetic` was built by hand and its
+//! expected `.json` files were blessed from these decoders, so on their own
+//! they only catch regressions. Two checks do not depend on the
+//! decoders: `SPEC_FIELDS` reads fields straight from their specified byte
+//! offsets, and a capture's `.nvme-cli.json`, if present, is the output of
+//! `nvme <cmd> -o json` for the same device.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use nvme::*;
+
+/// Set to regenerate the expected JSON from the current decoders.
+const BLESS_VAR: &str = "NVME_BLESS";
+
+/// How a field is laid out in the raw dump.
+#[derive(Clone, Copy)]
+enum Raw {
+    /// Little endian unsigned integer.
+    Int,
+    /// String of every byte, such as the space padded `sn`.
+    Str,
+    /// String up to its NUL padding.
+    NulStr,
+}
+
+/// Fields checked against their byte offsets in the NVMe specification:
+/// fixture stem, JSON pointer into the decoded value, offset and layout.
+const SPEC_FIELDS: &[(&str, &str, std::ops::Range<usize>, Raw)] = &[
+    ("id-ctrl", "/vid", 0..2, Raw::Int),
+    ("id-ctrl", "/ssvid", 2..4, Raw::Int),
+    ("id-ctrl", "/sn", 4..24, Raw::Str),
+    ("id-ctrl", "/mn", 24..64, Raw::Str),
+    ("id-ctrl", "/fr", 64..72, Raw::Str),
+    ("id-ctrl", "/ver", 80..84, Raw::Int),
+    ("id-ctrl", "/nn", 516..520, Raw::Int),
+    ("id-ns", "/nsze", 0..8, Raw::Int),
+    ("id-ns", "/ncap", 8..16, Raw::Int),
+    ("id-ns", "/nlbaf", 25..26, Raw::Int),
+    ("smart-log", "/comp_temp", 1..3, Raw::Int),
+    ("smart-log", "/avail_spare", 3..4, Raw::Int),
+    ("smart-log", "/percent_used", 5..6, Raw::Int),
+    ("smart-log", "/data_units_read", 32..48, Raw::Int),
+    ("smart-log", "/pwr_on_hrs", 128..144, Raw::Int),
+    ("error-log", "/0/err_count", 0..8, Raw::Int),
+    ("error-log", "/0/cmd_id", 10..12, Raw::Int),
+    ("error-log", "/0/lba", 16..24, Raw::Int),
+    ("fw-log", "/frs/0", 8..16, Raw::NulStr),
+];
+
+/// Keys whose nvme-cli representation differs from ours.
+const NVME_CLI_IGNORED: &[&str] = &["psds", "rrls", "vs", "lbafs"];
+
+type Decoder = fn(&[u8]) -> Result<serde_json::Value, usize>;
+
+fn decode<T>(bytes: &[u8]) -> Result<serde_json::Value, usize>
+where
+    T: FromBytes + serde::Serialize + ?Sized,
+{
+    T::from_bytes(bytes).map(|decoded| serde_json::to_value(decoded).expect("serialize"))
+}
+
+/// Decoders keyed by the fixture file stem, named after the nvme-cli command
+/// used to capture them.
+const DECODERS: &[(&str, Decoder)] = &[
+    ("id-ctrl", decode::<IdCtrl>),
+    ("id-ns", decode::<IdNmsp>),
+    ("smart-log", decode::<SmartLog>),
+    ("error-log", decode::<[ErrLogEntry]>),
+    ("fw-log", decode::<FwSlotLog>),
+    ("cmd-effects-log", decode::<CmdEffectsLog>),
+    ("self-test-log", decode::<SelfTestLog>),
+    ("changed-ns-list-log", decode::<ChangedNmspList>),
+    ("endurance-log", decode::<EndurGrpLog>),
+    ("sanitize-log", decode::<SanitizeLog>),
+    ("resv-notif-log", decode::<ReservationNotificationLog>),
+    ("supported-log-pages", decode::<LidEffectsLog>),
+    ("fid-support-effects-log", decode::<FidEffectsLog>),
+    ("mi-cmd-support-effects-log", decode::<MiCmdEffectsLog>),
+];
+
+fn check_fixture(bin: &Path) {
+    let stem = bin.file_stem().unwrap().to_string_lossy();
+    let decoder = DECODERS
+        .iter()
+        .find(|(name, _)| *name == stem)
+        .map(|(_, decoder)| decoder)
+        .unwrap_or_else(|| panic!("no decoder for {}", bin.display()));
+    let bytes = fs::read(bin).expect("read fixture");
+    let decoded = decoder(bytes.as_slice())
+        .unwrap_or_else(|len| panic!("decode {}: unexpected length {}", bin.display(), len));
+    check_spec_fields(&stem, &bytes, &decoded, bin);
+    check_nvme_cli(&decoded, bin);
+
+    let expected_path = bin.with_extension("json");
+    if std::env::var_os(BLESS_VAR).is_some() {
+        let mut json = serde_json::to_string_pretty(&decoded).unwrap();
+        json.push('\n');
+        fs::write(&expected_path, json).expect("write expected json");
+        return;
+    }
+    let expected: serde_json::Value = fs::read(&expected_path)
+        .map(|json| serde_json::from_slice(json.as_slice()).expect("parse expected json"))
+        .unwrap_or_else(|_| panic!("missing {}", expected_path.display()));
+    assert_eq!(decoded, expected, "{}", bin.display());
+}
+
+fn check_spec_fields(stem: &str, bytes: &[u8], decoded: &serde_json::Value, bin: &Path) {
+    for (_, pointer, range, raw) in SPEC_FIELDS.iter().filter(|field| field.0 == stem) {
+        let field = &bytes[range.clone()];
+        let expected = match raw {
+            Raw::Int => {
+                let mut le = [0u8; 16];
+                le[..field.len()].copy_from_slice(field);
+                serde_json::to_value(u128::from_le_bytes(le)).unwrap()
+            }
+            Raw::Str => std::str::from_utf8(field).unwrap().into(),
+            Raw::NulStr => {
+                let end = field.iter().rposition(|&b| b != 0).map_or(0, |end| end + 1);
+                std::str::from_utf8(&field[..end]).unwrap().into()
+            }
+        };
+        assert_eq!(
+            decoded.pointer(pointer),
+            Some(&expected),
+            "{} {}",
+            bin.display(),
+            pointer
+        );
+    }
+}
+
+/// Compare against nvme-cli's own decoding of a real capture, on the keys
+/// both name the same way.
+fn check_nvme_cli(decoded: &serde_json::Value, bin: &Path) {
+    let reference_path = bin.with_extension("nvme-cli.json");
+    let reference: HashMap<String, serde_json::Value> = match fs::read(&reference_path) {
+        Ok(json) => serde_json::from_slice(json.as_slice()).expect("parse nvme-cli json"),
+        Err(_) => return,
+    };
+    let decoded = decoded
+        .as_object()
+        .expect("nvme-cli reference for a structure");
+    for (key, value) in decoded {
+        if NVME_CLI_IGNORED.contains(&key.as_str()) {
+            continue;
+        }
+        if let Some(expected) = reference.get(key) {
+            assert_eq!(value, expected, "{} {}", reference_path.display(), key);
+        }
+    }
+}
+
+/// Check every dump under `dir` and its subdirectories, returning how many
+/// were checked.
+fn check_dir(dir: &Path) -> usize {
+    let mut checked = 0;
+    for entry in fs::read_dir(dir).expect("fixture directory") {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            checked += check_dir(path.as_path());
+        } else if path.extension().is_some_and(|ext| ext == "bin") {
+            check_fixture(path.as_path());
+            checked += 1;
+        }
+    }
+    checked
+}
+
+#[test]
+fn golden_fixtures() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    assert!(check_dir(root.as_path()) > 0, "no fixtures found");
+}
@@ -32,13 +32,15 @@ fn strip_namespace<T: AsRef<str>>(dev: T) -> Option<String> {
     // occurance of 'n' from the right.
     ref_dev.find("nvme").map(|_| {
         // SAFETY: It must have at least one 'n'.
-        let ctrl = ref_dev.rsplitn(2, 'n').nth(1).unwrap();
+        let ctrl = ref_dev.rsplit_once('n').unwrap().0;
         // If the ctrl actually just split on the 'n' from
         // "nvme", then return the whole device.
         if ctrl.len() < 5 { ref_dev } else { ctrl }.to_string()
     })
 }
 
+// Skip lines which fail to read rather than stopping at the first one.
+#[allow(clippy::lines_filter_map_ok)]
 fn get_dev() -> String {
     let mounted_devs: Vec<String> = fs::File::open("/proc/mounts")
         .map(BufReader::new)
@@ -51,11 +53,11 @@ fn get_dev() -> String {
                 // Filter out non-nvme devices and strip namespaces/partitions from it.
                 .filter_map(|l| {
                     // First word of the line is the device.
-                    l.split_whitespace().nth(0).and_then(strip_namespace)
+                    l.split_whitespace().next().and_then(strip_namespace)
                 })
                 .collect()
         })
-        .unwrap_or_else(Vec::new);
+        .unwrap_or_default();
     let dev = fs::read_dir("/dev/spdk")
         .or_else(|_| fs::read_dir("/dev"))
         .unwrap()
@@ -66,7 +68,7 @@ fn get_dev() -> String {
                 && !mounted_devs.iter().any(|mounted| dev.starts_with(mounted))
         })
         .filter_map(strip_namespace)
-        .nth(0)
+        .next()
         .unwrap_or_else(|| "/dev/nvme0".to_string());
     println!("device: {}", dev);
     dev
@@ -76,7 +78,7 @@ fn get_dev() -> String {
 #[test]
 fn pull_decode_smart() {
     let output = Command::new("nvme")
-        .args(&["smart-log", "-o", "binary", get_dev().as_str()])
+        .args(["smart-log", "-o", "binary", get_dev().as_str()])
         .output()
         .expect("failed to pull smart log");
     assert!(output.status.success());
@@ -91,7 +93,7 @@ fn pull_decode_smart() {
 #[test]
 fn pull_decode_fw_log() {
     let output = Command::new("nvme")
-        .args(&["fw-log", "-o", "binary", get_dev().as_str()])
+        .args(["fw-log", "-o", "binary", get_dev().as_str()])
         .output()
         .expect("failed to pull fw log");
     assert!(output.status.success());
@@ -106,7 +108,7 @@ fn pull_decode_fw_log() {
 #[test]
 fn pull_decode_err_log() {
     let output = Command::new("nvme")
-        .args(&["error-log", "-e", "8", "-o", "binary", get_dev().as_str()])
+        .args(["error-log", "-e", "8", "-o", "binary", get_dev().as_str()])
         .output()
         .expect("failed to pull err log");
     assert!(output.status.success());
@@ -120,9 +122,12 @@ fn pull_decode_err_log() {
 #[ignore]
 #[test]
 fn pull_decode_id_ctrl() {
-    let ignored: HashSet<_> = ["psds", "rrls"].iter().map(|s| (*s).to_string()).collect();
+    let ignored: HashSet<_> = ["psds", "rrls", "vs"]
+        .iter()
+        .map(|s| (*s).to_string())
+        .collect();
     let output = Command::new("nvme")
-        .args(&["id-ctrl", "-o", "binary", get_dev().as_str()])
+        .args(["id-ctrl", "-o", "binary", get_dev().as_str()])
         .output()
         .expect("failed to pull fw log");
     assert!(output.status.success());
@@ -131,7 +136,7 @@ fn pull_decode_id_ctrl() {
     let id_ctrl_json: HashMap<String, serde_json::Value> =
         serde_json::from_str(id_ctrl_str.as_str()).unwrap();
     let output = Command::new("nvme")
-        .args(&["id-ctrl", "-o", "json", get_dev().as_str()])
+        .args(["id-ctrl", "-o", "json", get_dev().as_str()])
         .output()
         .expect("failed to pull fw log json");
     assert!(output.status.success());
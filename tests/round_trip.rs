@@ -0,0 +1,167 @@
+/*
+ * Copyright (C) 2021  Oakes, Gregory C. <gregcoakes@gmail.com>
+ * Author: Oakes, Gregory C. <gregcoakes@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or
+ * modify it under the terms of the GNU Lesser General Public
+ * License as published by the Free Software Foundation, either
+ * version 3 of the License, or any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+ * Lesser General Public License for more details.
+ *
+ * You should have received a copy of the GNU Lesser General Public
+ * License along with this program.  If not, see
+ * <http://www.gnu.org/licenses/>.
+ */
+
+use std::ops::Range;
+
+use nvme::*;
+
+/// Deterministic pseudo-random bytes with the given ranges zeroed.
+fn pattern(len: usize, seed: u32, reserved: &[Range<usize>]) -> Vec<u8> {
+    let mut state = seed;
+    let mut bytes: Vec<u8> = (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (state >> 16) as u8
+        })
+        .collect();
+    for range in reserved {
+        bytes[range.clone()].iter_mut().for_each(|b| *b = 0);
+    }
+    bytes
+}
+
+/// Clear bits which are reserved within a packed bitfield.
+fn mask(bytes: &mut [u8], mask: &[u8]) {
+    bytes.iter_mut().zip(mask).for_each(|(b, m)| *b &= m);
+}
+
+fn ascii(bytes: &mut [u8]) {
+    bytes.iter_mut().for_each(|b| *b = b'!' + *b % 94);
+}
+
+fn round_trip<T>(bytes: &[u8])
+where
+    T: FromBytes + AsBytes + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let decoded = T::from_bytes(bytes).expect("decode");
+    let json = serde_json::to_string(decoded).expect("serialize");
+    let deserialized: T = serde_json::from_str(json.as_str()).expect("deserialize");
+    assert_eq!(deserialized.as_bytes(), bytes, "{}", json);
+}
+
+#[test]
+fn round_trip_id_ctrl() {
+    let mut bytes = vec![0u8; 4096];
+    bytes[0..2].copy_from_slice(&0x144du16.to_le_bytes());
+    bytes[4..24].copy_from_slice(b"S4EWNX0R123456      ");
+    bytes[24..64].copy_from_slice(b"SAMSUNG MZVLB512HBJQ-000L7              ");
+    bytes[64..72].copy_from_slice(b"5M2QEXF7");
+    bytes[280..296].copy_from_slice(&512_110_190_592u128.to_le_bytes());
+    bytes[768..768 + 18].copy_from_slice(b"nqn.2014.08.org.nv");
+    round_trip::<IdCtrl>(bytes.as_slice());
+
+    let id_ctrl = IdCtrl::from_bytes(bytes.as_slice()).unwrap();
+    let json = serde_json::to_value(id_ctrl).unwrap();
+    assert_eq!(json["sn"], "S4EWNX0R123456      ");
+    assert_eq!(json["subnqn"], "nqn.2014.08.org.nv");
+}
+
+#[test]
+fn round_trip_id_ctrl_all_fields() {
+    let mut bytes = pattern(
+        4096,
+        1,
+        &[102..111, 134..253, 356..512, 534..536, 544..768, 1024..2048],
+    );
+    ascii(&mut bytes[4..72]);
+    ascii(&mut bytes[768..800]);
+    bytes[800..1024].iter_mut().for_each(|b| *b = 0);
+    bytes[111] = 0x01;
+    for psd in bytes[2048..3072].chunks_mut(32) {
+        mask(
+            psd,
+            &[
+                0xff, 0xff, 0x00, 0x03, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x1f, 0x1f,
+                0x1f, 0x1f, 0xff, 0xff, 0xc0, 0x00, 0xff, 0xff, 0xc7,
+            ],
+        );
+        psd[23..].iter_mut().for_each(|b| *b = 0);
+    }
+    round_trip::<IdCtrl>(bytes.as_slice());
+}
+
+#[test]
+fn round_trip_id_nmsp() {
+    let mut bytes = pattern(4096, 2, &[74..92, 96..99, 192..3072]);
+    for lbaf in bytes[128..192].chunks_mut(4) {
+        mask(lbaf, &[0xff, 0xff, 0xff, 0x03]);
+    }
+    round_trip::<IdNmsp>(bytes.as_slice());
+}
+
+#[test]
+fn round_trip_smart_log() {
+    round_trip::<SmartLog>(pattern(512, 3, &[7..32, 232..512]).as_slice());
+}
+
+#[test]
+fn round_trip_err_log() {
+    let mut bytes = pattern(64 * 4, 4, &[]);
+    for entry in bytes.chunks_mut(64) {
+        entry[15] &= 0x07;
+        entry[30..32].iter_mut().for_each(|b| *b = 0);
+        entry[42..64].iter_mut().for_each(|b| *b = 0);
+    }
+    round_trip::<ErrLogEntry>(&bytes[..64]);
+
+    let errs = <[ErrLogEntry]>::from_bytes(bytes.as_slice()).unwrap();
+    let json = serde_json::to_string(errs).unwrap();
+    let deserialized: Vec<ErrLogEntry> = serde_json::from_str(json.as_str()).unwrap();
+    assert_eq!(deserialized.as_slice().as_bytes(), bytes.as_slice());
+}
+
+#[test]
+fn round_trip_fw_slot_log() {
+    let mut bytes = vec![0u8; 512];
+    bytes[0] = 0x11;
+    bytes[8..16].copy_from_slice(b"1.0.0   ");
+    bytes[16..24].copy_from_slice(b"2.0\0\0\0\0\0");
+    round_trip::<FwSlotLog>(bytes.as_slice());
+
+    let fw_log = FwSlotLog::from_bytes(bytes.as_slice()).unwrap();
+    assert_eq!(fw_log.get_slot(0), "1.0.0   ");
+    assert_eq!(fw_log.get_slot(1), "2.0");
+    assert_eq!(fw_log.get_slot(2), "");
+}
+
+#[test]
+fn round_trip_bitfields() {
+    let psd = serde_json::json!({
+        "mp": 900, "mxps": false, "nops": false, "enlat": 5, "exlat": 5,
+        "rrt": 1, "rrl": 2, "rwt": 3, "rwl": 4, "idlp": 0, "ips": 0,
+        "actp": 0, "apw": 0, "aps": 0,
+    });
+    let decoded: PowerState = serde_json::from_value(psd.clone()).unwrap();
+    assert_eq!(decoded.rwl(), 4);
+    assert_eq!(serde_json::to_value(decoded).unwrap(), psd);
+
+    let warning = EndurGrpCritWarning::from(0b1101);
+    let json = serde_json::to_string(&warning).unwrap();
+    let decoded: EndurGrpCritWarning = serde_json::from_str(json.as_str()).unwrap();
+    assert!(decoded.spare_cap_below_thresh() && decoded.readonly());
+    assert_eq!(u8::from(decoded), 0b1101);
+
+    let sanicap = Sanicap::new().with_nodmmas(Nodmmas::Modified);
+    let json = serde_json::to_string(&sanicap.nodmmas()).unwrap();
+    assert_eq!(json, "\"modified\"");
+    assert_eq!(
+        serde_json::from_str::<SglsType>("\"supported_aligned\"").unwrap(),
+        SglsType::SupportedAligned
+    );
+}